@@ -1,7 +1,10 @@
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
-use std::{convert::Infallible, net::SocketAddr};
+use std::{convert::Infallible, fs, io, net::SocketAddr};
 
+use futures_util::{FutureExt, Stream};
+use hyper::header::{self, HeaderValue};
+use hyper::server::accept;
 use hyper::Server;
 use hyper::{
     body::Body,
@@ -9,18 +12,37 @@ use hyper::{
     service::{make_service_fn, service_fn},
 };
 use inspect::{Inspect, InspectOk};
+use opentelemetry::global;
+use opentelemetry_http::HeaderExtractor;
 use sai::{Component, ComponentLifecycle, Injected};
-use tokio::sync::oneshot;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::{oneshot, Semaphore};
+use tokio::task::JoinHandle;
+use tracing::{info_span, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use util::elapse;
 
 use crate::command::CommandSet;
-use crate::config::Config;
+use crate::config::{Config, Listen, ServerOptions};
+use crate::cors::Cors;
+use crate::entity::session::Client;
+use crate::health::Health;
+use crate::listener::{self, RemoteAddr};
+use crate::metrics;
 use crate::model::{Model, Presenter};
-use crate::msg::Msg;
+use crate::msg::{self, BodyLimit, Msg};
+use crate::openapi::{self, OpenApi};
 use crate::repository::RepositorySet;
+use crate::router;
+use crate::tls::{self, CertResolver};
 use crate::usecase::{
-    check_access_token, check_authcode, create_authcode, create_token_pair, delete_token_pair,
-    refresh_token_pair,
+    check_access_token, check_and_refresh_token_pair, check_authcode, check_service_token,
+    clear_authcodes, create_api_key, create_authcode, create_service_token, create_token_pair,
+    create_totp, delete_api_key, delete_token_pair, enable_totp, finish_passkey_authentication,
+    finish_passkey_registration, introspect_tokens, list_api_keys, list_audit_events,
+    list_sessions, notify_new_login, refresh_token_pair, revoke_session, revoke_sessions,
+    start_passkey_authentication, start_passkey_registration,
 };
 
 #[cfg_attr(test, derive(Default))]
@@ -31,15 +53,19 @@ pub struct Resolver {
 
     #[injected]
     command: Injected<CommandSet>,
-    /* #[injected]
-    config: Injected<Config>, */
+
+    #[injected]
+    config: Injected<Config>,
+
+    #[injected]
+    health: Injected<Health>,
 }
 
 impl Resolver {
     async fn resolve(&self, msg: Msg) -> crate::Result<Model> {
         let repository = Arc::clone(&self.repository);
         let command = Arc::clone(&self.command);
-        // let config = Arc::clone(&self.config);
+        let config = Arc::clone(&self.config);
 
         let model = match msg {
             Msg::CreateAuthcode(payload) => create_authcode::execute(payload, repository, command)
@@ -47,11 +73,26 @@ impl Resolver {
                 .into(),
 
             Msg::CreateTokenPair(payload) => {
-                let model = check_authcode::execute(payload.clone(), repository.clone()).await?;
-
-                create_token_pair::execute(model.into(), repository, command)
-                    .await?
-                    .into()
+                let client = payload.client.clone();
+
+                let model = check_authcode::execute(
+                    payload,
+                    repository.clone(),
+                    command.clone(),
+                    config.clone(),
+                )
+                .await?;
+
+                let model = create_token_pair::execute(
+                    (model, client.clone()).into(),
+                    repository.clone(),
+                    command.clone(),
+                )
+                .await?;
+
+                self.notify_new_login(&model, client);
+
+                model.into()
             }
 
             /* Msg::RefreshTokenPair(payload) => {
@@ -62,42 +103,277 @@ impl Resolver {
                     .await?
                     .into()
             } */
+            // service token이면 유저 대신 service client를 확인함
+            Msg::CheckAccessToken(payload) if payload.is_service_token() => {
+                check_service_token::execute(payload.into(), repository)
+                    .await?
+                    .into()
+            }
+
             Msg::CheckAccessToken(payload) => {
+                let payload = payload
+                    .with_mfa_required_role(config.mfa_required_role())
+                    .with_role_scopes(config.role_scopes());
+
                 check_access_token::execute(payload, repository, command)
                     .await?
                     .into()
             }
 
+            // 만료된 access token이면 갱신하고 role을 확인함
+            Msg::CheckAndRefreshTokenPair(payload) => {
+                let payload = payload
+                    .with_mfa_required_role(config.mfa_required_role())
+                    .with_role_scopes(config.role_scopes())
+                    .with_warn_suspicious(config.warn_suspicious_refresh());
+
+                check_and_refresh_token_pair::execute(payload, repository, command)
+                    .await?
+                    .into()
+            }
+
+            Msg::IntrospectTokens(payload) => {
+                introspect_tokens::execute(payload, repository, command)
+                    .await?
+                    .into()
+            }
+
             Msg::RefreshTokenPair(payload) => {
+                let payload = payload.with_warn_suspicious(config.warn_suspicious_refresh());
+
                 refresh_token_pair::execute(payload, repository, command)
                     .await?
                     .into()
             }
 
-            Msg::DeleteTokenPair(payload) => delete_token_pair::execute(payload, repository)
+            Msg::DeleteTokenPair(payload) => {
+                delete_token_pair::execute(payload, repository, command)
+                    .await?
+                    .into()
+            }
+
+            Msg::StartPasskeyRegistration(payload) => {
+                start_passkey_registration::execute(payload, repository, command, config)
+                    .await?
+                    .into()
+            }
+
+            Msg::FinishPasskeyRegistration(payload) => {
+                finish_passkey_registration::execute(payload, repository, command, config)
+                    .await?
+                    .into()
+            }
+
+            Msg::StartPasskeyAuthentication(payload) => {
+                start_passkey_authentication::execute(payload, repository, command, config)
+                    .await?
+                    .into()
+            }
+
+            Msg::CreateTokenPairByPasskey(payload) => {
+                let model =
+                    finish_passkey_authentication::execute(payload, repository.clone(), config)
+                        .await?;
+
+                let client = model.client.clone();
+
+                let model = create_token_pair::execute(model.into(), repository, command).await?;
+
+                self.notify_new_login(&model, client);
+
+                model.into()
+            }
+
+            Msg::CreateTotp(payload) => create_totp::execute(payload, repository, command)
+                .await?
+                .into(),
+
+            Msg::EnableTotp(payload) => enable_totp::execute(payload, repository, command)
+                .await?
+                .into(),
+
+            Msg::ConfirmRevokeSession => revoke_session::Confirmation.into(),
+
+            Msg::RevokeSession(payload) => {
+                revoke_session::execute(payload, repository, command, config)
+                    .await?
+                    .into()
+            }
+
+            Msg::ListSessions(payload) => {
+                list_sessions::execute(payload, repository, command, config)
+                    .await?
+                    .into()
+            }
+
+            Msg::RevokeSessions(payload) => {
+                revoke_sessions::execute(payload, repository, command, config)
+                    .await?
+                    .into()
+            }
+
+            Msg::ClearAuthcodes(payload) => {
+                clear_authcodes::execute(payload, repository, command, config)
+                    .await?
+                    .into()
+            }
+
+            Msg::ListAuditEvents(payload) => {
+                list_audit_events::execute(payload, repository, command, config)
+                    .await?
+                    .into()
+            }
+
+            Msg::CreateServiceToken(payload) => {
+                create_service_token::execute(payload, repository, command)
+                    .await?
+                    .into()
+            }
+
+            Msg::CreateApiKey(payload) => create_api_key::execute(payload, repository, command)
+                .await?
+                .into(),
+
+            Msg::ListApiKeys(payload) => list_api_keys::execute(payload, repository, command)
+                .await?
+                .into(),
+
+            Msg::DeleteApiKey(payload) => delete_api_key::execute(payload, repository, command)
                 .await?
                 .into(),
+
+            Msg::OpenApi => OpenApi(&openapi::SPEC).into(),
+
+            Msg::Metrics(token) => {
+                metrics::authorize(token.as_deref(), config.metrics_token().as_deref())?;
+
+                metrics::gather().into()
+            }
+
+            Msg::Liveness => self.health.liveness().into(),
+
+            Msg::Readiness => self.health.readiness().await.into(),
         };
 
         Ok(model)
     }
+
+    /// 알림 메일을 보내지 못해도 로그인은 성공해야 하고, 메일을 보낼 때까지 응답을 미루지 않음
+    fn notify_new_login(&self, model: &create_token_pair::Model, client: Client) {
+        let payload = notify_new_login::Payload {
+            user_id: model.user_id,
+            session_id: model.session_id,
+            client,
+        };
+
+        let repository = Arc::clone(&self.repository);
+        let command = Arc::clone(&self.command);
+        let config = Arc::clone(&self.config);
+
+        // user 서버에 요청할 때 같은 request id를 넘겨줌
+        let request_id = msg::CURRENT_REQUEST_ID
+            .try_with(Clone::clone)
+            .unwrap_or_default();
+
+        let notify = async move {
+            let r = notify_new_login::execute(payload, repository, command, config).await;
+
+            if let Err(err) = r {
+                log::error!("notify new login: {}", err);
+            }
+        };
+
+        tokio::spawn(
+            msg::CURRENT_REQUEST_ID
+                .scope(request_id, notify)
+                .instrument(info_span!("notify_new_login")),
+        );
+    }
 }
 
-async fn handler(request: Request<Body>, resolver: Arc<Resolver>) -> crate::Result<Response<Body>> {
+/// msg_name은 routing이 끝나면 채워짐 (metrics의 label로 사용함)
+async fn handler(
+    request: Request<Body>,
+    resolver: Arc<Resolver>,
+    cors: &Cors,
+    msg_name: &mut &'static str,
+) -> crate::Result<Response<Body>> {
+    // preflight는 routing하지 않음
+    if let Some(response) = cors.preflight(&request) {
+        *msg_name = "Preflight";
+
+        return Ok(response);
+    }
+
+    let base_path = resolver.config.base_path();
+    let path = router::strip_prefix(request.uri().path(), base_path);
+
+    resolver.config.csrf().verify(&request, path, cors)?;
+
     let response = Response::builder();
 
-    let (msg, response) = elapse!("route", Msg::from_http(request, response).await?);
+    let (msg, response) = elapse!(
+        "route",
+        Msg::from_http(request, response, base_path)
+            .instrument(info_span!("route"))
+            .await?
+    );
 
-    let model = elapse!("execute", resolver.resolve(msg).await?);
+    *msg_name = msg.name();
 
-    let response = elapse!("present", model.to_http(response));
+    let span = info_span!("execute", msg = msg.name());
+
+    let model = elapse!("execute", resolver.resolve(msg).instrument(span).await?);
+
+    let response = elapse!(
+        "present",
+        info_span!("present").in_scope(|| model.to_http(response))
+    );
 
     Ok(response)
 }
 
 async fn service(
+    mut request: Request<Body>,
+    resolver: Arc<Resolver>,
+    limits: Arc<Limits>,
+) -> Result<Response<Body>, Infallible> {
+    // msg::Wrap에서 body를 읽을 때 사용함
+    request.extensions_mut().insert(limits.body_limit);
+    // msg::client에서 X-Forwarded-For를 믿을지 정할 때 사용함
+    request
+        .extensions_mut()
+        .insert(resolver.config.trusted_proxies());
+
+    let request_id = msg::request_id(&request);
+
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        uri = %request.uri()
+    );
+
+    // gateway에서 넘어온 traceparent가 있으면 이어서 trace함
+    let parent_cx = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    span.set_parent(parent_cx);
+
+    let client = msg::client(&request);
+
+    let fut = traced_service(request, resolver, limits, request_id.clone()).instrument(span);
+
+    msg::CURRENT_REQUEST_ID
+        .scope(request_id, msg::CURRENT_CLIENT.scope(client, fut))
+        .await
+}
+
+async fn traced_service(
     request: Request<Body>,
     resolver: Arc<Resolver>,
+    limits: Arc<Limits>,
+    request_id: String,
 ) -> Result<Response<Body>, Infallible> {
     let req_method = request.method().to_owned();
     let req_uri = request.uri().to_string();
@@ -106,7 +382,33 @@ async fn service(
 
     let start = SystemTime::now();
 
-    let response = handler(request, resolver).await;
+    let mut msg_name = "Unknown";
+
+    let cors = resolver.config.cors();
+    let cookie = resolver.config.cookie();
+    let origin = request.headers().get(header::ORIGIN).cloned();
+
+    // probe와 metrics는 제한하지 않음, 바쁘다고 liveness가 실패하면 pod가 재시작됨
+    let exempt = matches!(
+        router::strip_prefix(request.uri().path(), resolver.config.base_path()),
+        "/healthz" | "/readyz" | "/metrics"
+    );
+
+    // 처리 중인 요청이 너무 많으면 기다리지 않고 바로 거절함
+    let permit = Arc::clone(&limits.requests).try_acquire_owned();
+
+    let response = match permit {
+        _ if exempt => handler(request, resolver, &cors, &mut msg_name).await,
+        Ok(_permit) => {
+            let fut = handler(request, resolver, &cors, &mut msg_name);
+
+            match tokio::time::timeout(limits.request_timeout, fut).await {
+                Ok(response) => response,
+                Err(_) => Err(crate::Error::Timeout),
+            }
+        }
+        Err(_) => Err(crate::Error::Overloaded),
+    };
 
     let end = start
         .elapsed()
@@ -114,11 +416,32 @@ async fn service(
         .map(Duration::as_micros)
         .unwrap_or(0);
 
-    match response {
-        Ok(response) => Ok(response),
-        Err(err) => Ok(err.inspect(|e| log::error!("{}", e)).into()),
+    let mut response = match response {
+        Ok(response) => response,
+        Err(err) => err
+            .inspect(|e| log::error!("{} request_id = {}", e, request_id))
+            .inspect(metrics::record_error)
+            .to_http(&request_id),
+    };
+
+    let status = response.status();
+
+    metrics::HTTP_REQUESTS
+        .with_label_values(&[msg_name, status.as_str()])
+        .inc();
+    metrics::HTTP_REQUEST_DURATION
+        .with_label_values(&[msg_name, status.as_str()])
+        .observe(end as f64 / 1_000_000.0);
+
+    if let Ok(request_id) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(msg::REQUEST_ID, request_id);
     }
-    .inspect_ok(|res| {
+
+    cors.apply(origin.as_ref(), &mut response);
+
+    cookie.apply(&mut response);
+
+    Ok(response).inspect_ok(|res| {
         log::info!(
             "<-- {} {} {} {}ms",
             req_method,
@@ -141,8 +464,13 @@ pub struct HttpServer {
     #[injected]
     config: Injected<Config>,
 
+    #[injected]
+    health: Injected<Health>,
+
     stop_sender: Option<oneshot::Sender<()>>,
     stopped_receiver: Option<oneshot::Receiver<()>>,
+
+    tls_watcher: Option<JoinHandle<()>>,
 }
 
 #[async_trait::async_trait]
@@ -156,34 +484,90 @@ impl ComponentLifecycle for HttpServer {
 
         let resolver = Arc::clone(&self.resolver);
 
-        let port = self.config.port();
-        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+        let listen = self.config.listen();
+        let options = self.config.server_options();
 
-        tokio::spawn(async move {
-            let svc = |resolver: Arc<Resolver>| async move {
-                Ok::<_, Infallible>(service_fn(move |request| {
-                    service(request, Arc::clone(&resolver))
-                }))
-            };
-
-            let server =
-                Server::bind(&addr).serve(make_service_fn(move |_| svc(Arc::clone(&resolver))));
+        let limits = Arc::new(Limits {
+            requests: Arc::new(Semaphore::new(options.max_concurrent_requests)),
+            request_timeout: options.request_timeout,
+            body_limit: BodyLimit(options.max_body_size),
+        });
 
-            let server = Server::with_graceful_shutdown(server, async {
-                stop_rx.await.unwrap();
-            });
+        // bind에 실패하면 여기서 바로 멈춤
+        let server = match &listen {
+            Listen::Tcp(addr) => {
+                let listener = TcpListener::bind(*addr).await.expect("bind tcp");
+                let incoming = listener::limit(
+                    listener::tcp(listener),
+                    options.max_connections,
+                    options.idle_timeout,
+                );
+
+                serve(incoming, resolver, limits, options, stop_rx).boxed()
+            }
+            Listen::Tls {
+                addr,
+                cert_path,
+                key_path,
+            } => {
+                let cert_resolver =
+                    CertResolver::new(cert_path, key_path).unwrap_or_else(|err| panic!("{}", err));
+                let cert_resolver = Arc::new(cert_resolver);
+
+                self.tls_watcher.replace(Arc::clone(&cert_resolver).watch());
+
+                let acceptor = tls::acceptor(cert_resolver, options.http2);
+                let listener = TcpListener::bind(*addr).await.expect("bind tcp");
+                let incoming = listener::limit(
+                    listener::tls(listener, acceptor),
+                    options.max_connections,
+                    options.idle_timeout,
+                );
+
+                serve(incoming, resolver, limits, options, stop_rx).boxed()
+            }
+            Listen::Unix(path) => {
+                // 이전에 비정상 종료되면서 남은 socket 파일
+                let _ = fs::remove_file(path);
+
+                let listener = UnixListener::bind(path).expect("bind unix socket");
+                let incoming = listener::limit(
+                    listener::unix(listener),
+                    options.max_connections,
+                    options.idle_timeout,
+                );
+
+                serve(incoming, resolver, limits, options, stop_rx).boxed()
+            }
+        };
 
-            log::info!("started http server: 0.0.0.0:{}", port);
+        tokio::spawn(async move {
+            log::info!("started http server: {}", listen);
 
             if let Err(err) = server.await {
                 log::error!("{:?}", err);
             }
 
+            if let Listen::Unix(path) = &listen {
+                let _ = fs::remove_file(path);
+            }
+
             stopped_tx.send(()).unwrap();
         });
     }
 
     async fn stop(&mut self) {
+        // readiness가 실패하고 k8s가 endpoint에서 뺄 때까지 기다림
+        self.health.shutdown();
+
+        let shutdown_delay = self.config.shutdown_delay();
+
+        if !shutdown_delay.is_zero() {
+            log::info!("wait {:?} before stop http server", shutdown_delay);
+
+            tokio::time::sleep(shutdown_delay).await;
+        }
+
         let stop_tx = self.stop_sender.take().unwrap();
 
         stop_tx.send(()).unwrap();
@@ -191,105 +575,362 @@ impl ComponentLifecycle for HttpServer {
         let stopped_rx = self.stopped_receiver.take().unwrap();
 
         stopped_rx.await.unwrap();
+
+        if let Some(tls_watcher) = self.tls_watcher.take() {
+            tls_watcher.abort();
+        }
     }
 }
 
-/* #[cfg(test)]
-mod tests {
-    use std::{convert::Infallible, fs, sync::Arc};
+/// 요청마다 공유하는 제한
+struct Limits {
+    requests: Arc<Semaphore>,
+    request_timeout: Duration,
+    body_limit: BodyLimit,
+}
 
-    use hyper::{
-        service::{make_service_fn, service_fn},
-        Client,
+async fn serve<IO>(
+    incoming: impl Stream<Item = io::Result<IO>> + Send + 'static,
+    resolver: Arc<Resolver>,
+    limits: Arc<Limits>,
+    options: ServerOptions,
+    stop_rx: oneshot::Receiver<()>,
+) -> hyper::Result<()>
+where
+    IO: AsyncRead + AsyncWrite + RemoteAddr + Unpin + Send + 'static,
+{
+    let svc = |resolver: Arc<Resolver>, limits: Arc<Limits>, remote_addr: Option<SocketAddr>| async move {
+        Ok::<_, Infallible>(service_fn(move |mut request: Request<Body>| {
+            // msg::client에서 클라이언트의 주소로 사용함
+            if let Some(remote_addr) = remote_addr {
+                request.extensions_mut().insert(remote_addr);
+            }
+
+            service(request, Arc::clone(&resolver), Arc::clone(&limits))
+        }))
     };
-    use hyperlocal::{UnixClientExt, UnixServerExt};
-    use sai::{Component, ComponentLifecycle, Injected};
-    use util::test_registry;
+
+    let server = Server::builder(accept::from_stream(Box::pin(incoming)))
+        .http1_only(!options.http2)
+        .http1_keepalive(options.keep_alive)
+        .http1_header_read_timeout(options.header_read_timeout)
+        .serve(make_service_fn(move |conn: &IO| {
+            svc(
+                Arc::clone(&resolver),
+                Arc::clone(&limits),
+                conn.remote_addr(),
+            )
+        }));
+
+    Server::with_graceful_shutdown(server, async {
+        stop_rx.await.unwrap();
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use hyper::{header, Body, Method, Request, Response, StatusCode};
+    use madome_sdk::api::cookie::{MADOME_ACCESS_TOKEN, MADOME_REFRESH_TOKEN};
+    use madome_sdk::api::user::model::User;
+    use sai::Injected;
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+    use tokio::sync::{oneshot, Semaphore};
     use uuid::Uuid;
 
-    use crate::{
-        command::{self, CommandSet},
-        entity::token::Token,
-        json::user::UserInfo,
-        repository::RepositorySet,
-    };
+    use crate::command::{self, CommandSet};
+    use crate::config::{Config, ServerOptions};
+    use crate::entity::token::{amr, jwt, AccessToken, RefreshToken, Token, ACCESS_TOKEN_EXP};
+    use crate::listener;
+    use crate::model::Presenter;
+    use crate::msg::{BodyLimit, Msg};
+    use crate::repository::r#trait::SecretKeyRepository;
+    use crate::usecase::check_and_refresh_token_pair;
+
+    use super::{serve, Limits, Resolver};
+
+    fn resolver(config: Config, role: u8, user_id: Uuid) -> Resolver {
+        let mut command = CommandSet::default();
+
+        command.set_get_user_info(command::tests::GetUser::from(User {
+            id: user_id,
+            email: "".to_string(),
+            role,
+            name: "".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }));
+
+        Resolver {
+            command: Injected::new(command),
+            config: Injected::new(config),
+            ..Default::default()
+        }
+    }
 
-    use super::{service, Resolver};
+    /// expired이면 access token만 만료됨
+    async fn token_pair(resolver: &Resolver, token: Token, expired: bool) -> (String, String) {
+        let secret_key = "secret1234";
 
-    const SOCKET_PATH: &str = "aaaaa";
+        resolver
+            .repository
+            .secret_key()
+            .add(token.id, secret_key)
+            .await
+            .unwrap();
 
-    struct MockHttpServer {
-        pub resolver: Injected<Resolver>,
+        let mut access_token = AccessToken::from(token.clone());
+
+        if expired {
+            access_token.exp = Utc::now().timestamp() - ACCESS_TOKEN_EXP - 30;
+        }
+
+        (
+            jwt::serialize(&access_token, secret_key).unwrap(),
+            jwt::serialize(&RefreshToken::from(token), secret_key).unwrap(),
+        )
+    }
+
+    fn check_and_refresh(access_token: &str, refresh_token: &str, query: &str) -> Msg {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("/auth/token/check{}", query))
+            .header(
+                header::COOKIE,
+                format!(
+                    "{}={}; {}={}",
+                    MADOME_ACCESS_TOKEN, access_token, MADOME_REFRESH_TOKEN, refresh_token
+                ),
+            )
+            .body(Body::empty())
+            .unwrap();
+
+        Msg::CheckAndRefreshTokenPair(
+            check_and_refresh_token_pair::Payload::try_from(request).unwrap(),
+        )
     }
 
-    impl MockHttpServer {
-        async fn start(&self, socket_path: &str) {
-            let resolver = Arc::clone(&self.resolver);
+    fn sets_cookies(response: &Response<Body>) -> bool {
+        response
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .any(|x| x.to_str().unwrap().starts_with(MADOME_ACCESS_TOKEN))
+    }
+
+    #[tokio::test]
+    async fn check_and_refresh_sets_cookies() {
+        let user_id = Uuid::new_v4();
+        let resolver = resolver(Config::default(), 0, user_id);
 
-            tokio::spawn(async move {
-                let svc = |resolver: Arc<Resolver>| async move {
-                    Ok::<_, Infallible>(service_fn(move |request| {
-                        service(request, Arc::clone(&resolver))
-                    }))
-                };
+        let (access_token, refresh_token) = token_pair(&resolver, Token::new(user_id), true).await;
 
-                fs::create_dir_all("./.temp").expect("create dir");
+        let model = resolver
+            .resolve(check_and_refresh(&access_token, &refresh_token, ""))
+            .await
+            .unwrap();
 
-                let server = hyper::Server::bind_unix(format!("./.temp/{}", socket_path))
-                    .unwrap()
-                    .serve(make_service_fn(move |_| svc(Arc::clone(&resolver))));
+        let response = model.to_http(Response::builder());
 
-                if let Err(err) = server.await {
-                    panic!("{:?}", err);
-                }
-            });
-        }
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(sets_cookies(&response));
     }
 
     #[tokio::test]
-    async fn t() {
-        use crate::repository::r#trait::SecretKeyRepository;
-
-        test_registry!(
-        [(Injected) -> repository: RepositorySet, command: CommandSet] ->
-        [auth_socket_path: &str, user_id: Uuid, secret_key: String, token: Token] ->
-        {
-            user_id = Uuid::new_v4().to_string();
-            secret_key = "S3Cr#tK3y".to_string();
-            token = Token::new(user_id.clone());
-
-            repository
-                .secret_key()
-                .add(&token.id, &secret_key)
+    async fn check_and_refresh_permission_denied() {
+        let user_id = Uuid::new_v4();
+        let resolver = resolver(Config::default(), 0, user_id);
+
+        let (access_token, refresh_token) = token_pair(&resolver, Token::new(user_id), true).await;
+
+        let err = resolver
+            .resolve(check_and_refresh(&access_token, &refresh_token, "?role=1"))
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(err.code(), "permission_denied");
+
+        // 이전 token pair는 이미 갱신됐으므로 거절하더라도 새 token pair를 돌려줌
+        let response = err.to_http("request-id");
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(sets_cookies(&response));
+    }
+
+    #[tokio::test]
+    async fn check_and_refresh_requires_mfa() {
+        let user_id = Uuid::new_v4();
+
+        let mut config = Config::default();
+        config.set_mfa_required_role(1);
+
+        let resolver = resolver(config, 1, user_id);
+
+        for expired in [false, true] {
+            let (access_token, refresh_token) =
+                token_pair(&resolver, Token::new(user_id), expired).await;
+
+            let err = resolver
+                .resolve(check_and_refresh(&access_token, &refresh_token, ""))
                 .await
+                .err()
                 .unwrap();
 
-            let get_user_info = command::tests::GetUserInfo::from(UserInfo {
-                id: user_id.clone(),
-                email: "".to_string(),
-                role: 0,
-            });
+            assert_eq!(err.code(), "required_mfa");
 
-            command.set_get_user_info(get_user_info);
-        },
-        {
-            let resolver = Resolver {
-                repository,
-                command,
-            };
-            let mock_http_server = MockHttpServer {
-                resolver: Injected::new(resolver)
-            };
+            let response = err.to_http("request-id");
 
-            tokio::spawn(async move {
-                mock_http_server.start().await;
-            });
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            assert_eq!(sets_cookies(&response), expired);
+        }
 
-            /* test code */
+        let token = Token::new(user_id).with_amr(vec![amr::MFA.to_string()]);
+        let (access_token, refresh_token) = token_pair(&resolver, token, true).await;
 
-            let client = Client::unix();
+        let model = resolver
+            .resolve(check_and_refresh(&access_token, &refresh_token, ""))
+            .await
+            .unwrap();
 
-            client.get()
-        });
+        assert!(sets_cookies(&model.to_http(Response::builder())));
+    }
+
+    fn limits(options: &ServerOptions) -> Arc<Limits> {
+        Arc::new(Limits {
+            requests: Arc::new(Semaphore::new(options.max_concurrent_requests)),
+            request_timeout: options.request_timeout,
+            body_limit: BodyLimit(options.max_body_size),
+        })
+    }
+
+    async fn get(mut io: impl AsyncRead + AsyncWrite + Unpin, path: &str) -> String {
+        let request = format!(
+            "GET {} HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n",
+            path
+        );
+
+        io.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        io.read_to_string(&mut response).await.unwrap();
+
+        response
+    }
+
+    #[tokio::test]
+    async fn tcp_round_trip() {
+        let options = ServerOptions::default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = listener::limit(
+            listener::tcp(listener),
+            options.max_connections,
+            options.idle_timeout,
+        );
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        let server = tokio::spawn(serve(
+            incoming,
+            Arc::new(Resolver::default()),
+            limits(&options),
+            options,
+            stop_rx,
+        ));
+
+        let response = get(TcpStream::connect(addr).await.unwrap(), "/healthz").await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.ends_with(r#"{"status":"ok"}"#), "{}", response);
+
+        stop_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
     }
-} */
+
+    #[tokio::test]
+    async fn exempt_probes_when_overloaded() {
+        let options = ServerOptions::default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = listener::limit(
+            listener::tcp(listener),
+            options.max_connections,
+            options.idle_timeout,
+        );
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        let server = tokio::spawn(serve(
+            incoming,
+            Arc::new(Resolver::default()),
+            Arc::new(Limits {
+                requests: Arc::new(Semaphore::new(0)),
+                request_timeout: options.request_timeout,
+                body_limit: BodyLimit(options.max_body_size),
+            }),
+            options,
+            stop_rx,
+        ));
+
+        // prefix가 붙어도 probe는 제한하지 않음
+        for path in ["/healthz", "/v1/healthz"] {
+            let response = get(TcpStream::connect(addr).await.unwrap(), path).await;
+
+            assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        }
+
+        let response = get(TcpStream::connect(addr).await.unwrap(), "/v1/auth/token").await;
+
+        assert!(response.starts_with("HTTP/1.1 503"), "{}", response);
+
+        stop_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn unix_round_trip() {
+        let options = ServerOptions::default();
+
+        let path = std::env::temp_dir().join(format!("madome-auth-{}.sock", Uuid::new_v4()));
+
+        let listener = UnixListener::bind(&path).unwrap();
+        let incoming = listener::limit(
+            listener::unix(listener),
+            options.max_connections,
+            options.idle_timeout,
+        );
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        let server = tokio::spawn(serve(
+            incoming,
+            Arc::new(Resolver::default()),
+            limits(&options),
+            options,
+            stop_rx,
+        ));
+
+        let response = get(UnixStream::connect(&path).await.unwrap(), "/healthz").await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.ends_with(r#"{"status":"ok"}"#), "{}", response);
+
+        // 없는 route도 같은 경로로 응답함
+        let response = get(UnixStream::connect(&path).await.unwrap(), "/nope").await;
+
+        assert!(response.starts_with("HTTP/1.1 404"), "{}", response);
+
+        stop_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
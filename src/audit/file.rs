@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt,
+    sync::Mutex,
+};
+
+use crate::{entity::audit::AuditEvent, error::AuditError};
+
+use super::AuditSink;
+
+/// JSON lines 형식으로 파일 끝에 덧붙임
+pub struct FileAuditSink {
+    file: Mutex<File>,
+}
+
+impl FileAuditSink {
+    pub async fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(AuditError::from)?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for FileAuditSink {
+    async fn write(&self, event: &AuditEvent) -> crate::Result<()> {
+        let mut line = serde_json::to_vec(event).map_err(AuditError::from)?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().await;
+
+        file.write_all(&line).await.map_err(AuditError::from)?;
+        file.flush().await.map_err(AuditError::from)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use uuid::Uuid;
+
+    use crate::entity::audit::{AuditEvent, AuditKind};
+
+    use super::{AuditSink, FileAuditSink};
+
+    #[tokio::test]
+    async fn append_json_lines() {
+        let path = env::temp_dir().join(format!("audit-{}.jsonl", Uuid::new_v4()));
+
+        let sink = FileAuditSink::open(&path).await.unwrap();
+
+        let user_id = Uuid::new_v4();
+
+        sink.write(&AuditEvent::new(AuditKind::AuthcodeRequested).user_id(user_id))
+            .await
+            .unwrap();
+        sink.write(&AuditEvent::new(AuditKind::AuthcodeFailed).user_id(user_id))
+            .await
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let events = content
+            .lines()
+            .map(|line| serde_json::from_str::<AuditEvent>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, AuditKind::AuthcodeRequested);
+        assert_eq!(events[1].kind, AuditKind::AuthcodeFailed);
+        assert_eq!(events[1].user_id, Some(user_id));
+    }
+}
@@ -0,0 +1,44 @@
+mod file;
+mod redis;
+mod stdout;
+
+pub use self::redis::RedisAuditSink;
+pub use file::FileAuditSink;
+pub use stdout::StdoutAuditSink;
+
+use std::str::FromStr;
+
+use crate::entity::audit::AuditEvent;
+
+/// AUDIT_SINK
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditSinkKind {
+    Redis,
+    File,
+    Stdout,
+}
+
+impl Default for AuditSinkKind {
+    fn default() -> Self {
+        Self::Stdout
+    }
+}
+
+impl FromStr for AuditSinkKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "redis" => Ok(Self::Redis),
+            "file" => Ok(Self::File),
+            "stdout" => Ok(Self::Stdout),
+            _ => Err(format!("unknown audit sink: {}", s)),
+        }
+    }
+}
+
+/// 인증 관련 event를 어딘가에 덧붙이기만 함
+#[async_trait::async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn write(&self, event: &AuditEvent) -> crate::Result<()>;
+}
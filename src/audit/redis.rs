@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use crate::{database::DatabaseSet, entity::audit::AuditEvent, error::AuditError};
+
+use super::AuditSink;
+
+/// 전체 stream에 남기는 최대 개수 (대략)
+pub const STREAM_MAX_LEN: usize = 100_000;
+
+/// 유저별 stream에 남기는 최대 개수 (대략)
+pub const USER_STREAM_MAX_LEN: usize = 1_000;
+
+/// `audit` stream과 유저별 `audit:{user_id}` stream에 XADD함
+pub struct RedisAuditSink {
+    database: Arc<DatabaseSet>,
+}
+
+impl RedisAuditSink {
+    pub fn new(database: Arc<DatabaseSet>) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for RedisAuditSink {
+    async fn write(&self, event: &AuditEvent) -> crate::Result<()> {
+        let mut redis = self.database.redis().await?;
+
+        let serialized = serde_json::to_string(event).map_err(AuditError::from)?;
+
+        let mut pipe = redis::pipe();
+
+        pipe.cmd("XADD")
+            .arg("audit")
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(STREAM_MAX_LEN)
+            .arg("*")
+            .arg("event")
+            .arg(&serialized)
+            .ignore();
+
+        if let Some(user_id) = event.user_id {
+            pipe.cmd("XADD")
+                .arg(format!("audit:{}", user_id))
+                .arg("MAXLEN")
+                .arg("~")
+                .arg(USER_STREAM_MAX_LEN)
+                .arg("*")
+                .arg("event")
+                .arg(&serialized)
+                .ignore();
+        }
+
+        pipe.query_async::<_, ()>(&mut redis).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use tokio::{
+    io::{self, AsyncWriteExt, Stdout},
+    sync::Mutex,
+};
+
+use crate::{entity::audit::AuditEvent, error::AuditError};
+
+use super::AuditSink;
+
+/// 한 줄에 하나씩 json으로 출력함, 로그 수집기가 따로 모으는 환경에서 사용함
+pub struct StdoutAuditSink {
+    /// 동시에 쓰더라도 줄이 섞이지 않게 함
+    stdout: Mutex<Stdout>,
+}
+
+impl Default for StdoutAuditSink {
+    fn default() -> Self {
+        Self {
+            stdout: Mutex::new(io::stdout()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for StdoutAuditSink {
+    async fn write(&self, event: &AuditEvent) -> crate::Result<()> {
+        let mut line = serde_json::to_vec(event).map_err(AuditError::from)?;
+        line.push(b'\n');
+
+        let mut stdout = self.stdout.lock().await;
+
+        stdout.write_all(&line).await.map_err(AuditError::from)?;
+        stdout.flush().await.map_err(AuditError::from)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use std::sync::atomic::Ordering;
+
+use madome_auth::{cli, config, telemetry, CliRegistry};
+use sai::System;
+
+/// 명령어는 Cli component가 시작될 때 실행됨
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+
+    config::export_env();
+
+    telemetry::init("warn");
+
+    let mut system = System::<CliRegistry>::new();
+
+    system.start().await;
+
+    system.stop().await;
+
+    telemetry::shutdown();
+
+    std::process::exit(cli::EXIT_CODE.load(Ordering::SeqCst));
+}
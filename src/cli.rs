@@ -0,0 +1,627 @@
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use either::Either;
+use sai::{Component, ComponentLifecycle, Injected};
+use serde::{Deserialize, Serialize};
+use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use uuid::Uuid;
+
+use crate::{
+    command::CommandSet,
+    entity::{
+        audit::{AuditEvent, AuditKind},
+        service_client::ServiceClient,
+        session::{Client, Session},
+        token::{AccessToken, RefreshToken, Token},
+    },
+    repository::{
+        r#trait::{SecretKeyRepository, ServiceClientRepository, SessionRepository},
+        RepositorySet,
+    },
+    usecase::create_token_pair,
+};
+
+pub const USAGE: &str = "\
+Usage: madome-auth-admin <command>
+
+Commands:
+    revoke <user_id|email>      revoke all tokens of the user
+    inspect <token>             decode the token and check its secret key
+    mint <user_id>              issue a token pair for debugging
+    purge [--dry-run] [--secret-keys]
+                                remove sessions whose secret key is gone,
+                                --secret-keys also removes secret keys without a session
+                                (this revokes tokens issued before sessions existed)
+    export [file]               write sessions with their secret keys as json lines
+    import [file]               read sessions written by export
+    service-client add <name> [scope,...]
+                                register a service client and print its secret
+    service-client remove <client_id>
+    service-client list
+";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}")]
+    Service(#[from] crate::Error),
+    #[error("Io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Json: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// main에서 process의 exit code로 사용함
+pub static EXIT_CODE: AtomicI32 = AtomicI32::new(0);
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Revoke(Either<Uuid, String>),
+    Inspect(String),
+    Mint(Uuid),
+    Purge {
+        dry_run: bool,
+        /// session이 없는 secret key도 지움
+        secret_keys: bool,
+    },
+    /// None이면 stdout
+    Export(Option<String>),
+    /// None이면 stdin
+    Import(Option<String>),
+    AddServiceClient {
+        name: String,
+        scopes: Vec<String>,
+    },
+    RemoveServiceClient(String),
+    ListServiceClients,
+}
+
+impl Command {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> std::result::Result<Self, String> {
+        let mut args = args.into_iter();
+
+        let command = args.next().ok_or("missing command")?;
+        let arg = args.next();
+
+        let required = |name: &str| arg.clone().ok_or(format!("missing <{}>", name));
+
+        let command = match command.as_str() {
+            "revoke" => {
+                let user = required("user_id|email")?;
+
+                match Uuid::from_str(&user) {
+                    Ok(user_id) => Self::Revoke(Either::Left(user_id)),
+                    Err(_) => Self::Revoke(Either::Right(user)),
+                }
+            }
+            "inspect" => Self::Inspect(required("token")?),
+            "mint" => {
+                let user_id = required("user_id")?;
+
+                Self::Mint(user_id.parse().map_err(|_| "invalid <user_id>")?)
+            }
+            "purge" => {
+                let mut dry_run = false;
+                let mut secret_keys = false;
+
+                for flag in arg.into_iter().chain(args.by_ref()) {
+                    match flag.as_str() {
+                        "--dry-run" => dry_run = true,
+                        "--secret-keys" => secret_keys = true,
+                        x => return Err(format!("unknown purge option: {}", x)),
+                    }
+                }
+
+                Self::Purge {
+                    dry_run,
+                    secret_keys,
+                }
+            }
+            "export" => Self::Export(arg),
+            "import" => Self::Import(arg),
+            "service-client" => match arg.as_deref() {
+                Some("add") => Self::AddServiceClient {
+                    name: args.next().ok_or("missing <name>")?,
+                    scopes: args
+                        .next()
+                        .map(|x| {
+                            x.split(',')
+                                .filter(|x| !x.is_empty())
+                                .map(|x| x.to_string())
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                },
+                Some("remove") => {
+                    Self::RemoveServiceClient(args.next().ok_or("missing <client_id>")?)
+                }
+                Some("list") => Self::ListServiceClients,
+                _ => return Err("unknown service-client command".to_string()),
+            },
+            x => return Err(format!("unknown command: {}", x)),
+        };
+
+        Ok(command)
+    }
+}
+
+/// create_token_pair는 secret key를 쓴 뒤에 session을 씀
+///
+/// 그 사이에 purge가 secret key를 지우지 않게 session이 없는 secret key는 이만큼 기다렸다가 다시 확인함
+const SECRET_KEY_GRACE: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default, PartialEq)]
+struct Purged {
+    secret_keys: usize,
+    sessions: usize,
+}
+
+/// secret key가 없는 session은 이미 revoke된 것이므로 지움
+///
+/// session이 없는 secret key는 로그인하는 중이거나 session이 생기기 전에 발급된 token일 수 있어서
+/// secret_keys일 때만 지움
+async fn purge(
+    repository: &RepositorySet,
+    dry_run: bool,
+    secret_keys: bool,
+    grace: Duration,
+) -> Result<Purged> {
+    let secret_key_repository = repository.secret_key();
+    let session_repository = repository.session();
+
+    let mut purged = Purged::default();
+
+    if secret_keys {
+        let mut candidates = Vec::new();
+
+        for token_id in secret_key_repository.token_ids().await? {
+            if session_repository.get(token_id).await?.is_none() {
+                candidates.push(token_id);
+            }
+        }
+
+        if !candidates.is_empty() {
+            tokio::time::sleep(grace).await;
+        }
+
+        for token_id in candidates {
+            if session_repository.get(token_id).await?.is_some() {
+                continue;
+            }
+
+            if !dry_run {
+                secret_key_repository.remove(token_id).await?;
+            }
+            purged.secret_keys += 1;
+        }
+    }
+
+    for session in session_repository.all().await? {
+        if secret_key_repository.get(session.token_id).await?.is_none() {
+            if !dry_run {
+                session_repository.remove(session.token_id).await?;
+            }
+            purged.sessions += 1;
+        }
+    }
+
+    Ok(purged)
+}
+
+/// export, import할 때 한 줄에 하나씩 씀
+#[derive(Serialize, Deserialize)]
+struct SessionRecord {
+    session: Session,
+    secret_key: String,
+}
+
+/// server와 같은 RepositorySet을 사용하는 운영용 명령어
+///
+/// 다른 component가 모두 시작된 뒤에 명령어를 실행함
+#[derive(Component)]
+#[lifecycle]
+pub struct Cli {
+    #[injected]
+    repository: Injected<RepositorySet>,
+
+    #[injected]
+    command: Injected<CommandSet>,
+}
+
+#[async_trait::async_trait]
+impl ComponentLifecycle for Cli {
+    async fn start(&mut self) {
+        let command = match Command::parse(std::env::args().skip(1)) {
+            Ok(command) => command,
+            Err(err) => {
+                eprintln!("{}\n\n{}", err, USAGE);
+                EXIT_CODE.store(2, Ordering::SeqCst);
+                return;
+            }
+        };
+
+        if let Err(err) = self.run(command).await {
+            eprintln!("error: {}", err);
+            EXIT_CODE.store(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Cli {
+    async fn run(&self, command: Command) -> Result<()> {
+        match command {
+            Command::Revoke(user) => self.revoke(user).await,
+            Command::Inspect(token) => self.inspect(&token).await,
+            Command::Mint(user_id) => self.mint(user_id).await,
+            Command::Purge {
+                dry_run,
+                secret_keys,
+            } => self.purge(dry_run, secret_keys).await,
+            Command::Export(path) => self.export(path).await,
+            Command::Import(path) => self.import(path).await,
+            Command::AddServiceClient { name, scopes } => {
+                self.add_service_client(name, scopes).await
+            }
+            Command::RemoveServiceClient(client_id) => self.remove_service_client(&client_id).await,
+            Command::ListServiceClients => self.list_service_clients().await,
+        }
+    }
+
+    async fn revoke(&self, user: Either<Uuid, String>) -> Result<()> {
+        // user id를 알고 있으면 user 서버에 물어보지 않음 (탈퇴한 유저도 지울 수 있게)
+        let user_id = match user {
+            Either::Left(user_id) => user_id,
+            Either::Right(email) => self.command.get_user_info(Either::Right(email)).await?.id,
+        };
+
+        let sessions = self.repository.session().list(user_id).await?;
+
+        for session in &sessions {
+            self.repository
+                .secret_key()
+                .remove(session.token_id)
+                .await?;
+            self.repository.session().remove(session.token_id).await?;
+
+            self.command
+                .audit(
+                    AuditEvent::new(AuditKind::TokenPairRevoked)
+                        .user_id(user_id)
+                        .token_id(session.token_id)
+                        .detail("admin cli"),
+                )
+                .await;
+        }
+
+        println!("revoked {} session(s) of {}", sessions.len(), user_id);
+
+        Ok(())
+    }
+
+    async fn inspect(&self, token: &str) -> Result<()> {
+        let (token_id, payload, kind) = if let Some(x) = AccessToken::deserialize_payload(token) {
+            (x.id, serde_json::to_value(&x), "access")
+        } else if let Some(x) = RefreshToken::deserialize_payload(token) {
+            (x.id, serde_json::to_value(&x), "refresh")
+        } else {
+            println!("invalid token");
+            return Ok(());
+        };
+
+        let secret_key = self.repository.secret_key().get(token_id).await?;
+
+        // 서명만 확인하고 만료는 payload의 exp로 판단함
+        let valid_signature = secret_key
+            .as_ref()
+            .map(|secret_key| Token::deserialize::<serde_json::Value>(token, secret_key, false))
+            .map(|x| x.is_some());
+
+        let session = self.repository.session().get(token_id).await?;
+
+        let report = serde_json::json!({
+            "kind": kind,
+            "payload": payload.expect("json serialize"),
+            "secret_key_exists": secret_key.is_some(),
+            "valid_signature": valid_signature,
+            "session": session,
+        });
+
+        println!("{:#}", report);
+
+        Ok(())
+    }
+
+    async fn mint(&self, user_id: Uuid) -> Result<()> {
+        let payload = create_token_pair::Payload {
+            subject: create_token_pair::Subject::UserId(user_id),
+            client: Client {
+                user_agent: None,
+                ip: None,
+                device_name: Some("madome-auth-admin".to_string()),
+            },
+        };
+
+        let model = create_token_pair::execute(
+            payload,
+            Arc::clone(&self.repository),
+            Arc::clone(&self.command),
+        )
+        .await?;
+
+        println!("token_id      = {}", model.token_id);
+        println!("access_token  = {}", model.access_token);
+        println!("refresh_token = {}", model.refresh_token);
+
+        Ok(())
+    }
+
+    async fn purge(&self, dry_run: bool, secret_keys: bool) -> Result<()> {
+        let purged = purge(&self.repository, dry_run, secret_keys, SECRET_KEY_GRACE).await?;
+
+        println!(
+            "{} {} secret key(s) and {} session(s)",
+            if dry_run { "would purge" } else { "purged" },
+            purged.secret_keys,
+            purged.sessions
+        );
+
+        Ok(())
+    }
+
+    async fn export(&self, path: Option<String>) -> Result<()> {
+        let mut out: Box<dyn io::AsyncWrite + Unpin + Send> = match path {
+            Some(path) => Box::new(tokio::fs::File::create(path).await?),
+            None => Box::new(io::stdout()),
+        };
+
+        let mut exported = 0;
+
+        for session in self.repository.session().all().await? {
+            let secret_key = match self.repository.secret_key().get(session.token_id).await? {
+                Some(secret_key) => secret_key.0,
+                None => continue,
+            };
+
+            let mut line = serde_json::to_vec(&SessionRecord {
+                session,
+                secret_key,
+            })
+            .expect("json serialize");
+            line.push(b'\n');
+
+            out.write_all(&line).await?;
+            exported += 1;
+        }
+
+        out.flush().await?;
+
+        eprintln!("exported {} session(s)", exported);
+
+        Ok(())
+    }
+
+    async fn import(&self, path: Option<String>) -> Result<()> {
+        let input: Box<dyn io::AsyncRead + Unpin + Send> = match path {
+            Some(path) => Box::new(tokio::fs::File::open(path).await?),
+            None => Box::new(io::stdin()),
+        };
+
+        let mut lines = BufReader::new(input).lines();
+        let mut imported = 0;
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let SessionRecord {
+                session,
+                secret_key,
+            } = serde_json::from_str(&line)?;
+
+            self.repository
+                .secret_key()
+                .add(session.token_id, &secret_key)
+                .await?;
+            self.repository.session().add(&session).await?;
+
+            imported += 1;
+        }
+
+        eprintln!("imported {} session(s)", imported);
+
+        Ok(())
+    }
+
+    async fn add_service_client(&self, name: String, scopes: Vec<String>) -> Result<()> {
+        let (client, secret) = ServiceClient::new(name, scopes);
+
+        self.repository.service_client().add(&client).await?;
+
+        // secret은 hash만 저장하니 지금이 아니면 다시 볼 수 없음
+        println!("client_id     = {}", client.id);
+        println!("client_secret = {}", secret);
+        println!("scopes        = {}", client.scopes.join(" "));
+
+        Ok(())
+    }
+
+    async fn remove_service_client(&self, client_id: &str) -> Result<()> {
+        let removed = self.repository.service_client().remove(client_id).await?;
+
+        println!("{}", if removed { "removed" } else { "not found" });
+
+        Ok(())
+    }
+
+    async fn list_service_clients(&self) -> Result<()> {
+        for client in self.repository.service_client().list().await? {
+            println!(
+                "{}\t{}\t{}",
+                client.id,
+                client.name,
+                client.scopes.join(" ")
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use either::Either;
+    use sai::{Component, System};
+    use util::test_registry;
+    use uuid::Uuid;
+
+    use crate::entity::session::{Client, Session};
+    use crate::repository::{
+        r#trait::{SecretKeyRepository, SessionRepository},
+        RepositorySet,
+    };
+
+    use super::{purge, Command, Purged};
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        Command::parse(args.iter().map(|x| x.to_string()))
+    }
+
+    #[test]
+    fn parse_command() {
+        let user_id = Uuid::new_v4();
+
+        assert_eq!(
+            parse(&["revoke", &user_id.to_string()]),
+            Ok(Command::Revoke(Either::Left(user_id)))
+        );
+        assert_eq!(
+            parse(&["revoke", "user@madome.app"]),
+            Ok(Command::Revoke(Either::Right(
+                "user@madome.app".to_string()
+            )))
+        );
+        assert_eq!(
+            parse(&["purge", "--dry-run"]),
+            Ok(Command::Purge {
+                dry_run: true,
+                secret_keys: false
+            })
+        );
+        assert_eq!(
+            parse(&["purge", "--secret-keys", "--dry-run"]),
+            Ok(Command::Purge {
+                dry_run: true,
+                secret_keys: true
+            })
+        );
+        assert!(parse(&["purge", "--all"]).is_err());
+        assert_eq!(parse(&["export"]), Ok(Command::Export(None)));
+        assert_eq!(
+            parse(&[
+                "service-client",
+                "add",
+                "madome-library",
+                "library:read,users:read"
+            ]),
+            Ok(Command::AddServiceClient {
+                name: "madome-library".to_string(),
+                scopes: vec!["library:read".to_string(), "users:read".to_string()]
+            })
+        );
+
+        assert!(parse(&["mint", "not-uuid"]).is_err());
+        assert!(parse(&["inspect"]).is_err());
+        assert!(parse(&[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn purge_orphaned_sessions() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet] ->
+        [paired: Session, revoked: Session, legacy: Uuid] ->
+        {
+            let user_id = Uuid::new_v4();
+
+            paired = Session::new(Uuid::new_v4(), user_id, Client::default());
+            revoked = Session::new(Uuid::new_v4(), user_id, Client::default());
+            // session이 생기기 전에 발급된 token
+            legacy = Uuid::new_v4();
+
+            repository.secret_key().add(paired.token_id, "secret1234").await.unwrap();
+            repository.session().add(&paired).await.unwrap();
+            repository.session().add(&revoked).await.unwrap();
+            repository.secret_key().add(legacy, "secret1234").await.unwrap();
+        },
+        {
+            let r = purge(&repository, true, false, Duration::ZERO).await.unwrap();
+
+            assert_eq!(r, Purged { secret_keys: 0, sessions: 1 });
+            assert!(repository.session().get(revoked.token_id).await.unwrap().is_some());
+
+            let r = purge(&repository, false, false, Duration::ZERO).await.unwrap();
+
+            assert_eq!(r, Purged { secret_keys: 0, sessions: 1 });
+            assert!(repository.session().get(revoked.token_id).await.unwrap().is_none());
+            assert!(repository.session().get(paired.token_id).await.unwrap().is_some());
+            // --secret-keys 없이는 지우지 않음
+            assert!(repository.secret_key().get(legacy).await.unwrap().is_some());
+
+            let r = purge(&repository, false, true, Duration::ZERO).await.unwrap();
+
+            assert_eq!(r, Purged { secret_keys: 1, sessions: 0 });
+            assert!(repository.secret_key().get(legacy).await.unwrap().is_none());
+            assert!(repository.secret_key().get(paired.token_id).await.unwrap().is_some());
+        });
+    }
+
+    #[tokio::test]
+    async fn purge_keeps_secret_key_of_login_in_progress() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet] ->
+        [session: Session] ->
+        {
+            session = Session::new(Uuid::new_v4(), Uuid::new_v4(), Client::default());
+
+            // create_token_pair처럼 secret key를 먼저 씀
+            repository.secret_key().add(session.token_id, "secret1234").await.unwrap();
+        },
+        {
+            let handle = {
+                let repository = repository.clone();
+                let session = session.clone();
+
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+
+                    repository.session().add(&session).await.unwrap();
+                })
+            };
+
+            let r = purge(&repository, false, true, Duration::from_millis(500)).await.unwrap();
+
+            handle.await.unwrap();
+
+            assert_eq!(r, Purged::default());
+            assert!(repository.secret_key().get(session.token_id).await.unwrap().is_some());
+        });
+    }
+}
@@ -1,14 +1,54 @@
+use std::time::Instant;
+
 use either::Either;
-use madome_sdk::api::user::{get_user, model};
+use hyper::{header::HeaderValue, HeaderMap, StatusCode};
+use madome_sdk::api::user::{self, get_user, model};
+use opentelemetry::global;
+use opentelemetry_http::HeaderInjector;
 use sai::{Component, Injected};
+use tracing::{info_span, Instrument, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use url::Url;
 use uuid::Uuid;
 
-use crate::{command::r#trait::Command, config::Config, error::CommandError};
+use crate::{
+    command::r#trait::Command,
+    config::Config,
+    error::CommandError,
+    metrics,
+    msg::{CURRENT_REQUEST_ID, REQUEST_ID},
+};
 
 #[derive(Component)]
 pub struct GetUser {
     #[injected]
     config: Injected<Config>,
+
+    http: reqwest::Client,
+}
+
+impl GetUser {
+    /// 요청 id와 trace context를 user 서버로 넘겨줌
+    fn propagation_headers(span: &Span) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        let request_id = CURRENT_REQUEST_ID
+            .try_with(|request_id| HeaderValue::from_str(request_id).ok())
+            .ok()
+            .flatten();
+
+        if let Some(request_id) = request_id {
+            headers.insert(REQUEST_ID, request_id);
+        }
+
+        let cx = span.context();
+
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, &mut HeaderInjector(&mut headers))
+        });
+
+        headers
+    }
 }
 
 impl r#trait::GetUser for GetUser {}
@@ -21,28 +61,62 @@ impl Command<Either<Uuid, String>, model::User> for GetUser {
         &self,
         user_id_or_email: Either<Uuid, String>,
     ) -> Result<model::User, Self::Error> {
-        /* let url = format!(
-            "{}/users/{}",
-            self.config.madome_user_server(),
-            user_id_or_email
-        );
+        // madome_sdk의 get_user는 header를 넣을 수가 없어서 직접 요청함
+        let mut url = Url::parse(&self.config.madome_user_url()).map_err(Error::from)?;
 
-        let res = reqwest::get(url).await.map_err(Error::from)?;
+        // email은 path segment로 인코딩해서 `/`, `?`, `#` 등이 경로를 바꾸지 못하게 함
+        url.path_segments_mut()
+            .map_err(|_| Error::Url(url::ParseError::RelativeUrlWithCannotBeABaseBase))?
+            .pop_if_empty()
+            .push("users")
+            .push(&user_id_or_email.to_string());
 
-        if res.status().as_u16() == 404 {
-            return Ok(None);
-        }
+        let span = info_span!("get_user");
 
-        let user_info = res.json::<UserInfo>().await.map_err(Error::from)?; */
+        let start = Instant::now();
 
-        let user = get_user(self.config.madome_user_url(), "", user_id_or_email).await?;
+        let res = self
+            .http
+            .get(url)
+            .headers(Self::propagation_headers(&span))
+            .send()
+            .instrument(span)
+            .await
+            .map_err(Error::from)?;
+
+        metrics::USER_SERVICE_DURATION
+            .with_label_values(&[res.status().as_str()])
+            .observe(start.elapsed().as_secs_f64());
+
+        let user = match res.status() {
+            StatusCode::NOT_FOUND => {
+                return Err(user::Error::GetUser(get_user::Error::NotFoundUser).into())
+            }
+            status if status.is_success() => {
+                res.json::<model::User>().await.map_err(Error::from)?
+            }
+            status => {
+                let body = res.text().await.unwrap_or_default();
+
+                return Err(Error::Undefined(status, body).into());
+            }
+        };
 
         Ok(user)
     }
 }
 
 #[derive(Debug, thiserror::Error)]
-pub enum Error {}
+pub enum Error {
+    #[error("Reqwest: {0}")]
+    Reqwest(#[from] reqwest::Error),
+
+    #[error("Url: {0}")]
+    Url(#[from] url::ParseError),
+
+    #[error("Undefined: {0} {1}")]
+    Undefined(StatusCode, String),
+}
 
 impl From<Error> for crate::Error {
     fn from(err: Error) -> Self {
@@ -65,7 +139,7 @@ pub mod tests {
     // test command implements here..
 
     use either::Either;
-    use madome_sdk::api::user::model;
+    use madome_sdk::api::user::{self, get_user, model};
     use sai::Component;
     use uuid::Uuid;
 
@@ -76,11 +150,23 @@ pub mod tests {
     #[derive(Component, Default)]
     pub struct GetUser {
         users: Vec<model::User>,
+        /// user 서버가 404가 아닌 에러를 응답하는 유저
+        unavailable: Vec<Uuid>,
     }
 
     impl From<model::User> for GetUser {
         fn from(user: model::User) -> Self {
-            Self { users: vec![user] }
+            Self {
+                users: vec![user],
+                unavailable: vec![],
+            }
+        }
+    }
+
+    impl GetUser {
+        pub fn with_unavailable(mut self, user_id: Uuid) -> Self {
+            self.unavailable.push(user_id);
+            self
         }
     }
 
@@ -94,6 +180,16 @@ pub mod tests {
             &self,
             id_or_email: Either<Uuid, String>,
         ) -> Result<model::User, Self::Error> {
+            if let Either::Left(user_id) = &id_or_email {
+                if self.unavailable.contains(user_id) {
+                    return Err(super::Error::Undefined(
+                        hyper::StatusCode::BAD_GATEWAY,
+                        "".to_string(),
+                    )
+                    .into());
+                }
+            }
+
             let user = self.users.iter().find(|user| match &id_or_email {
                 Either::Left(user_id) => user_id == &user.id,
                 Either::Right(user_email) => user_email == &user.email,
@@ -101,8 +197,80 @@ pub mod tests {
 
             match user {
                 Some(user) => Ok(user.clone()),
-                None => Err(crate::Error::Test("not found user")),
+                None => Err(user::Error::GetUser(get_user::Error::NotFoundUser).into()),
             }
         }
     }
+
+    #[tokio::test]
+    async fn encode_email_in_path() {
+        use std::{convert::Infallible, net::SocketAddr};
+
+        use hyper::{
+            service::{make_service_fn, service_fn},
+            Body, Response, Server, StatusCode,
+        };
+        use sai::Injected;
+        use util::assert_debug;
+
+        use crate::config::Config;
+
+        // `/`, `?`, `#`가 그대로 들어가면 다른 경로를 요청하게 됨
+        let email = "a/../b?c#d@madome.app";
+        let user_id = Uuid::new_v4();
+
+        let make_svc = make_service_fn(move |_| async move {
+            Ok::<_, Infallible>(service_fn(move |req: hyper::Request<Body>| async move {
+                let response = if req.uri().path() == "/users/a%2F..%2Fb%3Fc%23d@madome.app" {
+                    let user = serde_json::json!({
+                        "id": user_id,
+                        "name": "madome",
+                        "email": "a/../b?c#d@madome.app",
+                        "role": 0,
+                        "created_at": "2022-01-01T00:00:00Z",
+                        "updated_at": "2022-01-01T00:00:00Z",
+                    });
+
+                    Response::new(Body::from(user.to_string()))
+                } else {
+                    Response::builder()
+                        .status(StatusCode::NOT_FOUND)
+                        .body(Body::empty())
+                        .unwrap()
+                };
+
+                Ok::<_, Infallible>(response)
+            }))
+        });
+
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_svc);
+        let addr = server.local_addr();
+
+        tokio::spawn(server);
+
+        let mut config = Config::default();
+        config.set_madome_user_url(&format!("http://{}", addr));
+
+        let command = super::GetUser {
+            config: Injected::new(config),
+            http: reqwest::Client::new(),
+        };
+
+        let user = command
+            .execute(Either::Right(email.to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(user.id, user_id);
+        assert_eq!(user.email, email);
+
+        let r = command
+            .execute(Either::Right("unknown@madome.app".to_string()))
+            .await
+            .expect_err("expected error, but returns ok");
+
+        let expected: crate::Error = user::Error::GetUser(get_user::Error::NotFoundUser).into();
+
+        assert_debug!(r, expected);
+    }
 }
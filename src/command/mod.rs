@@ -1,13 +1,17 @@
 pub mod get_user_info;
 pub mod random_code;
 pub mod send_email;
+pub mod write_audit;
 
 use either::Either;
 pub use get_user_info::GetUser;
 pub use random_code::RandomCode;
-pub use send_email::SendEmail;
+pub use send_email::{Mail, MailTemplate, SendEmail};
+pub use write_audit::WriteAudit;
 
 use madome_sdk::api::user::model;
+
+use crate::entity::audit::AuditEvent;
 use sai::{Component, Injected};
 use uuid::Uuid;
 
@@ -51,6 +55,14 @@ pub struct CommandSet {
     #[cfg(test)]
     #[injected]
     send_email: Injected<tests::SendEmail>,
+
+    #[cfg(not(test))]
+    #[injected]
+    write_audit: Injected<WriteAudit>,
+
+    #[cfg(test)]
+    #[injected]
+    write_audit: Injected<tests::WriteAudit>,
 }
 
 impl CommandSet {
@@ -65,8 +77,15 @@ impl CommandSet {
         self.random_code.execute(()).await
     }
 
-    pub async fn send_email(&self, email: String, content: String) -> crate::Result<()> {
-        self.send_email.execute((email, content)).await
+    pub async fn send_email(&self, email: String, mail: Mail) -> crate::Result<()> {
+        self.send_email.execute((email, mail)).await
+    }
+
+    /// audit log를 남기지 못해도 요청은 실패하지 않음
+    pub async fn audit(&self, event: AuditEvent) {
+        if let Err(err) = self.write_audit.execute(event).await {
+            log::error!("write audit: {}", err);
+        }
     }
 }
 
@@ -78,10 +97,15 @@ pub mod tests {
     pub use super::get_user_info::tests::*;
     pub use super::random_code::tests::*;
     pub use super::send_email::tests::*;
+    pub use super::write_audit::tests::*;
 
     impl super::CommandSet {
         pub fn set_get_user_info(&mut self, r: GetUser) {
             self.get_user_info = Injected::new(r);
         }
+
+        pub fn audit_events(&self) -> Vec<crate::entity::audit::AuditEvent> {
+            self.write_audit.events()
+        }
     }
 }
@@ -1,6 +1,6 @@
 use aws_sdk_sesv2::{
     error::SendEmailError,
-    model::{Destination, EmailContent, EmailTemplateContent, Template},
+    model::{Body, Content, Destination, EmailContent, Message},
     SdkError,
 };
 use sai::{Component, ComponentLifecycle, Injected};
@@ -9,7 +9,130 @@ use crate::{config::Config, error::CommandError};
 
 use super::r#trait::Command;
 
-pub const AUTHCODE_TEMPLATE_NAME: &str = "authcode_template";
+const AUTHCODE_HTML: &str = r#"<!DOCTYPE html><html><head><title>Madome</title><meta charset=utf-8><meta name="description"content="Madome Authcode"><meta http-equiv="cache-control"content="no-cache"><meta name="viewport"content="width=device-width,user-scalable=no,initial-scale=1,maximum-scale=1"><linkh ref="https://fonts.googleapis.com/css?family=Exo:300,600"rel="stylesheet"></head><body><div id="container"><span id="server">Madome Authcode</span><hr><div id="text">{{authcode}}</div><br/><div id="smallText">or</div><br/><div id="openurl"><a href="madome:///auth?value={{authcode}}">Open in Madome</a></div></div></body></html><style>a,a:visited{color:currentColor}*{font-family:Exo,'Noto Sans',Ubuntu,Roboto,sans-serif;font-weight:300}a{text-decoration:underline}hr{width:10%;border-style:solid;border-color:#000;border-width:.5px;margin:25px auto}#container{position:absolute;text-align:center;top:100px;margin:20px;left:0;right:0}#text{font-size:3rem;font-weight:600;color:#444}#smallText{font-size:0.8rem;font-weight:100;color:#333}#openurl{font-size:1rem;font-weight:400;color:#555}#server{font-size:0.9rem;color:#666}</style>"#;
+
+const NEW_LOGIN_HTML: &str = r#"<!DOCTYPE html><html><head><title>Madome</title><meta charset=utf-8><meta name="description"content="Madome New Login"><meta http-equiv="cache-control"content="no-cache"><meta name="viewport"content="width=device-width,user-scalable=no,initial-scale=1,maximum-scale=1"><link href="https://fonts.googleapis.com/css?family=Exo:300,600"rel="stylesheet"></head><body><div id="container"><span id="server">Madome New Login</span><hr><div id="text">{{device}}</div><div id="smallText">{{ip}}<br/>{{time}}</div><br/><div id="openurl"><a href="{{revoke_url}}">This wasn't me</a></div></div></body></html><style>a,a:visited{color:currentColor}*{font-family:Exo,'Noto Sans',Ubuntu,Roboto,sans-serif;font-weight:300}a{text-decoration:underline}hr{width:10%;border-style:solid;border-color:#000;border-width:.5px;margin:25px auto}#container{position:absolute;text-align:center;top:100px;margin:20px;left:0;right:0}#text{font-size:2rem;font-weight:600;color:#444}#smallText{font-size:0.8rem;font-weight:100;color:#333}#openurl{font-size:1rem;font-weight:400;color:#555}#server{font-size:0.9rem;color:#666}</style>"#;
+
+/// 보낼 메일의 종류
+#[derive(Debug, Clone)]
+pub enum Mail {
+    Authcode(String),
+    /// 처음 보는 기기나 IP에서 로그인했을 때
+    NewLogin {
+        device: String,
+        ip: String,
+        time: String,
+        revoke_url: String,
+    },
+}
+
+impl Mail {
+    fn template_data(&self) -> Vec<(&'static str, &str)> {
+        match self {
+            Self::Authcode(authcode) => vec![("authcode", authcode)],
+            Self::NewLogin {
+                device,
+                ip,
+                time,
+                revoke_url,
+            } => vec![
+                ("device", device),
+                ("ip", ip),
+                ("time", time),
+                ("revoke_url", revoke_url),
+            ],
+        }
+    }
+}
+
+/// 메일 종류마다 보내는 주소와 내용
+///
+/// `{{name}}`은 Mail의 값으로 바뀜, html에서는 escape함
+#[derive(Debug, Clone, PartialEq)]
+pub struct MailTemplate {
+    pub from: String,
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+impl MailTemplate {
+    /// 빠지면 메일을 받아도 쓸 수 없음
+    pub const AUTHCODE_PLACEHOLDER: &'static str = "{{authcode}}";
+
+    pub const NEW_LOGIN_PLACEHOLDER: &'static str = "{{revoke_url}}";
+
+    pub fn authcode() -> Self {
+        Self {
+            from: "verify@madome.app".to_string(),
+            subject: "Authcode of madome.app".to_string(),
+            html: AUTHCODE_HTML.to_string(),
+            text: "{{authcode}}".to_string(),
+        }
+    }
+
+    pub fn new_login() -> Self {
+        Self {
+            from: "security@madome.app".to_string(),
+            subject: "New login to madome.app".to_string(),
+            html: NEW_LOGIN_HTML.to_string(),
+            text: "New login from {{device}} ({{ip}}) at {{time}}\nThis wasn't me: {{revoke_url}}"
+                .to_string(),
+        }
+    }
+
+    fn render(&self, mail: &Mail) -> EmailContent {
+        let data = mail.template_data();
+
+        let content = |x: String| Content::builder().data(x).charset("UTF-8").build();
+
+        let message = Message::builder()
+            .subject(content(render(&self.subject, &data, false)))
+            .body(
+                Body::builder()
+                    .html(content(render(&self.html, &data, true)))
+                    .text(content(render(&self.text, &data, false)))
+                    .build(),
+            )
+            .build();
+
+        EmailContent::builder().simple(message).build()
+    }
+}
+
+/// device는 클라이언트가 보낸 user agent라서 html에 그대로 넣으면 안 됨
+fn render(template: &str, data: &[(&str, &str)], escape_html: bool) -> String {
+    let mut rendered = template.to_string();
+
+    for (name, value) in data {
+        let value = if escape_html {
+            escape(value)
+        } else {
+            value.to_string()
+        };
+
+        rendered = rendered.replace(&format!("{{{{{}}}}}", name), &value);
+    }
+
+    rendered
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
 
 #[derive(Component)]
 #[lifecycle]
@@ -25,56 +148,15 @@ impl ComponentLifecycle for SendEmail {
     async fn start(&mut self) {
         self.aws_ses
             .replace(aws_sdk_sesv2::Client::new(self.config.aws_config()));
-
-        if !self.has_template(AUTHCODE_TEMPLATE_NAME).await {
-            /* self.aws_ses()
-            .delete_email_template()
-            .template_name(AUTHCODE_TEMPLATE_NAME)
-            .send()
-            .await
-            .expect("delete email template"); */
-
-            const AUTHCODE_TEMPLATE: &str = r#"<!DOCTYPE html><html><head><title>Madome</title><meta charset=utf-8><meta name="description"content="Madome Authcode"><meta http-equiv="cache-control"content="no-cache"><meta name="viewport"content="width=device-width,user-scalable=no,initial-scale=1,maximum-scale=1"><linkh ref="https://fonts.googleapis.com/css?family=Exo:300,600"rel="stylesheet"></head><body><div id="container"><span id="server">Madome Authcode</span><hr><div id="text">{{authcode}}</div><br/><div id="smallText">or</div><br/><div id="openurl"><a href="madome:///auth?value={{authcode}}">Open in Madome</a></div></div></body></html><style>a,a:visited{color:currentColor}*{font-family:Exo,'Noto Sans',Ubuntu,Roboto,sans-serif;font-weight:300}a{text-decoration:underline}hr{width:10%;border-style:solid;border-color:#000;border-width:.5px;margin:25px auto}#container{position:absolute;text-align:center;top:100px;margin:20px;left:0;right:0}#text{font-size:3rem;font-weight:600;color:#444}#smallText{font-size:0.8rem;font-weight:100;color:#333}#openurl{font-size:1rem;font-weight:400;color:#555}#server{font-size:0.9rem;color:#666}</style>"#;
-
-            self.aws_ses()
-                .create_email_template()
-                .template_name(AUTHCODE_TEMPLATE_NAME)
-                .template_content(
-                    EmailTemplateContent::builder()
-                        .subject("Authcode of madome.app")
-                        .html(AUTHCODE_TEMPLATE)
-                        .text("{{authcode}}")
-                        .build(),
-                )
-                .send()
-                .await
-                .expect("create email template");
-        }
     }
 }
 
 impl SendEmail {
-    async fn has_template(&self, template_name: &str) -> bool {
-        let has_template = self
-            .aws_ses()
-            .get_email_template()
-            .template_name(template_name)
-            .send()
-            .await
-            .is_ok();
-
-        has_template
-    }
-
-    fn authcode_template(content: &str) -> EmailContent {
-        EmailContent::builder()
-            .template(
-                Template::builder()
-                    .template_name(AUTHCODE_TEMPLATE_NAME)
-                    .template_data(format!("{{\"authcode\": \"{}\"}}", content))
-                    .build(),
-            )
-            .build()
+    fn template(&self, mail: &Mail) -> MailTemplate {
+        match mail {
+            Mail::Authcode(_) => self.config.authcode_mail(),
+            Mail::NewLogin { .. } => self.config.new_login_mail(),
+        }
     }
 
     fn aws_ses(&self) -> &aws_sdk_sesv2::Client {
@@ -83,16 +165,18 @@ impl SendEmail {
 }
 
 #[async_trait::async_trait]
-impl Command<(String, String), ()> for SendEmail {
+impl Command<(String, Mail), ()> for SendEmail {
     type Error = crate::Error;
 
-    async fn execute(&self, (email, content): (String, String)) -> Result<(), Self::Error> {
+    async fn execute(&self, (email, mail): (String, Mail)) -> Result<(), Self::Error> {
+        let template = self.template(&mail);
+
         let _output = self
             .aws_ses()
             .send_email()
-            .from_email_address("verify@madome.app")
+            .from_email_address(template.from.as_str())
             .destination(Destination::builder().to_addresses(email).build())
-            .content(Self::authcode_template(&content))
+            .content(template.render(&mail))
             .send()
             .await
             .map_err(|e| Error::AwsSes(Box::new(e)))?;
@@ -116,7 +200,9 @@ impl From<Error> for crate::Error {
 pub mod r#trait {
     use crate::command::r#trait::Command;
 
-    pub trait SendEmail: Command<(String, String), (), Error = crate::Error> {}
+    use super::Mail;
+
+    pub trait SendEmail: Command<(String, Mail), (), Error = crate::Error> {}
 }
 
 #[cfg(test)]
@@ -125,7 +211,7 @@ pub mod tests {
 
     use crate::command::r#trait::Command;
 
-    use super::r#trait;
+    use super::{r#trait, render, Mail, MailTemplate};
 
     #[derive(Component, Default)]
     pub struct SendEmail;
@@ -133,11 +219,38 @@ pub mod tests {
     impl r#trait::SendEmail for SendEmail {}
 
     #[async_trait::async_trait]
-    impl Command<(String, String), ()> for SendEmail {
+    impl Command<(String, Mail), ()> for SendEmail {
         type Error = crate::Error;
 
-        async fn execute(&self, _: (String, String)) -> Result<(), Self::Error> {
+        async fn execute(&self, _: (String, Mail)) -> Result<(), Self::Error> {
             Ok(())
         }
     }
+
+    #[test]
+    fn render_escapes_html() {
+        let mail = Mail::NewLogin {
+            device: "<script>".to_string(),
+            ip: "127.0.0.1".to_string(),
+            time: "now".to_string(),
+            revoke_url: "https://api.madome.app/auth/sessions/revoke?token=a&b".to_string(),
+        };
+        let data = mail.template_data();
+
+        assert_eq!(
+            render("{{device}} {{revoke_url}}", &data, true),
+            "&lt;script&gt; https://api.madome.app/auth/sessions/revoke?token=a&amp;b"
+        );
+        assert_eq!(
+            render("{{device}} {{ip}}", &data, false),
+            "<script> 127.0.0.1"
+        );
+
+        let template = MailTemplate::authcode();
+
+        assert!(template.html.contains(MailTemplate::AUTHCODE_PLACEHOLDER));
+        assert!(MailTemplate::new_login()
+            .text
+            .contains(MailTemplate::NEW_LOGIN_PLACEHOLDER));
+    }
 }
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use sai::{Component, ComponentLifecycle, Injected};
+
+use crate::{
+    audit::{AuditSink, AuditSinkKind, FileAuditSink, RedisAuditSink, StdoutAuditSink},
+    config::Config,
+    database::DatabaseSet,
+    entity::audit::AuditEvent,
+    error::CommandError,
+    msg::{CURRENT_CLIENT, CURRENT_REQUEST_ID},
+};
+
+use super::r#trait::Command;
+
+#[derive(Component)]
+#[lifecycle]
+pub struct WriteAudit {
+    #[injected]
+    config: Injected<Config>,
+
+    #[injected]
+    database: Injected<DatabaseSet>,
+
+    sink: Option<Box<dyn AuditSink>>,
+}
+
+#[async_trait::async_trait]
+impl ComponentLifecycle for WriteAudit {
+    async fn start(&mut self) {
+        let sink: Box<dyn AuditSink> = match self.config.audit_sink() {
+            AuditSinkKind::Redis => Box::new(RedisAuditSink::new(Arc::clone(&self.database))),
+            AuditSinkKind::File => Box::new(
+                FileAuditSink::open(self.config.audit_file())
+                    .await
+                    .expect("open audit file"),
+            ),
+            AuditSinkKind::Stdout => Box::new(StdoutAuditSink::default()),
+        };
+
+        self.sink.replace(sink);
+    }
+}
+
+impl WriteAudit {
+    fn sink(&self) -> &dyn AuditSink {
+        self.sink.as_deref().unwrap()
+    }
+}
+
+impl r#trait::WriteAudit for WriteAudit {}
+
+#[async_trait::async_trait]
+impl Command<AuditEvent, ()> for WriteAudit {
+    type Error = crate::Error;
+
+    /// 요청을 처리하는 중이라면 클라이언트 정보와 요청 id를 채워넣음
+    async fn execute(&self, mut event: AuditEvent) -> Result<(), Self::Error> {
+        if let Ok(client) = CURRENT_CLIENT.try_with(Clone::clone) {
+            event = event.with_client(&client);
+        }
+
+        if let Ok(request_id) = CURRENT_REQUEST_ID.try_with(Clone::clone) {
+            event = event.with_request_id(request_id);
+        }
+
+        self.sink().write(&event).await
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        CommandError::from(err).into()
+    }
+}
+
+pub mod r#trait {
+    use crate::{command::r#trait::Command, entity::audit::AuditEvent};
+
+    pub trait WriteAudit: Command<AuditEvent, (), Error = crate::Error> {}
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::RwLock;
+
+    use sai::Component;
+
+    use crate::{command::r#trait::Command, entity::audit::AuditEvent};
+
+    use super::r#trait;
+
+    #[derive(Component, Default)]
+    pub struct WriteAudit {
+        events: RwLock<Vec<AuditEvent>>,
+    }
+
+    impl WriteAudit {
+        pub fn events(&self) -> Vec<AuditEvent> {
+            self.events.read().unwrap().clone()
+        }
+    }
+
+    impl r#trait::WriteAudit for WriteAudit {}
+
+    #[async_trait::async_trait]
+    impl Command<AuditEvent, ()> for WriteAudit {
+        type Error = crate::Error;
+
+        async fn execute(&self, event: AuditEvent) -> Result<(), Self::Error> {
+            self.events.write().unwrap().push(event);
+
+            Ok(())
+        }
+    }
+}
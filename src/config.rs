@@ -1,29 +1,711 @@
-use std::{env, fmt::Debug, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    fmt::{self, Display},
+    fs,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::Path,
+    str::FromStr,
+    sync::{Arc, RwLock, RwLockReadGuard},
+    time::Duration,
+};
 
-use sai::{Component, ComponentLifecycle};
+use sai::{Component, ComponentLifecycle, Injected};
+use tokio::{
+    signal::{self, unix::SignalKind},
+    task::JoinHandle,
+};
+use toml::{value::Table, Value};
+use webauthn_rs::Webauthn;
 
-fn env<T>(key: &str) -> T
-where
-    T: FromStr,
-    <T as FromStr>::Err: Debug,
-{
-    let var = env::var(key).expect("Please set dotenv");
+use crate::{
+    audit::AuditSinkKind,
+    command::MailTemplate,
+    cookie::{self, CookieOptions, SameSite},
+    cors::Cors,
+    csrf::{Csrf, Route},
+    entity::{authcode, passkey, scope::RoleScopes, totp},
+    msg::{TrustedProxies, DEFAULT_MAX_BODY_SIZE},
+};
 
-    var.parse().expect("Please set dotenv to valid value")
+/// ADMIN_ROLE이 없으면 사용함
+pub const DEFAULT_ADMIN_ROLE: u8 = 2;
+
+/// config file 경로를 지정하는 env
+pub const CONFIG_FILE: &str = "CONFIG_FILE";
+
+/// CONFIG_FILE이 없을 때 이 파일이 있으면 읽음
+pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+/// report에서 값을 가림
+const SECRET_KEYS: [&str; 7] = [
+    "REDIS_URL",
+    "SESSION_REVOKE_SECRET",
+    "PASSKEY_DECOY_SECRET",
+    "TOTP_ENCRYPTION_KEY",
+    "METRICS_TOKEN",
+    "AWS_ACCESS_KEY_ID",
+    "AWS_SECRET_ACCESS_KEY",
+];
+
+/// SIGHUP으로 다시 읽을 수 있는 key, 나머지는 재시작해야 반영됨
+const RELOADABLE_KEYS: [&str; 32] = [
+    "MADOME_USER_URL",
+    "MFA_REQUIRED_ROLE",
+    "ADMIN_ROLE",
+    "ROLE_SCOPES",
+    "MADOME_AUTH_URL",
+    "SESSION_REVOKE_SECRET",
+    "SHUTDOWN_DELAY",
+    "CORS_ALLOWED_ORIGINS",
+    "CORS_ALLOWED_METHODS",
+    "CORS_ALLOWED_HEADERS",
+    "CORS_ALLOW_CREDENTIALS",
+    "CORS_MAX_AGE",
+    "CSRF_PROTECTED_ROUTES",
+    "CSRF_TRUSTED_ORIGINS",
+    "CSRF_ALLOW_MISSING_ORIGIN",
+    "COOKIE_SAME_SITE",
+    "COOKIE_DOMAIN",
+    "COOKIE_MAX_AGE",
+    "AUTHCODE_LIMIT",
+    "TOTP_MAX_FAILURES",
+    "TOTP_FAILURE_WINDOW",
+    "AUTHCODE_MAIL_FROM",
+    "AUTHCODE_MAIL_SUBJECT",
+    "AUTHCODE_MAIL_HTML",
+    "AUTHCODE_MAIL_TEXT",
+    "NEW_LOGIN_MAIL_FROM",
+    "NEW_LOGIN_MAIL_SUBJECT",
+    "NEW_LOGIN_MAIL_HTML",
+    "NEW_LOGIN_MAIL_TEXT",
+    "TRUSTED_PROXIES",
+    "WARN_SUSPICIOUS_REFRESH",
+    "METRICS_TOKEN",
+];
+
+/// env로만 설정을 읽는 라이브러리(aws sdk, opentelemetry)에 넘겨주는 값
+const EXPORTED_KEYS: [&str; 3] = [
+    "AWS_ACCESS_KEY_ID",
+    "AWS_SECRET_ACCESS_KEY",
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+];
+
+/// 값을 어디서 읽었는지
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    File(String),
+    Env,
+    SecretFile(String),
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "config file {}", path),
+            Self::Env => write!(f, "env"),
+            Self::SecretFile(path) => write!(f, "secret file {}", path),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Invalid {
+    pub key: String,
+    pub origin: Option<Origin>,
+    pub reason: String,
+}
+
+impl Display for Invalid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.origin {
+            Some(origin) => write!(f, "{}: {} (from {})", self.key, self.reason, origin),
+            None => write!(f, "{}: {}", self.key, self.reason),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Errors(pub Vec<Invalid>);
+
+impl std::error::Error for Errors {}
+
+impl Display for Errors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration:")?;
+
+        for invalid in &self.0 {
+            write!(f, "\n  - {}", invalid)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// --check-config 결과
+#[derive(Debug)]
+pub struct Report {
+    resolved: Vec<(&'static str, String, Origin)>,
+    unset: Vec<&'static str>,
+}
+
+impl Report {
+    pub fn values(self) -> HashMap<&'static str, String> {
+        self.resolved
+            .into_iter()
+            .map(|(key, value, _)| (key, value))
+            .collect()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "configuration is valid")?;
+
+        for (key, value, origin) in &self.resolved {
+            let value = if SECRET_KEYS.contains(key) {
+                "***"
+            } else {
+                value.as_str()
+            };
+
+            write!(f, "\n  {} = {} (from {})", key, value, origin)?;
+        }
+
+        for key in &self.unset {
+            write!(f, "\n  {} is not set, using default", key)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// HttpServer가 요청을 받을 곳
+#[derive(Debug, Clone, PartialEq)]
+pub enum Listen {
+    Tcp(SocketAddr),
+    Tls {
+        addr: SocketAddr,
+        cert_path: String,
+        key_path: String,
+    },
+    Unix(String),
+}
+
+impl Display for Listen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "http://{}", addr),
+            Self::Tls { addr, .. } => write!(f, "https://{}", addr),
+            Self::Unix(path) => write!(f, "unix:{}", path),
+        }
+    }
+}
+
+/// hyper server 설정과 요청 제한
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServerOptions {
+    /// h2c (prior knowledge)와 tls의 h2를 허용함
+    pub http2: bool,
+
+    pub keep_alive: bool,
+
+    /// 이 시간 안에 header를 다 보내지 않으면 연결을 끊음
+    pub header_read_timeout: Duration,
+
+    /// 이 시간 동안 읽거나 쓴 게 없으면 연결을 끊음
+    pub idle_timeout: Duration,
+
+    /// body를 읽는 시간을 포함함, 넘으면 503
+    pub request_timeout: Duration,
+
+    /// json body의 최대 크기, 넘으면 413
+    pub max_body_size: usize,
+
+    /// 넘는 연결은 accept한 뒤 바로 끊음
+    pub max_connections: usize,
+
+    /// 넘으면 바로 503으로 응답함
+    pub max_concurrent_requests: usize,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            http2: true,
+            keep_alive: true,
+            header_read_timeout: Duration::from_secs(10),
+            idle_timeout: Duration::from_secs(60),
+            request_timeout: Duration::from_secs(30),
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            max_connections: 1024,
+            max_concurrent_requests: 256,
+        }
+    }
+}
+
+/// `a, b, c`
+struct List(Vec<String>);
+
+impl FromStr for List {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let list = s
+            .split(',')
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        Ok(Self(list))
+    }
+}
+
+/// http(s) url인지만 확인하고 원래 문자열을 그대로 씀
+struct HttpUrl(String);
+
+impl FromStr for HttpUrl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let url = reqwest::Url::parse(s).map_err(|err| err.to_string())?;
+
+        match url.scheme() {
+            "http" | "https" => Ok(Self(s.to_string())),
+            scheme => Err(format!("unsupported scheme: {}", scheme)),
+        }
+    }
+}
+
+/// `verify@madome.app` 또는 `Madome <verify@madome.app>`
+struct MailAddress(String);
+
+impl FromStr for MailAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let address = match s.trim_end().strip_suffix('>') {
+            Some(x) => x.rsplit_once('<').map(|(_, x)| x).unwrap_or(x),
+            None => s,
+        };
+
+        match address.split_once('@') {
+            Some((local, domain))
+                if !local.is_empty()
+                    && !domain.is_empty()
+                    && !address.contains(|c: char| c.is_whitespace() || c.is_control()) =>
+            {
+                Ok(Self(s.to_string()))
+            }
+            _ => Err(format!("invalid mail address: {}", s)),
+        }
+    }
+}
+
+/// config file -> env -> *_FILE 순서로 나중에 읽은 값이 덮어씀
+///
+/// config file의 key는 env 이름의 소문자임 (`REDIS_URL` -> `redis_url`)
+pub struct Loader {
+    file: Table,
+    file_path: Option<String>,
+    env: Box<dyn Fn(&str) -> Option<String> + Send>,
+    known: HashSet<&'static str>,
+    resolved: Vec<(&'static str, String, Origin)>,
+    unset: Vec<&'static str>,
+    errors: Vec<Invalid>,
+}
+
+impl Loader {
+    pub fn from_env() -> Self {
+        let mut loader = Self::new(Table::new(), None, |key| env::var(key).ok());
+
+        match env::var(CONFIG_FILE) {
+            Ok(path) => loader.read_file(path),
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                loader.read_file(DEFAULT_CONFIG_FILE.to_string())
+            }
+            Err(_) => {}
+        }
+
+        loader
+    }
+
+    pub fn new(
+        file: Table,
+        file_path: Option<String>,
+        env: impl Fn(&str) -> Option<String> + Send + 'static,
+    ) -> Self {
+        Self {
+            file,
+            file_path,
+            env: Box::new(env),
+            known: HashSet::new(),
+            resolved: Vec::new(),
+            unset: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn read_file(&mut self, path: String) {
+        let file = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|x| toml::from_str::<Table>(&x).map_err(|err| err.to_string()));
+
+        match file {
+            Ok(file) => {
+                self.file = file;
+            }
+            Err(reason) => self.errors.push(Invalid {
+                key: CONFIG_FILE.to_string(),
+                origin: Some(Origin::File(path.clone())),
+                reason,
+            }),
+        }
+
+        self.file_path.replace(path);
+    }
+
+    fn raw(&mut self, key: &'static str) -> Option<(String, Origin)> {
+        self.known.insert(key);
+
+        let mut found = None;
+
+        if let Some(value) = self.file.get(&key.to_lowercase()) {
+            let origin = Origin::File(self.file_path.clone().unwrap_or_default());
+
+            match value {
+                Value::String(x) => found = Some((x.clone(), origin)),
+                Value::Integer(x) => found = Some((x.to_string(), origin)),
+                Value::Float(x) => found = Some((x.to_string(), origin)),
+                Value::Boolean(x) => found = Some((x.to_string(), origin)),
+                _ => self.errors.push(Invalid {
+                    key: key.to_string(),
+                    origin: Some(origin),
+                    reason: "expected a string, number or boolean".to_string(),
+                }),
+            }
+        }
+
+        if let Some(var) = (self.env)(key) {
+            found = Some((var, Origin::Env));
+        }
+
+        if let Some(path) = (self.env)(&format!("{}_FILE", key)) {
+            match fs::read_to_string(&path) {
+                Ok(x) => {
+                    let x = x.trim_end_matches(&['\r', '\n'][..]).to_string();
+
+                    found = Some((x, Origin::SecretFile(path)));
+                }
+                Err(err) => self.errors.push(Invalid {
+                    key: key.to_string(),
+                    origin: Some(Origin::SecretFile(path)),
+                    reason: err.to_string(),
+                }),
+            }
+        }
+
+        found
+    }
+
+    fn optional<T>(&mut self, key: &'static str) -> Option<T>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        let (raw, origin) = match self.raw(key) {
+            Some(x) => x,
+            None => {
+                self.unset.push(key);
+                return None;
+            }
+        };
+
+        match raw.parse() {
+            Ok(x) => {
+                self.resolved.push((key, raw, origin));
+                Some(x)
+            }
+            Err(err) => {
+                self.errors.push(Invalid {
+                    key: key.to_string(),
+                    reason: format!("invalid value {:?}: {}", raw, err),
+                    origin: Some(origin),
+                });
+                None
+            }
+        }
+    }
+
+    fn required<T>(&mut self, key: &'static str) -> Option<T>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        let x = self.optional(key);
+
+        if x.is_none() && !self.errors.iter().any(|x| x.key == key) {
+            self.unset.retain(|x| *x != key);
+            self.errors.push(Invalid {
+                key: key.to_string(),
+                origin: None,
+                reason: "required but not set".to_string(),
+            });
+        }
+
+        x
+    }
+
+    /// 다른 key와 함께 봐야 알 수 있는 오류
+    fn invalid(&mut self, key: &'static str, reason: &str) {
+        self.errors.push(Invalid {
+            key: key.to_string(),
+            origin: None,
+            reason: reason.to_string(),
+        });
+    }
+
+    /// config file이나 *_FILE에서 읽은 값을 env에도 넣어줌
+    fn export(&mut self, key: &'static str) {
+        if self.optional::<String>(key).is_none() {
+            return;
+        }
+
+        if let Some((_, x, origin)) = self.resolved.last() {
+            if *origin != Origin::Env {
+                env::set_var(key, x);
+            }
+        }
+    }
+
+    /// config file에 모르는 key가 있으면 오타일 가능성이 큼
+    fn finish(self) -> Result<Report, Errors> {
+        let mut errors = self.errors;
+
+        for key in self.file.keys() {
+            if !self.known.iter().any(|x| x.to_lowercase() == *key) {
+                errors.push(Invalid {
+                    key: key.clone(),
+                    origin: Some(Origin::File(self.file_path.clone().unwrap_or_default())),
+                    reason: "unknown key".to_string(),
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Report {
+                resolved: self.resolved,
+                unset: self.unset,
+            })
+        } else {
+            Err(Errors(errors))
+        }
+    }
+}
+
+/// log에 남길 때 SECRET_KEYS의 값을 가림
+fn masked(values: &HashMap<&'static str, String>) -> Vec<(&'static str, &str)> {
+    let mut masked = values
+        .iter()
+        .map(|(key, value)| {
+            let value = if SECRET_KEYS.contains(key) {
+                "***"
+            } else {
+                value.as_str()
+            };
+
+            (*key, value)
+        })
+        .collect::<Vec<_>>();
+    masked.sort_unstable();
+
+    masked
+}
+
+/// telemetry를 초기화하기 전에 불러야 함
+pub fn export_env() {
+    let mut loader = Loader::from_env();
+
+    for key in EXPORTED_KEYS {
+        loader.export(key);
+    }
+}
+
+/// 설정을 모두 읽어보고 결과를 돌려줌
+pub fn check() -> Result<Report, Errors> {
+    let mut loader = Loader::from_env();
+
+    Config::from_loader(&mut loader);
+
+    loader.finish()
+}
+
+/// SIGHUP을 받았을 때 다시 읽어도 되는 설정
+#[derive(Debug, Clone, Default)]
+struct Reloadable {
+    madome_user_server: Option<String>,
+
+    /// 이 role 이상을 요구하는 요청에는 mfa를 거친 token이 필요함
+    mfa_required_role: Option<u8>,
+
+    /// admin api를 사용할 수 있는 최소 role
+    admin_role: Option<u8>,
+
+    /// role별 기본 scope, 없으면 RoleScopes::default()
+    role_scopes: Option<RoleScopes>,
+
+    /// 새 기기 로그인 알림 메일에 들어가는 링크의 base url
+    madome_auth_url: Option<String>,
+
+    /// session revoke link를 서명할 때 사용함
+    session_revoke_secret: Option<String>,
+
+    /// GET /metrics에 `Authorization: Bearer`로 보내야 하는 값, 없으면 /metrics를 열지 않음
+    metrics_token: Option<String>,
+
+    /// 종료 신호를 받은 뒤 readiness를 실패시키고 이만큼 기다렸다가 server를 멈춤
+    shutdown_delay: Option<u64>,
+
+    cors: Cors,
+
+    csrf: Csrf,
+
+    /// 없으면 Lax
+    cookie_same_site: Option<SameSite>,
+
+    /// 한 email로 동시에 유효한 authcode 수, 없으면 authcode::MAX_CODES
+    authcode_limit: Option<usize>,
+
+    /// 없으면 totp::MAX_FAILURES
+    totp_max_failures: Option<u64>,
+
+    /// 초 단위, 없으면 totp::FAILURE_WINDOW
+    totp_failure_window: Option<u64>,
+
+    /// token cookie의 Domain, 없으면 cookie::DEFAULT_DOMAIN
+    cookie_domain: Option<String>,
+
+    /// 초 단위, 없으면 cookie::DEFAULT_MAX_AGE
+    cookie_max_age: Option<u64>,
+
+    /// 없으면 MailTemplate::authcode()
+    authcode_mail: Option<MailTemplate>,
+
+    /// 없으면 MailTemplate::new_login()
+    new_login_mail: Option<MailTemplate>,
+
+    /// 이 주소에서 온 요청만 X-Forwarded-For를 믿음
+    trusted_proxies: TrustedProxies,
+
+    /// refresh할 때 user agent가 바뀌었으면 warn log를 남김, 클라이언트 ip가 log에 남음
+    warn_suspicious_refresh: Option<bool>,
+}
+
+#[derive(Debug, Default)]
+pub struct Reloaded {
+    /// 새 값이 반영된 key
+    pub changed: Vec<&'static str>,
+
+    /// 값이 바뀌었지만 재시작해야 반영되는 key
+    pub requires_restart: Vec<&'static str>,
+}
+
+/// 처음 읽은 값과 비교해서 무엇이 바뀌었는지 알려줌
+struct Reloader {
+    reloadable: Arc<RwLock<Reloadable>>,
+    values: HashMap<&'static str, String>,
+}
+
+impl Reloader {
+    fn reload(&mut self, mut loader: Loader) -> Result<Reloaded, Errors> {
+        let config = Config::from_loader(&mut loader);
+        let values = loader.finish()?.values();
+
+        let mut reloaded = Reloaded::default();
+
+        for key in values.keys().chain(self.values.keys()) {
+            if values.get(key) == self.values.get(key)
+                || reloaded.changed.contains(key)
+                || reloaded.requires_restart.contains(key)
+            {
+                continue;
+            }
+
+            if RELOADABLE_KEYS.contains(key) {
+                reloaded.changed.push(*key);
+            } else {
+                reloaded.requires_restart.push(*key);
+            }
+        }
+
+        for key in RELOADABLE_KEYS {
+            match values.get(key) {
+                Some(x) => self.values.insert(key, x.clone()),
+                None => self.values.remove(key),
+            };
+        }
+
+        let reloadable = config.reloadable.read().unwrap().clone();
+
+        *self.reloadable.write().unwrap() = reloadable;
+
+        Ok(reloaded)
+    }
 }
 
+#[cfg_attr(test, derive(Default))]
 #[derive(Debug, Component)]
 #[lifecycle]
 pub struct Config {
     port: Option<u16>,
 
+    /// 없으면 0.0.0.0, ipv6는 `::`
+    bind_address: Option<IpAddr>,
+
+    /// 둘 다 있으면 https로 받음, 파일이 바뀌면 다시 읽음
+    tls_cert_path: Option<String>,
+    tls_key_path: Option<String>,
+
+    /// 있으면 tcp 대신 unix domain socket으로 받음
+    unix_socket_path: Option<String>,
+
+    server_options: ServerOptions,
+
+    /// gateway가 붙이는 prefix, 예를 들면 `/api`
+    base_path: Option<String>,
+
     redis_url: Option<String>,
 
-    madome_user_server: Option<String>,
+    webauthn_rp_id: Option<String>,
+
+    /// WEBAUTHN_RP_ID, WEBAUTHN_RP_ORIGIN으로 시작할 때 만들어 둠
+    webauthn: Option<Arc<Webauthn>>,
+
+    /// 없는 사용자에게 줄 가짜 credential id를 만드는 key, replica끼리 같아야 함
+    passkey_decoy_secret: Option<String>,
+
+    /// 저장된 TOTP를 읽을 수 없게 되므로 다시 읽지 않음
+    totp_encryption_key: Option<totp::EncryptionKey>,
+
+    /// redis | file | stdout
+    audit_sink: Option<AuditSinkKind>,
+
+    /// audit_sink가 file일 때 사용함
+    audit_file: Option<String>,
+
+    /// 없으면 aws sdk의 기본 region provider를 따름
+    aws_region: Option<String>,
 
     // AWS_ACCESS_KEY_ID=
     // AWS_SECRET_ACCESS_KEY=
     aws_config: Option<aws_config::Config>,
+
+    /// SIGHUP을 받으면 통째로 바꿈
+    reloadable: Arc<RwLock<Reloadable>>,
+
+    /// 처음 읽은 값
+    values: HashMap<&'static str, String>,
 }
 
 #[async_trait::async_trait]
@@ -31,33 +713,1024 @@ impl ComponentLifecycle for Config {
     async fn start(&mut self) {
         dotenv::dotenv().ok();
 
-        self.port.replace(env("PORT"));
+        match Self::load().await {
+            Ok(config) => *self = config,
+            Err(err) => panic!("{}", err),
+        }
+
+        log::info!("config loaded: {:?}", masked(&self.values));
+    }
+}
+
+/// SIGHUP을 받으면 설정을 다시 읽음
+///
+/// server에서만 사용함, madome-auth-admin처럼 잠깐 실행되는 곳에서는 필요 없음
+#[derive(Component)]
+#[lifecycle]
+pub struct ConfigReloader {
+    #[injected]
+    config: Injected<Config>,
+
+    handle: Option<JoinHandle<()>>,
+}
+
+#[async_trait::async_trait]
+impl ComponentLifecycle for ConfigReloader {
+    async fn start(&mut self) {
+        let mut reloader = Reloader {
+            reloadable: Arc::clone(&self.config.reloadable),
+            values: self.config.values.clone(),
+        };
 
-        self.redis_url.replace(env("REDIS_URL"));
+        // env는 실행 중에 바뀌지 않으므로 실제로는 config file과 *_FILE만 다시 읽게 됨
+        let handle = tokio::spawn(async move {
+            let mut sighup = signal::unix::signal(SignalKind::hangup()).unwrap();
 
-        self.madome_user_server.replace(env("MADOME_USER_URL"));
+            while sighup.recv().await.is_some() {
+                match reloader.reload(Loader::from_env()) {
+                    Ok(Reloaded {
+                        changed,
+                        requires_restart,
+                    }) => {
+                        log::info!("config reloaded: changed = {:?}", changed);
 
-        self.aws_config
-            .replace(aws_config::from_env().region("us-east-1").load().await);
+                        if !requires_restart.is_empty() {
+                            log::warn!(
+                                "config: {:?} changed but can't be reloaded, restart to apply",
+                                requires_restart
+                            );
+                        }
+                    }
+                    Err(err) => log::error!("config reload failed, keep current: {}", err),
+                }
+            }
+        });
 
-        log::info!("{:?}", self);
+        self.handle.replace(handle);
+    }
+
+    async fn stop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
     }
 }
 
 impl Config {
+    pub async fn load() -> Result<Self, Errors> {
+        let mut loader = Loader::from_env();
+
+        // reload할 때는 다시 하지 않음, set_var는 다른 thread가 env를 읽는 중이면 안전하지 않음
+        for key in EXPORTED_KEYS {
+            loader.export(key);
+        }
+
+        let mut config = Self::from_loader(&mut loader);
+
+        config.values = loader.finish()?.values();
+
+        let mut aws_config = aws_config::from_env();
+
+        if let Some(region) = &config.aws_region {
+            aws_config = aws_config.region(aws_sdk_sesv2::Region::new(region.clone()));
+        }
+
+        config.aws_config.replace(aws_config.load().await);
+
+        Ok(config)
+    }
+
+    fn from_loader(loader: &mut Loader) -> Self {
+        // aws sdk가 env에서 직접 읽지만 config file에 있어도 모르는 key가 아님
+        for key in EXPORTED_KEYS {
+            loader.optional::<String>(key);
+        }
+
+        let authcode_limit = loader.optional("AUTHCODE_LIMIT");
+        let totp_max_failures = loader.optional("TOTP_MAX_FAILURES");
+        let totp_failure_window = loader.optional("TOTP_FAILURE_WINDOW");
+        let cookie_max_age = loader.optional("COOKIE_MAX_AGE");
+
+        for (key, x) in [
+            ("AUTHCODE_LIMIT", authcode_limit.map(|x: usize| x as u64)),
+            ("TOTP_MAX_FAILURES", totp_max_failures),
+            ("TOTP_FAILURE_WINDOW", totp_failure_window),
+            ("COOKIE_MAX_AGE", cookie_max_age),
+        ] {
+            if x == Some(0) {
+                loader.invalid(key, "must be greater than 0");
+            }
+        }
+
+        let authcode_mail = Self::mail_template_from_loader(
+            loader,
+            [
+                "AUTHCODE_MAIL_FROM",
+                "AUTHCODE_MAIL_SUBJECT",
+                "AUTHCODE_MAIL_HTML",
+                "AUTHCODE_MAIL_TEXT",
+            ],
+            MailTemplate::authcode(),
+            MailTemplate::AUTHCODE_PLACEHOLDER,
+        );
+
+        let new_login_mail = Self::mail_template_from_loader(
+            loader,
+            [
+                "NEW_LOGIN_MAIL_FROM",
+                "NEW_LOGIN_MAIL_SUBJECT",
+                "NEW_LOGIN_MAIL_HTML",
+                "NEW_LOGIN_MAIL_TEXT",
+            ],
+            MailTemplate::new_login(),
+            MailTemplate::NEW_LOGIN_PLACEHOLDER,
+        );
+
+        let reloadable = Reloadable {
+            madome_user_server: loader.required::<HttpUrl>("MADOME_USER_URL").map(|x| x.0),
+            mfa_required_role: loader.optional("MFA_REQUIRED_ROLE"),
+            admin_role: loader.optional("ADMIN_ROLE"),
+            role_scopes: loader.optional("ROLE_SCOPES"),
+            madome_auth_url: loader.required::<HttpUrl>("MADOME_AUTH_URL").map(|x| x.0),
+            session_revoke_secret: loader.required("SESSION_REVOKE_SECRET"),
+            metrics_token: loader.optional("METRICS_TOKEN"),
+            shutdown_delay: loader.optional("SHUTDOWN_DELAY"),
+            cors: Self::cors_from_loader(loader),
+            csrf: Self::csrf_from_loader(loader),
+            cookie_same_site: loader.optional("COOKIE_SAME_SITE"),
+            authcode_limit,
+            totp_max_failures,
+            totp_failure_window,
+            cookie_domain: loader
+                .optional::<cookie::Domain>("COOKIE_DOMAIN")
+                .map(|x| x.0),
+            cookie_max_age,
+            authcode_mail: Some(authcode_mail),
+            new_login_mail: Some(new_login_mail),
+            trusted_proxies: Self::trusted_proxies_from_loader(loader),
+            warn_suspicious_refresh: loader.optional("WARN_SUSPICIOUS_REFRESH"),
+        };
+
+        let unix_socket_path = loader.optional::<String>("UNIX_SOCKET_PATH");
+
+        let port = if unix_socket_path.is_some() {
+            loader.optional("PORT")
+        } else {
+            loader.required("PORT")
+        };
+
+        let bind_address = loader.optional("BIND_ADDRESS");
+        let tls_cert_path = loader.optional::<String>("TLS_CERT_PATH");
+        let tls_key_path = loader.optional::<String>("TLS_KEY_PATH");
+
+        match (&tls_cert_path, &tls_key_path) {
+            (Some(_), None) => loader.invalid("TLS_KEY_PATH", "required with TLS_CERT_PATH"),
+            (None, Some(_)) => loader.invalid("TLS_CERT_PATH", "required with TLS_KEY_PATH"),
+            (Some(_), Some(_)) if unix_socket_path.is_some() => {
+                loader.invalid("TLS_CERT_PATH", "can't be used with UNIX_SOCKET_PATH")
+            }
+            _ => {}
+        }
+
+        let default = ServerOptions::default();
+        let secs =
+            |x: Option<u64>, default: Duration| x.map(Duration::from_secs).unwrap_or(default);
+
+        let server_options = ServerOptions {
+            http2: loader.optional("HTTP2").unwrap_or(default.http2),
+            keep_alive: loader.optional("KEEP_ALIVE").unwrap_or(default.keep_alive),
+            header_read_timeout: secs(
+                loader.optional("HEADER_READ_TIMEOUT"),
+                default.header_read_timeout,
+            ),
+            idle_timeout: secs(loader.optional("IDLE_TIMEOUT"), default.idle_timeout),
+            request_timeout: secs(loader.optional("REQUEST_TIMEOUT"), default.request_timeout),
+            max_body_size: loader
+                .optional("MAX_BODY_SIZE")
+                .unwrap_or(default.max_body_size),
+            max_connections: loader
+                .optional("MAX_CONNECTIONS")
+                .unwrap_or(default.max_connections),
+            max_concurrent_requests: loader
+                .optional("MAX_CONCURRENT_REQUESTS")
+                .unwrap_or(default.max_concurrent_requests),
+        };
+
+        for (key, x) in [
+            ("MAX_BODY_SIZE", server_options.max_body_size),
+            ("MAX_CONNECTIONS", server_options.max_connections),
+            (
+                "MAX_CONCURRENT_REQUESTS",
+                server_options.max_concurrent_requests,
+            ),
+        ] {
+            if x == 0 {
+                loader.invalid(key, "must be greater than 0");
+            }
+        }
+
+        if server_options.idle_timeout.is_zero() {
+            loader.invalid("IDLE_TIMEOUT", "must be greater than 0");
+        }
+
+        let base_path = loader.optional::<String>("BASE_PATH");
+
+        if matches!(&base_path, Some(x) if !x.starts_with('/')) {
+            loader.invalid("BASE_PATH", "must start with `/`");
+        }
+
+        let webauthn_rp_id = loader.required::<String>("WEBAUTHN_RP_ID");
+        let webauthn_rp_origin = loader
+            .required::<HttpUrl>("WEBAUTHN_RP_ORIGIN")
+            .map(|x| x.0);
+
+        let passkey_decoy_secret = loader.required::<String>("PASSKEY_DECOY_SECRET");
+
+        if matches!(&passkey_decoy_secret, Some(x) if x.len() < 32) {
+            loader.invalid("PASSKEY_DECOY_SECRET", "must be at least 32 bytes");
+        }
+
+        let webauthn = match (&webauthn_rp_id, &webauthn_rp_origin) {
+            (Some(rp_id), Some(rp_origin)) => match passkey::webauthn(rp_id, rp_origin) {
+                Ok(webauthn) => Some(Arc::new(webauthn)),
+                Err(err) => {
+                    loader.invalid("WEBAUTHN_RP_ORIGIN", &err);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        Self {
+            port,
+            bind_address,
+            server_options,
+            base_path,
+            tls_cert_path,
+            tls_key_path,
+            unix_socket_path,
+            redis_url: loader.required("REDIS_URL"),
+            webauthn_rp_id,
+            webauthn,
+            passkey_decoy_secret,
+            totp_encryption_key: loader.required("TOTP_ENCRYPTION_KEY"),
+            audit_sink: loader.optional("AUDIT_SINK"),
+            audit_file: loader.optional("AUDIT_FILE"),
+            aws_region: loader.optional("AWS_REGION"),
+            aws_config: None,
+            reloadable: Arc::new(RwLock::new(reloadable)),
+            values: HashMap::new(),
+        }
+    }
+
+    fn trusted_proxies_from_loader(loader: &mut Loader) -> TrustedProxies {
+        let mut proxies = Vec::new();
+
+        for proxy in loader
+            .optional::<List>("TRUSTED_PROXIES")
+            .map(|x| x.0)
+            .unwrap_or_default()
+        {
+            match proxy.parse() {
+                Ok(proxy) => proxies.push(proxy),
+                Err(err) => loader.invalid("TRUSTED_PROXIES", &err),
+            }
+        }
+
+        TrustedProxies(proxies)
+    }
+
+    fn cors_from_loader(loader: &mut Loader) -> Cors {
+        let default = Cors::default();
+
+        let mut methods = Vec::new();
+
+        for method in loader
+            .optional::<List>("CORS_ALLOWED_METHODS")
+            .map(|x| x.0)
+            .unwrap_or_default()
+        {
+            match method.to_uppercase().parse() {
+                Ok(method) => methods.push(method),
+                Err(_) => loader.invalid("CORS_ALLOWED_METHODS", "invalid method"),
+            }
+        }
+
+        let cors = Cors {
+            allowed_origins: loader
+                .optional::<List>("CORS_ALLOWED_ORIGINS")
+                .map(|x| x.0)
+                .unwrap_or(default.allowed_origins),
+            allowed_methods: if methods.is_empty() {
+                default.allowed_methods
+            } else {
+                methods
+            },
+            allowed_headers: loader
+                .optional::<List>("CORS_ALLOWED_HEADERS")
+                .map(|x| x.0.into_iter().map(|x| x.to_lowercase()).collect())
+                .unwrap_or(default.allowed_headers),
+            allow_credentials: loader
+                .optional("CORS_ALLOW_CREDENTIALS")
+                .unwrap_or(default.allow_credentials),
+            max_age: loader.optional("CORS_MAX_AGE").unwrap_or(default.max_age),
+        };
+
+        // 아무 사이트에서나 쿠키를 실어 보낼 수 있게 됨
+        if cors.allow_credentials && cors.allowed_origins.iter().any(|x| x == "*") {
+            loader.invalid(
+                "CORS_ALLOWED_ORIGINS",
+                "`*` can't be used with CORS_ALLOW_CREDENTIALS=true",
+            );
+        }
+
+        cors
+    }
+
+    fn csrf_from_loader(loader: &mut Loader) -> Csrf {
+        let default = Csrf::default();
+
+        let mut protected_routes = Vec::new();
+
+        for route in loader
+            .optional::<List>("CSRF_PROTECTED_ROUTES")
+            .map(|x| x.0)
+            .unwrap_or_default()
+        {
+            match route.parse::<Route>() {
+                Ok(route) => protected_routes.push(route),
+                Err(err) => loader.invalid("CSRF_PROTECTED_ROUTES", &err),
+            }
+        }
+
+        Csrf {
+            protected_routes: if protected_routes.is_empty() {
+                default.protected_routes
+            } else {
+                protected_routes
+            },
+            trusted_origins: loader
+                .optional::<List>("CSRF_TRUSTED_ORIGINS")
+                .map(|x| x.0)
+                .unwrap_or(default.trusted_origins),
+            allow_missing_origin: loader
+                .optional("CSRF_ALLOW_MISSING_ORIGIN")
+                .unwrap_or(default.allow_missing_origin),
+        }
+    }
+
+    /// html, text는 `*_FILE`로 파일에서 읽을 수 있음
+    fn mail_template_from_loader(
+        loader: &mut Loader,
+        [from, subject, html, text]: [&'static str; 4],
+        default: MailTemplate,
+        placeholder: &str,
+    ) -> MailTemplate {
+        let template = MailTemplate {
+            from: loader
+                .optional::<MailAddress>(from)
+                .map(|x| x.0)
+                .unwrap_or(default.from),
+            subject: loader.optional(subject).unwrap_or(default.subject),
+            html: loader.optional(html).unwrap_or(default.html),
+            text: loader.optional(text).unwrap_or(default.text),
+        };
+
+        if template.subject.trim().is_empty() {
+            loader.invalid(subject, "must not be empty");
+        }
+
+        for (key, body) in [(html, &template.html), (text, &template.text)] {
+            if !body.contains(placeholder) {
+                loader.invalid(key, &format!("must contain {}", placeholder));
+            }
+        }
+
+        template
+    }
+
+    fn reloadable(&self) -> RwLockReadGuard<'_, Reloadable> {
+        self.reloadable.read().unwrap()
+    }
+
     pub fn port(&self) -> u16 {
         self.port.unwrap()
     }
 
+    pub fn listen(&self) -> Listen {
+        if let Some(path) = &self.unix_socket_path {
+            return Listen::Unix(path.clone());
+        }
+
+        let ip = self
+            .bind_address
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        let addr = SocketAddr::new(ip, self.port());
+
+        match (&self.tls_cert_path, &self.tls_key_path) {
+            (Some(cert_path), Some(key_path)) => Listen::Tls {
+                addr,
+                cert_path: cert_path.clone(),
+                key_path: key_path.clone(),
+            },
+            _ => Listen::Tcp(addr),
+        }
+    }
+
+    pub fn server_options(&self) -> ServerOptions {
+        self.server_options
+    }
+
+    pub fn base_path(&self) -> &str {
+        self.base_path.as_deref().unwrap_or("")
+    }
+
     pub fn redis_url(&self) -> &str {
         self.redis_url.as_ref().unwrap()
     }
 
-    pub fn madome_user_url(&self) -> &str {
-        self.madome_user_server.as_ref().unwrap()
+    pub fn madome_user_url(&self) -> String {
+        self.reloadable().madome_user_server.clone().unwrap()
+    }
+
+    pub fn webauthn_rp_id(&self) -> &str {
+        self.webauthn_rp_id.as_ref().unwrap()
+    }
+
+    pub fn webauthn(&self) -> &Webauthn {
+        self.webauthn.as_ref().unwrap()
+    }
+
+    pub fn passkey_decoy_secret(&self) -> &str {
+        self.passkey_decoy_secret.as_ref().unwrap()
+    }
+
+    pub fn totp_encryption_key(&self) -> &totp::EncryptionKey {
+        self.totp_encryption_key.as_ref().unwrap()
+    }
+
+    pub fn mfa_required_role(&self) -> Option<u8> {
+        self.reloadable().mfa_required_role
+    }
+
+    pub fn admin_role(&self) -> u8 {
+        self.reloadable().admin_role.unwrap_or(DEFAULT_ADMIN_ROLE)
+    }
+
+    pub fn role_scopes(&self) -> RoleScopes {
+        self.reloadable().role_scopes.clone().unwrap_or_default()
+    }
+
+    pub fn madome_auth_url(&self) -> String {
+        self.reloadable().madome_auth_url.clone().unwrap()
+    }
+
+    pub fn session_revoke_secret(&self) -> String {
+        self.reloadable().session_revoke_secret.clone().unwrap()
+    }
+
+    pub fn metrics_token(&self) -> Option<String> {
+        self.reloadable().metrics_token.clone()
+    }
+
+    pub fn shutdown_delay(&self) -> Duration {
+        Duration::from_secs(self.reloadable().shutdown_delay.unwrap_or(0))
+    }
+
+    pub fn cors(&self) -> Cors {
+        self.reloadable().cors.clone()
+    }
+
+    pub fn csrf(&self) -> Csrf {
+        self.reloadable().csrf.clone()
+    }
+
+    pub fn trusted_proxies(&self) -> TrustedProxies {
+        self.reloadable().trusted_proxies.clone()
+    }
+
+    pub fn warn_suspicious_refresh(&self) -> bool {
+        self.reloadable().warn_suspicious_refresh.unwrap_or(false)
+    }
+
+    pub fn cookie(&self) -> CookieOptions {
+        let reloadable = self.reloadable();
+
+        CookieOptions {
+            domain: reloadable
+                .cookie_domain
+                .clone()
+                .unwrap_or_else(|| cookie::DEFAULT_DOMAIN.to_string()),
+            max_age: reloadable.cookie_max_age.unwrap_or(cookie::DEFAULT_MAX_AGE),
+            same_site: reloadable.cookie_same_site.unwrap_or_default(),
+        }
+    }
+
+    pub fn authcode_limit(&self) -> usize {
+        self.reloadable()
+            .authcode_limit
+            .unwrap_or(authcode::MAX_CODES)
+    }
+
+    pub fn totp_max_failures(&self) -> u64 {
+        self.reloadable()
+            .totp_max_failures
+            .unwrap_or(totp::MAX_FAILURES)
+    }
+
+    pub fn totp_failure_window(&self) -> u64 {
+        self.reloadable()
+            .totp_failure_window
+            .unwrap_or(totp::FAILURE_WINDOW)
+    }
+
+    pub fn authcode_mail(&self) -> MailTemplate {
+        self.reloadable()
+            .authcode_mail
+            .clone()
+            .unwrap_or_else(MailTemplate::authcode)
+    }
+
+    pub fn new_login_mail(&self) -> MailTemplate {
+        self.reloadable()
+            .new_login_mail
+            .clone()
+            .unwrap_or_else(MailTemplate::new_login)
+    }
+
+    pub fn audit_sink(&self) -> AuditSinkKind {
+        self.audit_sink.unwrap_or_default()
+    }
+
+    pub fn audit_file(&self) -> &str {
+        self.audit_file.as_deref().unwrap_or("audit.jsonl")
     }
 
     pub fn aws_config(&self) -> &aws_config::Config {
         self.aws_config.as_ref().unwrap()
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use std::collections::HashMap;
+
+    use std::sync::Arc;
+
+    use std::time::Duration;
+
+    use hyper::Method;
+
+    use crate::{
+        command::MailTemplate,
+        cookie::{CookieOptions, SameSite},
+    };
+
+    use super::{Config, Listen, Loader, Origin, Reloader, ServerOptions};
+
+    fn loader(file: &str, env: &[(&str, &str)]) -> Loader {
+        let env = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>();
+
+        Loader::new(
+            toml::from_str(file).unwrap(),
+            Some("config.toml".to_string()),
+            move |key| env.get(key).cloned(),
+        )
+    }
+
+    const FILE: &str = r#"
+port = 3112
+redis_url = "redis://localhost:6379"
+madome_user_url = "http://localhost:3200"
+webauthn_rp_id = "madome.app"
+webauthn_rp_origin = "https://madome.app"
+madome_auth_url = "https://api.madome.app"
+session_revoke_secret = "secret"
+passkey_decoy_secret = "0123456789abcdef0123456789abcdef"
+totp_encryption_key = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA="
+"#;
+
+    #[test]
+    fn layered() {
+        let path = std::env::temp_dir().join("madome_auth_config_layered");
+        std::fs::write(&path, "from file\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let mut loader = loader(
+            FILE,
+            &[
+                ("PORT", "3113"),
+                ("SESSION_REVOKE_SECRET", "from env"),
+                ("SESSION_REVOKE_SECRET_FILE", path),
+            ],
+        );
+
+        let config = Config::from_loader(&mut loader);
+        let report = loader.finish().unwrap();
+
+        assert_eq!(config.port(), 3113);
+        assert_eq!(config.redis_url(), "redis://localhost:6379");
+        assert_eq!(config.session_revoke_secret(), "from file");
+        assert!(report.resolved.contains(&(
+            "SESSION_REVOKE_SECRET",
+            "from file".to_string(),
+            Origin::SecretFile(path.to_string())
+        )));
+        assert!(report.unset.contains(&"AWS_REGION"));
+    }
+
+    #[test]
+    fn error_names_key() {
+        let mut loader = loader(FILE, &[("PORT", "abc"), ("MADOME_USER_URL", "localhost")]);
+
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        let keys = err.0.iter().map(|x| x.key.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(keys, ["MADOME_USER_URL", "PORT"]);
+        assert!(err.to_string().contains("PORT: invalid value \"abc\""));
+    }
+
+    #[test]
+    fn error_webauthn() {
+        // rp origin이 rp id의 domain이 아니면 passkey를 사용할 수 없음
+        let mut loader = loader(FILE, &[("WEBAUTHN_RP_ORIGIN", "https://example.com")]);
+
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        assert_eq!(err.0[0].key, "WEBAUTHN_RP_ORIGIN");
+    }
+
+    #[test]
+    fn error_short_passkey_decoy_secret() {
+        let mut loader = loader(FILE, &[("PASSKEY_DECOY_SECRET", "short")]);
+
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        assert_eq!(err.0[0].key, "PASSKEY_DECOY_SECRET");
+    }
+
+    #[test]
+    fn error_short_totp_encryption_key() {
+        let mut loader = loader(FILE, &[("TOTP_ENCRYPTION_KEY", "AAAAAAAAAAAAAAAAAAAAAA==")]);
+
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        assert_eq!(err.0[0].key, "TOTP_ENCRYPTION_KEY");
+    }
+
+    #[test]
+    fn error_missing_and_unknown_key() {
+        let file = FILE.replace("port = 3112", "prot = 3112");
+        let mut loader = loader(&file, &[]);
+
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        let keys = err.0.iter().map(|x| x.key.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(keys, ["PORT", "prot"]);
+    }
+
+    #[test]
+    fn listen() {
+        let mut loader = loader(FILE, &[("BIND_ADDRESS", "::1")]);
+        let config = Config::from_loader(&mut loader);
+        loader.finish().unwrap();
+
+        assert_eq!(config.listen(), Listen::Tcp("[::1]:3112".parse().unwrap()));
+
+        let file = FILE.replace("port = 3112", "unix_socket_path = \"./.temp/auth.sock\"");
+        let mut loader = self::loader(&file, &[]);
+        let config = Config::from_loader(&mut loader);
+        loader.finish().unwrap();
+
+        assert_eq!(
+            config.listen(),
+            Listen::Unix("./.temp/auth.sock".to_string())
+        );
+    }
+
+    #[test]
+    fn server_options() {
+        let mut loader = loader(
+            FILE,
+            &[
+                ("HTTP2", "false"),
+                ("REQUEST_TIMEOUT", "5"),
+                ("IDLE_TIMEOUT", "15"),
+            ],
+        );
+        let config = Config::from_loader(&mut loader);
+        loader.finish().unwrap();
+
+        assert_eq!(
+            config.server_options(),
+            ServerOptions {
+                http2: false,
+                request_timeout: Duration::from_secs(5),
+                idle_timeout: Duration::from_secs(15),
+                ..Default::default()
+            }
+        );
+
+        let mut loader = self::loader(FILE, &[("MAX_CONNECTIONS", "0")]);
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        assert_eq!(err.0[0].key, "MAX_CONNECTIONS");
+
+        let mut loader = self::loader(FILE, &[("IDLE_TIMEOUT", "0")]);
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        assert_eq!(err.0[0].key, "IDLE_TIMEOUT");
+    }
+
+    #[test]
+    fn masked() {
+        let mut loader = loader(FILE, &[("SESSION_REVOKE_SECRET", "secret1234")]);
+        Config::from_loader(&mut loader);
+        let values = loader.finish().unwrap().values();
+
+        let masked = super::masked(&values);
+
+        assert!(masked.contains(&("SESSION_REVOKE_SECRET", "***")));
+        assert!(!format!("{:?}", masked).contains("secret1234"));
+    }
+
+    #[test]
+    fn trusted_proxies() {
+        let mut loader = self::loader(FILE, &[]);
+        let config = Config::from_loader(&mut loader);
+        loader.finish().unwrap();
+
+        // 설정하지 않으면 X-Forwarded-For를 믿지 않고 suspicious refresh도 남기지 않음
+        assert!(config.trusted_proxies().0.is_empty());
+        assert!(!config.warn_suspicious_refresh());
+
+        let mut loader = self::loader(FILE, &[("TRUSTED_PROXIES", "10.0.0.0/8, 127.0.0.1")]);
+        let config = Config::from_loader(&mut loader);
+        loader.finish().unwrap();
+
+        assert_eq!(config.trusted_proxies().0.len(), 2);
+
+        let mut loader = self::loader(FILE, &[("TRUSTED_PROXIES", "10.0.0.0/40")]);
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        assert_eq!(err.0[0].key, "TRUSTED_PROXIES");
+    }
+
+    #[test]
+    fn cors() {
+        let mut loader = loader(
+            FILE,
+            &[
+                (
+                    "CORS_ALLOWED_ORIGINS",
+                    "https://*.madome.app, http://localhost:3000",
+                ),
+                ("CORS_ALLOWED_METHODS", "get,post"),
+            ],
+        );
+        let config = Config::from_loader(&mut loader);
+        loader.finish().unwrap();
+
+        let cors = config.cors();
+
+        assert_eq!(
+            cors.allowed_origins,
+            ["https://*.madome.app", "http://localhost:3000"]
+        );
+        assert_eq!(cors.allowed_methods, [Method::GET, Method::POST]);
+
+        let mut loader = self::loader(FILE, &[("CORS_ALLOWED_ORIGINS", "*")]);
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        assert_eq!(err.0[0].key, "CORS_ALLOWED_ORIGINS");
+    }
+
+    #[test]
+    fn limits_cookie_and_mail() {
+        let mut loader = loader(FILE, &[]);
+        let config = Config::from_loader(&mut loader);
+        loader.finish().unwrap();
+
+        assert_eq!(config.authcode_limit(), 5);
+        assert_eq!(config.totp_max_failures(), 5);
+        assert_eq!(config.totp_failure_window(), 60 * 15);
+        assert_eq!(config.cookie(), CookieOptions::default());
+        assert_eq!(config.authcode_mail(), MailTemplate::authcode());
+
+        let path = std::env::temp_dir().join("madome_auth_config_authcode_mail_html");
+        std::fs::write(&path, "<b>{{authcode}}</b>\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let mut loader = self::loader(
+            FILE,
+            &[
+                ("AUTHCODE_LIMIT", "3"),
+                ("TOTP_MAX_FAILURES", "10"),
+                ("COOKIE_DOMAIN", "example.com"),
+                ("COOKIE_MAX_AGE", "60"),
+                ("COOKIE_SAME_SITE", "strict"),
+                ("AUTHCODE_MAIL_FROM", "Madome <noreply@example.com>"),
+                ("AUTHCODE_MAIL_HTML_FILE", path),
+            ],
+        );
+        let config = Config::from_loader(&mut loader);
+        loader.finish().unwrap();
+
+        assert_eq!(config.authcode_limit(), 3);
+        assert_eq!(config.totp_max_failures(), 10);
+        assert_eq!(
+            config.cookie(),
+            CookieOptions {
+                domain: "example.com".to_string(),
+                max_age: 60,
+                same_site: SameSite::Strict,
+            }
+        );
+        assert_eq!(config.authcode_mail().from, "Madome <noreply@example.com>");
+        assert_eq!(config.authcode_mail().html, "<b>{{authcode}}</b>");
+
+        let mut loader = self::loader(
+            FILE,
+            &[
+                ("AUTHCODE_LIMIT", "0"),
+                ("COOKIE_DOMAIN", "example.com; Secure"),
+                ("NEW_LOGIN_MAIL_FROM", "security"),
+                ("NEW_LOGIN_MAIL_TEXT", "New login"),
+            ],
+        );
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        let mut keys = err.0.iter().map(|x| x.key.as_str()).collect::<Vec<_>>();
+        keys.sort_unstable();
+
+        assert_eq!(
+            keys,
+            [
+                "AUTHCODE_LIMIT",
+                "COOKIE_DOMAIN",
+                "NEW_LOGIN_MAIL_FROM",
+                "NEW_LOGIN_MAIL_TEXT"
+            ]
+        );
+    }
+
+    #[test]
+    fn error_tls_without_key() {
+        let mut loader = loader(FILE, &[("TLS_CERT_PATH", "./tls.crt")]);
+
+        Config::from_loader(&mut loader);
+        let err = loader.finish().unwrap_err();
+
+        assert_eq!(err.0[0].key, "TLS_KEY_PATH");
+    }
+
+    #[test]
+    fn reload() {
+        let mut loader = loader(FILE, &[]);
+        let config = Config::from_loader(&mut loader);
+
+        let mut reloader = Reloader {
+            reloadable: Arc::clone(&config.reloadable),
+            values: loader.finish().unwrap().values(),
+        };
+
+        let file = FILE
+            .replace("localhost:3200", "madome-user:3200")
+            .replace("port = 3112", "port = 3113");
+
+        let reloaded = reloader.reload(self::loader(&file, &[])).unwrap();
+
+        assert_eq!(reloaded.changed, ["MADOME_USER_URL"]);
+        assert_eq!(reloaded.requires_restart, ["PORT"]);
+        assert_eq!(config.madome_user_url(), "http://madome-user:3200");
+        assert_eq!(config.port(), 3112);
+
+        // 잘못된 설정이면 기존 값을 유지함
+        let file = FILE.replace("localhost:3200", "madome-user");
+
+        assert!(reloader.reload(self::loader(&file, &[])).is_err());
+        assert_eq!(config.madome_user_url(), "http://madome-user:3200");
+    }
+
+    #[test]
+    fn reload_limits_cookie_and_mail() {
+        let mut loader = loader(FILE, &[]);
+        let config = Config::from_loader(&mut loader);
+
+        let mut reloader = Reloader {
+            reloadable: Arc::clone(&config.reloadable),
+            values: loader.finish().unwrap().values(),
+        };
+
+        let file = format!(
+            "{}authcode_limit = 3\ntotp_failure_window = 60\ncookie_domain = \"example.com\"\nnew_login_mail_subject = \"New login\"\n",
+            FILE
+        );
+
+        let mut reloaded = reloader.reload(self::loader(&file, &[])).unwrap();
+        reloaded.changed.sort_unstable();
+
+        assert_eq!(
+            reloaded.changed,
+            [
+                "AUTHCODE_LIMIT",
+                "COOKIE_DOMAIN",
+                "NEW_LOGIN_MAIL_SUBJECT",
+                "TOTP_FAILURE_WINDOW"
+            ]
+        );
+        assert!(reloaded.requires_restart.is_empty());
+        assert_eq!(config.authcode_limit(), 3);
+        assert_eq!(config.totp_failure_window(), 60);
+        assert_eq!(config.cookie().domain, "example.com");
+        assert_eq!(config.new_login_mail().subject, "New login");
+        assert_eq!(config.new_login_mail().from, MailTemplate::new_login().from);
+    }
+
+    #[test]
+    fn reload_does_not_export() {
+        let mut loader = loader(FILE, &[]);
+        let config = Config::from_loader(&mut loader);
+
+        let mut reloader = Reloader {
+            reloadable: Arc::clone(&config.reloadable),
+            values: loader.finish().unwrap().values(),
+        };
+
+        let file = format!(
+            "{}otel_exporter_otlp_endpoint = \"http://collector:4317\"\n",
+            FILE
+        );
+
+        let reloaded = reloader.reload(self::loader(&file, &[])).unwrap();
+
+        assert_eq!(reloaded.requires_restart, ["OTEL_EXPORTER_OTLP_ENDPOINT"]);
+        assert!(std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_err());
+    }
+
+    impl super::Config {
+        pub fn set_webauthn(&mut self, rp_id: &str, rp_origin: &str) {
+            self.webauthn_rp_id.replace(rp_id.to_string());
+            self.webauthn.replace(Arc::new(
+                super::passkey::webauthn(rp_id, rp_origin).unwrap(),
+            ));
+        }
+
+        pub fn set_passkey_decoy_secret(&mut self, passkey_decoy_secret: &str) {
+            self.passkey_decoy_secret
+                .replace(passkey_decoy_secret.to_string());
+        }
+
+        pub fn set_audit_sink(&mut self, audit_sink: super::AuditSinkKind) {
+            self.audit_sink.replace(audit_sink);
+        }
+
+        pub fn set_mfa_required_role(&mut self, mfa_required_role: u8) {
+            self.reloadable
+                .write()
+                .unwrap()
+                .mfa_required_role
+                .replace(mfa_required_role);
+        }
+
+        pub fn set_totp_max_failures(&mut self, totp_max_failures: u64) {
+            self.reloadable
+                .write()
+                .unwrap()
+                .totp_max_failures
+                .replace(totp_max_failures);
+        }
+
+        pub fn set_madome_user_url(&mut self, madome_user_url: &str) {
+            self.reloadable
+                .write()
+                .unwrap()
+                .madome_user_server
+                .replace(madome_user_url.to_string());
+        }
+
+        pub fn set_session_revoke(&mut self, madome_auth_url: &str, secret: &str) {
+            let mut reloadable = self.reloadable.write().unwrap();
+
+            reloadable
+                .madome_auth_url
+                .replace(madome_auth_url.to_string());
+            reloadable.session_revoke_secret.replace(secret.to_string());
+        }
+    }
+}
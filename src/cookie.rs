@@ -0,0 +1,187 @@
+use std::{fmt, str::FromStr};
+
+use hyper::{
+    header::{self, HeaderValue},
+    Body, Response,
+};
+
+/// COOKIE_DOMAIN이 없으면 사용함
+pub const DEFAULT_DOMAIN: &str = "madome.app";
+
+/// COOKIE_MAX_AGE가 없으면 사용함 (초)
+pub const DEFAULT_MAX_AGE: u64 = 3600 * 24 * 7;
+
+/// cookie의 SameSite
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl Default for SameSite {
+    fn default() -> Self {
+        Self::Lax
+    }
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Strict => write!(f, "Strict"),
+            Self::Lax => write!(f, "Lax"),
+            Self::None => write!(f, "None"),
+        }
+    }
+}
+
+impl FromStr for SameSite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(Self::Strict),
+            "lax" => Ok(Self::Lax),
+            "none" => Ok(Self::None),
+            _ => Err(format!("unknown same site: {}", s)),
+        }
+    }
+}
+
+/// `madome.app`, 값 없이 속성 하나로 들어가므로 `;`나 공백이 있으면 안 됨
+#[derive(Debug, Clone, PartialEq)]
+pub struct Domain(pub String);
+
+impl FromStr for Domain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("empty domain".to_string());
+        }
+
+        if s.contains(|c: char| c == ';' || c == ',' || c.is_whitespace() || c.is_control()) {
+            return Err(format!("invalid domain: {}", s));
+        }
+
+        Ok(Self(s.to_string()))
+    }
+}
+
+/// presenter가 붙인 Set-Cookie에 없는 속성만 붙임
+///
+/// token pair를 지우는 cookie는 presenter가 Max-Age=0을 붙이므로 그대로 둠
+#[derive(Debug, Clone, PartialEq)]
+pub struct CookieOptions {
+    pub domain: String,
+    /// 초 단위
+    pub max_age: u64,
+    pub same_site: SameSite,
+}
+
+impl Default for CookieOptions {
+    fn default() -> Self {
+        Self {
+            domain: DEFAULT_DOMAIN.to_string(),
+            max_age: DEFAULT_MAX_AGE,
+            same_site: SameSite::default(),
+        }
+    }
+}
+
+impl CookieOptions {
+    pub fn apply(&self, response: &mut Response<Body>) {
+        let headers = response.headers_mut();
+
+        let set_cookies = headers
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .map(|x| match x.to_str() {
+                Ok(cookie) => {
+                    HeaderValue::from_str(&self.complete(cookie)).unwrap_or_else(|_| x.clone())
+                }
+                _ => x.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        headers.remove(header::SET_COOKIE);
+
+        for set_cookie in set_cookies {
+            headers.append(header::SET_COOKIE, set_cookie);
+        }
+    }
+
+    fn complete(&self, cookie: &str) -> String {
+        // 첫번째는 name=value
+        let attributes = cookie
+            .split(';')
+            .skip(1)
+            .filter_map(|x| x.split('=').next())
+            .map(|x| x.trim().to_lowercase())
+            .collect::<Vec<_>>();
+
+        let has = |name: &str| attributes.iter().any(|x| x == name);
+
+        let mut cookie = cookie.to_string();
+
+        if !has("domain") {
+            cookie.push_str(&format!("; Domain={}", self.domain));
+        }
+
+        if !has("max-age") {
+            cookie.push_str(&format!("; Max-Age={}", self.max_age));
+        }
+
+        if !has("samesite") {
+            cookie.push_str(&format!("; SameSite={}", self.same_site));
+        }
+
+        cookie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{header, Body, Response};
+
+    use super::{CookieOptions, Domain, SameSite};
+
+    #[test]
+    fn apply() {
+        let mut response = Response::builder()
+            .header(header::SET_COOKIE, "a=1; Path=/; HttpOnly")
+            .header(header::SET_COOKIE, "b=; Max-Age=0; SameSite=None; Secure")
+            .body(Body::empty())
+            .unwrap();
+
+        let options = CookieOptions {
+            domain: "example.com".to_string(),
+            max_age: 60,
+            same_site: SameSite::Strict,
+        };
+
+        options.apply(&mut response);
+
+        let set_cookies = response
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .map(|x| x.to_str().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            set_cookies,
+            [
+                "a=1; Path=/; HttpOnly; Domain=example.com; Max-Age=60; SameSite=Strict",
+                "b=; Max-Age=0; SameSite=None; Secure; Domain=example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn domain() {
+        assert!("madome.app".parse::<Domain>().is_ok());
+        assert!("".parse::<Domain>().is_err());
+        assert!("madome.app; Secure".parse::<Domain>().is_err());
+    }
+}
@@ -0,0 +1,302 @@
+use hyper::{
+    header::{self, HeaderValue},
+    Body, Method, Request, Response, StatusCode,
+};
+
+use crate::msg;
+
+/// 다른 origin의 브라우저에서 오는 요청
+///
+/// allowed_origins가 비어있으면 CORS header를 붙이지 않음
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cors {
+    /// `https://madome.app`, `https://*.madome.app`, `*`
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<Method>,
+    /// 소문자로 비교함
+    pub allowed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    /// preflight 결과를 브라우저가 캐시하는 시간 (초)
+    pub max_age: u64,
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: vec![Method::GET, Method::POST, Method::PATCH, Method::DELETE],
+            allowed_headers: vec![
+                header::CONTENT_TYPE.to_string(),
+                header::AUTHORIZATION.to_string(),
+                msg::REQUEST_ID.to_string(),
+                msg::DEVICE_NAME.to_string(),
+            ],
+            allow_credentials: true,
+            max_age: 600,
+        }
+    }
+}
+
+impl Cors {
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.allowed_origins.iter().any(|allowed| {
+            if allowed == "*" || allowed == origin {
+                return true;
+            }
+
+            // https://*.madome.app
+            match allowed.split_once("://*.") {
+                Some((scheme, domain)) => origin
+                    .strip_prefix(scheme)
+                    .and_then(|x| x.strip_prefix("://"))
+                    .and_then(|x| x.strip_suffix(domain))
+                    .and_then(|x| x.strip_suffix('.'))
+                    .filter(|x| !x.is_empty() && !x.contains('/'))
+                    .is_some(),
+                None => false,
+            }
+        })
+    }
+
+    fn origin<'a>(&self, request: &'a Request<Body>) -> Option<&'a str> {
+        if self.allowed_origins.is_empty() {
+            return None;
+        }
+
+        request
+            .headers()
+            .get(header::ORIGIN)
+            .and_then(|x| x.to_str().ok())
+    }
+
+    /// routing하기 전에 확인함, preflight가 아니면 None
+    pub fn preflight(&self, request: &Request<Body>) -> Option<Response<Body>> {
+        if request.method() != Method::OPTIONS {
+            return None;
+        }
+
+        let origin = self.origin(request)?;
+
+        let headers = request.headers();
+
+        let method = headers
+            .get(header::ACCESS_CONTROL_REQUEST_METHOD)?
+            .to_str()
+            .ok()
+            .and_then(|x| x.parse::<Method>().ok());
+
+        let request_headers = headers
+            .get(header::ACCESS_CONTROL_REQUEST_HEADERS)
+            .and_then(|x| x.to_str().ok())
+            .unwrap_or_default();
+
+        let allowed = self.allows_origin(origin)
+            && matches!(&method, Some(method) if self.allowed_methods.contains(method))
+            && request_headers
+                .split(',')
+                .map(|x| x.trim().to_lowercase())
+                .filter(|x| !x.is_empty())
+                .all(|x| {
+                    self.allowed_headers
+                        .iter()
+                        .any(|y| y.eq_ignore_ascii_case(&x))
+                });
+
+        if !allowed {
+            return Some(
+                Response::builder()
+                    .status(StatusCode::FORBIDDEN)
+                    .body(Body::empty())
+                    .unwrap(),
+            );
+        }
+
+        let methods = self
+            .allowed_methods
+            .iter()
+            .map(Method::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut response = Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header(header::ACCESS_CONTROL_ALLOW_METHODS, methods)
+            .header(
+                header::ACCESS_CONTROL_ALLOW_HEADERS,
+                self.allowed_headers.join(", "),
+            )
+            .header(header::ACCESS_CONTROL_MAX_AGE, self.max_age)
+            .body(Body::empty())
+            .unwrap();
+
+        self.set_headers(origin, &mut response);
+
+        Some(response)
+    }
+
+    /// 실패한 응답에도 붙여야 브라우저가 에러 body를 읽을 수 있음
+    pub fn apply(&self, origin: Option<&HeaderValue>, response: &mut Response<Body>) {
+        if self.allowed_origins.is_empty() {
+            return;
+        }
+
+        match origin.and_then(|x| x.to_str().ok()) {
+            Some(origin) if self.allows_origin(origin) => {
+                self.set_headers(origin, response);
+
+                response.headers_mut().insert(
+                    header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                    HeaderValue::from_static(msg::REQUEST_ID),
+                );
+            }
+            // origin에 따라 응답이 달라지므로 캐시가 섞이지 않게 함
+            _ => {
+                response
+                    .headers_mut()
+                    .append(header::VARY, HeaderValue::from_static("Origin"));
+            }
+        }
+    }
+
+    fn set_headers(&self, origin: &str, response: &mut Response<Body>) {
+        let headers = response.headers_mut();
+
+        if let Ok(origin) = HeaderValue::from_str(origin) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        }
+
+        if self.allow_credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+
+        headers.append(header::VARY, HeaderValue::from_static("Origin"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{header, Body, Method, Request, Response, StatusCode};
+
+    use super::Cors;
+
+    fn cors() -> Cors {
+        Cors {
+            allowed_origins: vec![
+                "https://*.madome.app".to_string(),
+                "http://localhost:3000".to_string(),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn preflight(origin: &str, method: &str, headers: &str) -> Request<Body> {
+        Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/auth/token")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, method)
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, headers)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn allows_origin() {
+        let cors = cors();
+
+        assert!(cors.allows_origin("https://www.madome.app"));
+        assert!(cors.allows_origin("http://localhost:3000"));
+        assert!(!cors.allows_origin("https://madome.app"));
+        assert!(!cors.allows_origin("https://evil.app/.madome.app"));
+        assert!(!cors.allows_origin("http://www.madome.app"));
+        assert!(!cors.allows_origin("http://localhost:3001"));
+    }
+
+    #[test]
+    fn success_preflight() {
+        let request = preflight("https://www.madome.app", "PATCH", "Content-Type");
+
+        let response = cors().preflight(&request).unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .unwrap(),
+            "https://www.madome.app"
+        );
+        assert_eq!(
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+                .unwrap(),
+            "true"
+        );
+    }
+
+    #[test]
+    fn error_preflight() {
+        let cors = cors();
+
+        let request = preflight("https://evil.app", "PATCH", "");
+        assert_eq!(
+            cors.preflight(&request).unwrap().status(),
+            StatusCode::FORBIDDEN
+        );
+
+        let request = preflight("https://www.madome.app", "PUT", "");
+        assert_eq!(
+            cors.preflight(&request).unwrap().status(),
+            StatusCode::FORBIDDEN
+        );
+
+        let request = preflight("https://www.madome.app", "GET", "x-unknown");
+        assert_eq!(
+            cors.preflight(&request).unwrap().status(),
+            StatusCode::FORBIDDEN
+        );
+
+        // Origin이 없으면 preflight가 아님
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .body(Body::empty())
+            .unwrap();
+        assert!(cors.preflight(&request).is_none());
+
+        // 설정하지 않으면 처리하지 않음
+        let request = preflight("https://www.madome.app", "PATCH", "");
+        assert!(Cors::default().preflight(&request).is_none());
+    }
+
+    #[test]
+    fn apply() {
+        let cors = cors();
+
+        let mut response = Response::new(Body::empty());
+        let origin = header::HeaderValue::from_static("http://localhost:3000");
+
+        cors.apply(Some(&origin), &mut response);
+
+        assert_eq!(
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .unwrap(),
+            "http://localhost:3000"
+        );
+        assert_eq!(response.headers().get(header::VARY).unwrap(), "Origin");
+
+        let mut response = Response::new(Body::empty());
+        let origin = header::HeaderValue::from_static("https://evil.app");
+
+        cors.apply(Some(&origin), &mut response);
+
+        assert!(!response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+}
@@ -0,0 +1,274 @@
+use std::str::FromStr;
+
+use hyper::{header, Body, Method, Request};
+use madome_sdk::api::cookie::{MADOME_ACCESS_TOKEN, MADOME_REFRESH_TOKEN};
+use util::http::Cookie;
+
+use crate::{cors::Cors, msg};
+
+/// cookie로 인증하는 상태 변경 요청
+pub const DEFAULT_PROTECTED_ROUTES: [&str; 14] = [
+    "PATCH /auth/token",
+    "POST /auth/token/check",
+    "DELETE /auth/token",
+    "POST /auth/totp",
+    "PATCH /auth/totp",
+    "POST /auth/webauthn/registration",
+    "PATCH /auth/webauthn/registration",
+    "POST /auth/api-keys",
+    "DELETE /auth/api-keys",
+    "DELETE /auth/api-keys/{id}",
+    "DELETE /auth/admin/sessions",
+    "DELETE /auth/admin/sessions/{session_id}",
+    "DELETE /auth/admin/authcodes",
+    "POST /auth/sessions/revoke",
+];
+
+/// `PATCH /auth/token`, `DELETE /auth/api-keys/{id}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route(pub Method, pub String);
+
+impl FromStr for Route {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (method, path) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("expected `METHOD /path`: {}", s))?;
+
+        let method = method
+            .to_uppercase()
+            .parse()
+            .map_err(|_| format!("invalid method: {}", method))?;
+
+        Ok(Self(method, path.trim().to_string()))
+    }
+}
+
+/// Origin이나 Referer로 요청한 사이트를 확인함
+///
+/// cookie 없이 Bearer token으로만 인증하는 요청은 브라우저가 알아서 붙이지 않으므로 확인하지 않음
+#[derive(Debug, Clone, PartialEq)]
+pub struct Csrf {
+    pub protected_routes: Vec<Route>,
+    /// CORS에서 허용한 origin 외에 추가로 허용함
+    pub trusted_origins: Vec<String>,
+    /// Origin과 Referer가 모두 없으면 브라우저가 아닌 클라이언트로 보고 허용함
+    pub allow_missing_origin: bool,
+}
+
+impl Default for Csrf {
+    fn default() -> Self {
+        Self {
+            protected_routes: DEFAULT_PROTECTED_ROUTES
+                .iter()
+                .map(|x| x.parse().unwrap())
+                .collect(),
+            trusted_origins: Vec::new(),
+            allow_missing_origin: false,
+        }
+    }
+}
+
+impl Csrf {
+    fn protects(&self, method: &Method, path: &str) -> bool {
+        let path = path
+            .split('/')
+            .filter(|x| !x.is_empty())
+            .collect::<Vec<_>>();
+
+        self.protected_routes.iter().any(|Route(m, p)| {
+            let pattern = p.split('/').filter(|x| !x.is_empty()).collect::<Vec<_>>();
+
+            m == method
+                && pattern.len() == path.len()
+                && pattern
+                    .iter()
+                    .zip(&path)
+                    .all(|(p, x)| p == x || (p.starts_with('{') && p.ends_with('}')))
+        })
+    }
+
+    /// Basic 인증은 브라우저가 기억했다가 붙일 수 있고,
+    /// cookie가 같이 있으면 대부분의 usecase는 cookie를 먼저 사용함
+    fn bearer_only(request: &Request<Body>) -> bool {
+        let mut cookie = Cookie::from(request);
+
+        msg::bearer_token(request).is_some()
+            && cookie.take(MADOME_ACCESS_TOKEN).is_none()
+            && cookie.take(MADOME_REFRESH_TOKEN).is_none()
+    }
+
+    /// routing하기 전에 확인함, path는 base path와 version을 뗀 것
+    pub fn verify(&self, request: &Request<Body>, path: &str, cors: &Cors) -> crate::Result<()> {
+        if !self.protects(request.method(), path) || Self::bearer_only(request) {
+            return Ok(());
+        }
+
+        let headers = request.headers();
+
+        let origin = headers
+            .get(header::ORIGIN)
+            .and_then(|x| x.to_str().ok())
+            .map(str::to_string)
+            .or_else(|| {
+                headers
+                    .get(header::REFERER)
+                    .and_then(|x| x.to_str().ok())
+                    .and_then(origin_of)
+            });
+
+        let origin = match origin {
+            Some(origin) => origin,
+            None if self.allow_missing_origin => return Ok(()),
+            None => return Err(msg::Error::CrossSiteRequest.into()),
+        };
+
+        let same_origin = host(request)
+            .zip(origin.split_once("://"))
+            .map(|(host, (_, origin_host))| host.eq_ignore_ascii_case(origin_host))
+            .unwrap_or(false);
+
+        if same_origin
+            || self.trusted_origins.iter().any(|x| x == &origin)
+            || (cors.allows_origin(&origin) && !cors.allowed_origins.iter().any(|x| x == "*"))
+        {
+            Ok(())
+        } else {
+            Err(msg::Error::CrossSiteRequest.into())
+        }
+    }
+}
+
+fn host(request: &Request<Body>) -> Option<&str> {
+    request
+        .headers()
+        .get(header::HOST)
+        .and_then(|x| x.to_str().ok())
+        .or_else(|| request.uri().authority().map(|x| x.as_str()))
+}
+
+/// `https://madome.app/login?x=1` -> `https://madome.app`
+fn origin_of(referer: &str) -> Option<String> {
+    let (scheme, rest) = referer.split_once("://")?;
+    let host = rest.split(&['/', '?', '#'][..]).next()?;
+
+    if host.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}://{}", scheme, host))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{header, Body, Method, Request};
+    use madome_sdk::api::cookie::{MADOME_ACCESS_TOKEN, MADOME_REFRESH_TOKEN};
+
+    use crate::cors::Cors;
+
+    use super::{origin_of, Csrf};
+
+    fn request(method: Method, headers: &[(header::HeaderName, &str)]) -> Request<Body> {
+        let mut request = Request::builder()
+            .method(method)
+            .uri("/auth/token")
+            .header(header::HOST, "api.madome.app");
+
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+
+        request.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn verify() {
+        let csrf = Csrf::default();
+        let cors = Cors {
+            allowed_origins: vec!["https://madome.app".to_string()],
+            ..Default::default()
+        };
+
+        let ok = [
+            request(Method::GET, &[(header::ORIGIN, "https://evil.app")]),
+            request(Method::PATCH, &[(header::ORIGIN, "https://madome.app")]),
+            request(Method::PATCH, &[(header::ORIGIN, "https://api.madome.app")]),
+            request(
+                Method::PATCH,
+                &[(header::REFERER, "https://madome.app/login?next=/")],
+            ),
+            request(
+                Method::DELETE,
+                &[
+                    (header::ORIGIN, "https://evil.app"),
+                    (header::AUTHORIZATION, "Bearer token"),
+                ],
+            ),
+        ];
+
+        for request in ok {
+            assert!(csrf.verify(&request, "/auth/token", &cors).is_ok());
+        }
+
+        let access_token_cookie = format!("{}=token", MADOME_ACCESS_TOKEN);
+        let refresh_token_cookie = format!("{}=token", MADOME_REFRESH_TOKEN);
+
+        let rejected = [
+            request(Method::PATCH, &[(header::ORIGIN, "https://evil.app")]),
+            request(Method::DELETE, &[(header::ORIGIN, "null")]),
+            request(Method::DELETE, &[(header::REFERER, "https://evil.app/")]),
+            request(Method::DELETE, &[]),
+            request(
+                Method::DELETE,
+                &[
+                    (header::ORIGIN, "https://evil.app"),
+                    (header::AUTHORIZATION, "Basic YTpi"),
+                ],
+            ),
+            // cookie가 있으면 Bearer token이 있어도 cookie로 인증될 수 있음
+            request(
+                Method::DELETE,
+                &[
+                    (header::ORIGIN, "https://evil.app"),
+                    (header::AUTHORIZATION, "Bearer token"),
+                    (header::COOKIE, &access_token_cookie),
+                ],
+            ),
+            request(
+                Method::PATCH,
+                &[
+                    (header::ORIGIN, "https://evil.app"),
+                    (header::AUTHORIZATION, "Bearer token"),
+                    (header::COOKIE, &refresh_token_cookie),
+                ],
+            ),
+        ];
+
+        for request in rejected {
+            assert!(matches!(
+                csrf.verify(&request, "/auth/token", &cors),
+                Err(crate::Error::Msg(crate::msg::Error::CrossSiteRequest))
+            ));
+        }
+
+        let csrf = Csrf {
+            allow_missing_origin: true,
+            ..Default::default()
+        };
+
+        assert!(csrf
+            .verify(&request(Method::DELETE, &[]), "/auth/token", &cors)
+            .is_ok());
+    }
+
+    #[test]
+    fn referer_origin() {
+        assert_eq!(
+            origin_of("https://madome.app:8443/a/b?c#d").as_deref(),
+            Some("https://madome.app:8443")
+        );
+        assert_eq!(origin_of("madome.app/a"), None);
+    }
+}
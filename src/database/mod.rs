@@ -1,7 +1,9 @@
+use redis::{aio::ConnectionLike, Arg, Cmd, Pipeline, RedisFuture, Value};
 use sai::{Component, ComponentLifecycle, Injected};
 
-use crate::config::Config;
+use crate::{config::Config, metrics};
 
+#[cfg_attr(test, derive(Default))]
 #[derive(Component)]
 #[lifecycle]
 pub struct DatabaseSet {
@@ -21,7 +23,70 @@ impl ComponentLifecycle for DatabaseSet {
 }
 
 impl DatabaseSet {
-    pub async fn redis(&self) -> redis::RedisResult<redis::aio::Connection> {
-        self.redis.as_ref().unwrap().get_async_connection().await
+    pub async fn redis(&self) -> redis::RedisResult<Connection> {
+        let conn = self.redis.as_ref().unwrap().get_async_connection().await?;
+
+        Ok(Connection(conn))
+    }
+}
+
+#[cfg(test)]
+impl DatabaseSet {
+    pub fn from_redis_url(redis_url: &str) -> Self {
+        Self {
+            redis: Some(redis::Client::open(redis_url).unwrap()),
+            ..Default::default()
+        }
+    }
+}
+
+/// 명령어마다 걸린 시간을 기록하는 redis connection
+pub struct Connection(redis::aio::Connection);
+
+impl Connection {
+    fn command_name(cmd: &Cmd) -> String {
+        match cmd.args_iter().next() {
+            Some(Arg::Simple(name)) => String::from_utf8_lossy(name).to_uppercase(),
+            _ => "UNKNOWN".to_string(),
+        }
+    }
+}
+
+impl ConnectionLike for Connection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        Box::pin(async move {
+            let timer = metrics::REDIS_COMMAND_DURATION
+                .with_label_values(&[&Self::command_name(cmd)])
+                .start_timer();
+
+            let r = self.0.req_packed_command(cmd).await;
+
+            timer.observe_duration();
+
+            r
+        })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        Box::pin(async move {
+            let timer = metrics::REDIS_COMMAND_DURATION
+                .with_label_values(&["PIPELINE"])
+                .start_timer();
+
+            let r = self.0.req_packed_commands(cmd, offset, count).await;
+
+            timer.observe_duration();
+
+            r
+        })
+    }
+
+    fn get_db(&self) -> i64 {
+        self.0.get_db()
     }
 }
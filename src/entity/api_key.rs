@@ -0,0 +1,127 @@
+use chrono::Utc;
+use ring::{constant_time, rand::SystemRandom};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::service_client::hash_secret;
+
+/// access token과 구분하기 위해 붙임
+pub const PREFIX: &str = "mak";
+
+/// 유저가 스크립트 등에서 사용하려고 직접 만든 key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    /// hashed
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub secret: String,
+    pub scopes: Vec<String>,
+    pub created_at: i64,
+    /// None이면 만료되지 않음
+    pub expires_at: Option<i64>,
+}
+
+impl ApiKey {
+    /// # Return
+    /// (ApiKey, 평문 key)
+    pub fn new(
+        user_id: Uuid,
+        name: impl Into<String>,
+        scopes: Vec<String>,
+        expires_in: Option<i64>,
+    ) -> (Self, String) {
+        let rng = SystemRandom::new();
+
+        let random_bytes = ring::rand::generate::<[u8; 32]>(&rng).unwrap().expose();
+        let secret = base64::encode_config(random_bytes, base64::URL_SAFE_NO_PAD);
+
+        let id = Uuid::new_v4();
+        let created_at = Utc::now().timestamp();
+
+        let api_key = Self {
+            id,
+            user_id,
+            name: name.into(),
+            secret: hash_secret(&secret),
+            scopes,
+            created_at,
+            expires_at: expires_in.map(|x| created_at + x),
+        };
+
+        let key = format!("{}_{}_{}", PREFIX, id.to_simple(), secret);
+
+        (api_key, key)
+    }
+
+    pub fn is_api_key(key: &str) -> bool {
+        key.starts_with(PREFIX) && key[PREFIX.len()..].starts_with('_')
+    }
+
+    /// # Return
+    /// (id, 평문 secret)
+    pub fn split(key: &str) -> Option<(Uuid, &str)> {
+        let mut parts = key.splitn(3, '_');
+
+        if parts.next()? != PREFIX {
+            return None;
+        }
+
+        let id = parts.next()?.parse().ok()?;
+        let secret = parts.next()?;
+
+        Some((id, secret))
+    }
+
+    pub fn verify_secret(&self, secret: &str) -> bool {
+        constant_time::verify_slices_are_equal(
+            self.secret.as_bytes(),
+            hash_secret(secret).as_bytes(),
+        )
+        .is_ok()
+    }
+
+    pub fn expired(&self) -> bool {
+        self.expires_at
+            .map(|expires_at| expires_at <= Utc::now().timestamp())
+            .unwrap_or(false)
+    }
+
+    /// 응답에는 hash도 넣지 않음
+    pub fn without_secret(self) -> Self {
+        Self {
+            secret: String::new(),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::ApiKey;
+
+    #[test]
+    fn split_and_verify() {
+        let (api_key, key) = ApiKey::new(Uuid::new_v4(), "cron", vec![], Some(3600));
+
+        assert!(ApiKey::is_api_key(&key));
+
+        let (id, secret) = ApiKey::split(&key).unwrap();
+
+        assert_eq!(id, api_key.id);
+        assert!(api_key.verify_secret(secret));
+        assert!(!api_key.verify_secret("wrong secret"));
+        assert!(!api_key.expired());
+    }
+
+    #[test]
+    fn access_token_is_not_api_key() {
+        assert!(!ApiKey::is_api_key(
+            "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9.e30.sig"
+        ));
+        assert!(ApiKey::split("mak_not-uuid_secret").is_none());
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::session::Client;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+    AuthcodeRequested,
+    AuthcodeVerified,
+    AuthcodeFailed,
+    /// admin이 유저의 authcode를 지움
+    AuthcodesCleared,
+    TokenPairIssued,
+    TokenPairRefreshed,
+    TokenPairRevoked,
+    PermissionDenied,
+    ServiceTokenIssued,
+    ServiceTokenDenied,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Success,
+    Failure,
+}
+
+impl AuditKind {
+    pub fn outcome(&self) -> Outcome {
+        match self {
+            Self::AuthcodeFailed | Self::PermissionDenied | Self::ServiceTokenDenied => {
+                Outcome::Failure
+            }
+            _ => Outcome::Success,
+        }
+    }
+}
+
+/// 한번 기록되면 수정하거나 지우지 않음
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    /// unix timestamp (milliseconds)
+    pub time: i64,
+    pub kind: AuditKind,
+    pub outcome: Outcome,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<Uuid>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl AuditEvent {
+    pub fn new(kind: AuditKind) -> Self {
+        Self {
+            time: Utc::now().timestamp_millis(),
+            kind,
+            outcome: kind.outcome(),
+            user_id: None,
+            user_email: None,
+            token_id: None,
+            ip: None,
+            user_agent: None,
+            request_id: None,
+            detail: None,
+        }
+    }
+
+    pub fn user_id(self, user_id: Uuid) -> Self {
+        Self {
+            user_id: Some(user_id),
+            ..self
+        }
+    }
+
+    pub fn user_email(self, user_email: impl Into<String>) -> Self {
+        Self {
+            user_email: Some(user_email.into()),
+            ..self
+        }
+    }
+
+    pub fn token_id(self, token_id: Uuid) -> Self {
+        Self {
+            token_id: Some(token_id),
+            ..self
+        }
+    }
+
+    pub fn detail(self, detail: impl Into<String>) -> Self {
+        Self {
+            detail: Some(detail.into()),
+            ..self
+        }
+    }
+
+    /// 이미 들어있는 값은 덮어쓰지 않음
+    pub fn with_client(self, client: &Client) -> Self {
+        Self {
+            ip: self.ip.or_else(|| client.ip.clone()),
+            user_agent: self.user_agent.or_else(|| client.user_agent.clone()),
+            ..self
+        }
+    }
+
+    pub fn with_request_id(self, request_id: String) -> Self {
+        Self {
+            request_id: self.request_id.or(Some(request_id)),
+            ..self
+        }
+    }
+}
@@ -1,4 +1,6 @@
 pub const MAX_AGE: u64 = 60 * 2;
+/// 한 email로 동시에 유효한 authcode 수, AUTHCODE_LIMIT이 없으면 사용함
+pub const MAX_CODES: usize = 5;
 
 pub struct Authcode {
     pub user_email: String,
@@ -1,3 +1,10 @@
+pub mod api_key;
+pub mod audit;
 pub mod authcode;
+pub mod passkey;
+pub mod scope;
 pub mod secret_key;
+pub mod service_client;
+pub mod session;
 pub mod token;
+pub mod totp;
@@ -0,0 +1,65 @@
+use ring::{
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde_json::{json, Value};
+use webauthn_rs::{prelude::Url, Webauthn, WebauthnBuilder};
+
+/// registration, authentication ceremony의 state를 보관하는 시간
+pub const CEREMONY_MAX_AGE: u64 = 60 * 5;
+
+/// webauthn-rs가 authentication challenge에 넣는 timeout(ms)
+const AUTHENTICATION_TIMEOUT: u64 = 60_000;
+
+pub use webauthn_rs::prelude::{
+    CreationChallengeResponse, Passkey, PasskeyAuthentication, PasskeyRegistration,
+    PublicKeyCredential, RegisterPublicKeyCredential, RequestChallengeResponse,
+};
+
+/// 시작할 때 한번만 만들고 config에서 꺼내 씀
+pub fn webauthn(rp_id: &str, rp_origin: &str) -> Result<Webauthn, String> {
+    let rp_origin = Url::parse(rp_origin).map_err(|err| err.to_string())?;
+
+    WebauthnBuilder::new(rp_id, &rp_origin)
+        .and_then(|builder| builder.rp_name("Madome").build())
+        .map_err(|err| err.to_string())
+}
+
+/// 가입하지 않았거나 passkey가 없는 email에도 진짜와 같은 모양의 challenge를 돌려줌
+///
+/// state는 저장하지 않으므로 이 challenge로는 로그인할 수 없음
+///
+/// 가짜 credential id는 PASSKEY_DECOY_SECRET으로 만듦,
+/// 재시작하거나 다른 replica가 응답해도 진짜 credential id처럼 바뀌지 않아야 함
+pub fn decoy_challenge(rp_id: &str, decoy_secret: &str, email: &str) -> Value {
+    let mut challenge = [0; 32];
+    SystemRandom::new()
+        .fill(&mut challenge)
+        .expect("generate decoy challenge");
+
+    let key = hmac::Key::new(hmac::HMAC_SHA256, decoy_secret.as_bytes());
+    let credential_id = hmac::sign(&key, email.as_bytes());
+
+    json!({
+        "publicKey": {
+            "challenge": base64::encode_config(challenge, base64::URL_SAFE_NO_PAD),
+            "timeout": AUTHENTICATION_TIMEOUT,
+            "rpId": rp_id,
+            "allowCredentials": [{
+                "type": "public-key",
+                "id": base64::encode_config(credential_id.as_ref(), base64::URL_SAFE_NO_PAD),
+            }],
+            "userVerification": "required",
+        }
+    })
+}
+
+/// webauthn-rs의 uuid와 버전이 달라서 변환해줘야 함
+pub fn user_unique_id(user_id: uuid::Uuid) -> webauthn_rs::prelude::Uuid {
+    webauthn_rs::prelude::Uuid::from_bytes(*user_id.as_bytes())
+}
+
+/// credential id를 저장소에서 key로 사용할 수 있게 문자열로 변환
+pub fn credential_key(passkey: &Passkey) -> String {
+    base64::encode_config(passkey.cred_id(), base64::URL_SAFE_NO_PAD)
+}
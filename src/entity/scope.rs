@@ -0,0 +1,125 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+pub const LIBRARY_READ: &str = "library:read";
+pub const LIBRARY_WRITE: &str = "library:write";
+pub const ADMIN_USERS: &str = "admin:users";
+/// service client만 가질 수 있음, POST /auth/token/introspect
+pub const AUTH_INTROSPECT: &str = "auth:introspect";
+
+/// role별 기본 scope
+///
+/// 자기 role 이하에 지정된 scope를 모두 가짐
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoleScopes(BTreeMap<u8, Vec<String>>);
+
+impl Default for RoleScopes {
+    fn default() -> Self {
+        let mut inner = BTreeMap::new();
+
+        inner.insert(0, vec![LIBRARY_READ.to_string(), LIBRARY_WRITE.to_string()]);
+        inner.insert(
+            crate::config::DEFAULT_ADMIN_ROLE,
+            vec![ADMIN_USERS.to_string()],
+        );
+
+        Self(inner)
+    }
+}
+
+impl RoleScopes {
+    pub fn get(&self, role: u8) -> Vec<String> {
+        self.0
+            .range(..=role)
+            .flat_map(|(_, scopes)| scopes.iter().cloned())
+            .collect()
+    }
+}
+
+/// `0=library:read,library:write;2=admin:users`
+impl FromStr for RoleScopes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut inner = BTreeMap::new();
+
+        for entry in s.split(';').map(str::trim).filter(|x| !x.is_empty()) {
+            let (role, scopes) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected <role>=<scope>,...: {}", entry))?;
+
+            let role = role
+                .trim()
+                .parse::<u8>()
+                .map_err(|_| format!("invalid role: {}", role))?;
+
+            let scopes = scopes
+                .split(',')
+                .map(str::trim)
+                .filter(|x| !x.is_empty())
+                .map(|x| x.to_string())
+                .collect();
+
+            inner.insert(role, scopes);
+        }
+
+        Ok(Self(inner))
+    }
+}
+
+/// 빠진 scope
+///
+/// token에 scope가 지정되어 있지 않으면 role의 scope를 모두 가진 걸로 봄
+pub fn missing(required: &[String], granted: &[String], token_scopes: &[String]) -> Vec<String> {
+    required
+        .iter()
+        .filter(|x| !granted.contains(x) || (!token_scopes.is_empty() && !token_scopes.contains(x)))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{missing, RoleScopes};
+
+    fn strings(xs: &[&str]) -> Vec<String> {
+        xs.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn role_inherits_lower_scopes() {
+        let role_scopes: RoleScopes = "0=library:read;1=library:write;3=admin:users"
+            .parse()
+            .unwrap();
+
+        assert_eq!(role_scopes.get(0), strings(&["library:read"]));
+        assert_eq!(
+            role_scopes.get(2),
+            strings(&["library:read", "library:write"])
+        );
+        assert_eq!(
+            role_scopes.get(3),
+            strings(&["library:read", "library:write", "admin:users"])
+        );
+
+        assert!("library:read".parse::<RoleScopes>().is_err());
+    }
+
+    #[test]
+    fn token_scopes_restrict_role_scopes() {
+        let granted = strings(&["library:read", "library:write"]);
+
+        assert!(missing(&strings(&["library:write"]), &granted, &[]).is_empty());
+        assert_eq!(
+            missing(
+                &strings(&["library:write"]),
+                &granted,
+                &strings(&["library:read"])
+            ),
+            strings(&["library:write"])
+        );
+        assert_eq!(
+            missing(&strings(&["admin:users"]), &granted, &[]),
+            strings(&["admin:users"])
+        );
+    }
+}
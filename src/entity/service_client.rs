@@ -0,0 +1,135 @@
+use chrono::Utc;
+use jsonwebtoken::TokenData;
+use ring::{constant_time, digest, rand::SystemRandom};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::token::{jwt, Token};
+
+/// service token은 재발급이 쉬우니 짧게 유지함
+pub const SERVICE_TOKEN_EXP: i64 = 60 * 10;
+
+/// 다른 Madome 서비스가 자기 자신으로 인증할 때 사용하는 client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceClient {
+    pub id: String,
+    pub name: String,
+    /// hashed
+    pub secret: String,
+    /// 발급할 수 있는 scope
+    pub scopes: Vec<String>,
+    /// service token을 서명할 때 사용함, client를 지우면 발급된 token도 모두 무효가 됨
+    pub signing_key: String,
+    pub created_at: i64,
+}
+
+impl ServiceClient {
+    /// # Return
+    /// (ServiceClient, 평문 secret)
+    pub fn new(name: impl Into<String>, scopes: Vec<String>) -> (Self, String) {
+        let secret = random_secret();
+
+        let client = Self {
+            id: nanoid::nanoid!(16),
+            name: name.into(),
+            secret: hash_secret(&secret),
+            scopes,
+            signing_key: random_secret(),
+            created_at: Utc::now().timestamp(),
+        };
+
+        (client, secret)
+    }
+
+    pub fn verify_secret(&self, secret: &str) -> bool {
+        constant_time::verify_slices_are_equal(
+            self.secret.as_bytes(),
+            hash_secret(secret).as_bytes(),
+        )
+        .is_ok()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceToken {
+    pub sub: String,
+    pub iss: String,
+    pub iat: i64,
+    pub exp: i64,
+
+    pub id: Uuid,
+    pub client_id: String,
+    pub scopes: Vec<String>,
+
+    /// placeholder for service token
+    ///
+    /// serialize할 때 이게 있으면 service_token이라는 증거
+    pub _s: bool,
+}
+
+impl ServiceToken {
+    pub fn new(client_id: impl Into<String>, scopes: Vec<String>) -> Self {
+        let issued_at = Utc::now().timestamp();
+
+        Self {
+            sub: "madome service token".to_string(),
+            iss: "madome.app".to_string(),
+            iat: issued_at,
+            exp: issued_at + SERVICE_TOKEN_EXP,
+            id: Uuid::new_v4(),
+            client_id: client_id.into(),
+            scopes,
+            _s: true,
+        }
+    }
+
+    pub fn serialize(&self, signing_key: &str) -> String {
+        jwt::serialize(self, signing_key).expect("jsonwebtoken serialize")
+    }
+
+    pub fn deserialize(service_token: &str, signing_key: &str) -> Option<TokenData<Self>> {
+        Token::deserialize(service_token, signing_key, true)
+    }
+
+    pub fn deserialize_payload(service_token: &str) -> Option<Self> {
+        Token::deserialize_payload(service_token)
+    }
+}
+
+pub fn hash_secret(secret: &str) -> String {
+    let hashed = digest::digest(&digest::SHA256, secret.as_bytes());
+
+    base64::encode_config(hashed, base64::URL_SAFE_NO_PAD)
+}
+
+fn random_secret() -> String {
+    let rng = SystemRandom::new();
+
+    let random_bytes = ring::rand::generate::<[u8; 32]>(&rng).unwrap().expose();
+
+    base64::encode_config(random_bytes, base64::URL_SAFE_NO_PAD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ServiceClient, ServiceToken};
+
+    #[test]
+    fn verify_secret() {
+        let (client, secret) = ServiceClient::new("madome-library", vec![]);
+
+        assert!(client.verify_secret(&secret));
+        assert!(!client.verify_secret("wrong secret"));
+        assert_ne!(client.secret, secret);
+    }
+
+    #[test]
+    fn service_token_is_not_access_token() {
+        let token = ServiceToken::new("client", vec!["library:read".to_string()]);
+        let serialized = token.serialize("signing key");
+
+        assert!(crate::entity::token::AccessToken::deserialize_payload(&serialized).is_none());
+        assert!(ServiceToken::deserialize(&serialized, "signing key").is_some());
+        assert!(ServiceToken::deserialize(&serialized, "other key").is_none());
+    }
+}
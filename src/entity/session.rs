@@ -0,0 +1,158 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::token::jwt;
+
+/// 새 기기 로그인 알림 메일에 들어가는 링크의 유효기간
+pub const REVOKE_LINK_EXP: i64 = 3600 * 24 * 7;
+
+/// token pair를 발급받은 클라이언트의 정보
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Client {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    /// 클라이언트가 직접 알려준 기기 이름
+    pub device_name: Option<String>,
+}
+
+impl Client {
+    pub fn user_agent_family(&self) -> &'static str {
+        user_agent_family(self.user_agent.as_deref().unwrap_or_default())
+    }
+
+    /// 같은 session을 다른 종류의 클라이언트가 이어서 쓰면 token을 훔쳐간 것일 수 있음
+    pub fn suspicious_change(&self, next: &Client) -> bool {
+        self.user_agent_family() != next.user_agent_family()
+    }
+
+    /// 처음 보는 기기나 IP인지 확인할 때 사용함
+    pub fn known_device_keys(&self) -> Vec<String> {
+        let device = format!(
+            "device:{}:{}",
+            self.user_agent_family(),
+            self.device_name.as_deref().unwrap_or_default()
+        );
+
+        let ip = self.ip.as_ref().map(|ip| format!("ip:{}", ip));
+
+        std::iter::once(device).chain(ip).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// refresh해도 바뀌지 않음
+    #[serde(default = "Uuid::new_v4")]
+    pub id: Uuid,
+    pub token_id: Uuid,
+    pub user_id: Uuid,
+    #[serde(flatten)]
+    pub client: Client,
+    pub created_at: i64,
+    pub last_refreshed_at: Option<i64>,
+}
+
+impl Session {
+    pub fn new(token_id: Uuid, user_id: Uuid, client: Client) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            token_id,
+            user_id,
+            client,
+            created_at: Utc::now().timestamp(),
+            last_refreshed_at: None,
+        }
+    }
+
+    /// refresh하면 token id는 바뀌지만 session id와 처음 로그인한 시간은 유지됨
+    pub fn refreshed(self, token_id: Uuid, client: Client) -> Self {
+        Self {
+            token_id,
+            client,
+            last_refreshed_at: Some(Utc::now().timestamp()),
+            ..self
+        }
+    }
+}
+
+/// "본인이 아닙니다" 링크에 들어가는 token
+#[derive(Serialize, Deserialize)]
+pub struct RevokeLink {
+    pub iat: i64,
+    pub exp: i64,
+
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+}
+
+impl RevokeLink {
+    pub fn new(user_id: Uuid, session_id: Uuid) -> Self {
+        let issued_at = Utc::now().timestamp();
+
+        Self {
+            iat: issued_at,
+            exp: issued_at + REVOKE_LINK_EXP,
+            user_id,
+            session_id,
+        }
+    }
+
+    pub fn serialize(&self, secret: &str) -> String {
+        jwt::serialize(self, secret).expect("jsonwebtoken serialize")
+    }
+
+    pub fn deserialize(token: &str, secret: &str) -> Option<Self> {
+        jwt::deserialize(token, secret, true)
+            .ok()
+            .map(|token_data| token_data.claims)
+    }
+}
+
+/// 브라우저나 앱이 바뀌었는지 정도만 알면 되기 때문에 대충 구분함
+pub fn user_agent_family(user_agent: &str) -> &'static str {
+    // 순서 중요: Chrome 계열의 user agent에는 Safari도 들어있음
+    const FAMILIES: [(&str, &str); 9] = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+        ("okhttp/", "Android"),
+        ("CFNetwork/", "iOS"),
+        ("Dart/", "Dart"),
+        ("curl/", "curl"),
+    ];
+
+    FAMILIES
+        .iter()
+        .find(|(pattern, _)| user_agent.contains(pattern))
+        .map(|(_, family)| *family)
+        .unwrap_or("Other")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{user_agent_family, Client};
+
+    #[test]
+    fn chrome_is_not_safari() {
+        let chrome = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/100.0.4896.75 Safari/537.36";
+        let safari = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.4 Safari/605.1.15";
+
+        assert_eq!(user_agent_family(chrome), "Chrome");
+        assert_eq!(user_agent_family(safari), "Safari");
+        assert_eq!(user_agent_family(""), "Other");
+    }
+
+    #[test]
+    fn suspicious_change() {
+        let client = |user_agent: &str| Client {
+            user_agent: Some(user_agent.to_string()),
+            ..Default::default()
+        };
+
+        assert!(!client("curl/7.79.1").suspicious_change(&client("curl/7.80.0")));
+        assert!(client("curl/7.79.1").suspicious_change(&client("")));
+    }
+}
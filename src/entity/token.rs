@@ -6,6 +6,20 @@ use uuid::Uuid;
 pub const ACCESS_TOKEN_EXP: i64 = 3600 * 4;
 pub const REFRESH_TOKEN_EXP: i64 = 3600 * 24 * 7;
 
+/// Authentication Method Reference (RFC 8176)
+pub mod amr {
+    /// 이메일로 받은 authcode
+    pub const EMAIL: &str = "email";
+    /// TOTP
+    pub const OTP: &str = "otp";
+    /// passkey
+    pub const HWK: &str = "hwk";
+    /// 인증 수단을 두개 이상 사용함
+    pub const MFA: &str = "mfa";
+    /// 유저가 만든 api key (RFC 8176에는 없음)
+    pub const API_KEY: &str = "api_key";
+}
+
 pub mod jwt {
     use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation};
     use serde::de::DeserializeOwned;
@@ -51,6 +65,11 @@ pub mod jwt {
 pub struct Token {
     pub id: Uuid,
     pub user_id: Uuid,
+    pub amr: Vec<String>,
+    /// 마지막으로 인증(로그인)한 시간, refresh해도 유지됨
+    pub auth_time: i64,
+    /// 비어있으면 role의 scope를 모두 가짐
+    pub scopes: Vec<String>,
 }
 
 #[cfg_attr(test, derive(Default, Clone))]
@@ -68,6 +87,17 @@ pub struct AccessToken {
     ///
     /// serialize할 때 이게 있으면 access_token이라는 증거
     pub _a: bool,
+
+    #[serde(default)]
+    pub amr: Vec<String>,
+
+    /// 이전에 발급된 token에는 없기 때문에 0이면 오래 전에 인증한 걸로 간주함
+    #[serde(default)]
+    pub auth_time: i64,
+
+    /// 비어있으면 role의 scope를 모두 가짐
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
 }
 
 impl AccessToken {
@@ -82,10 +112,22 @@ impl AccessToken {
     pub fn deserialize_payload(access_token: &str) -> Option<Self> {
         Token::deserialize_payload(access_token)
     }
+
+    pub fn mfa(&self) -> bool {
+        self.amr.iter().any(|x| x == amr::MFA)
+    }
 }
 
 impl From<Token> for AccessToken {
-    fn from(Token { id, user_id }: Token) -> Self {
+    fn from(
+        Token {
+            id,
+            user_id,
+            amr,
+            auth_time,
+            scopes,
+        }: Token,
+    ) -> Self {
         let issued_at = Utc::now().timestamp();
 
         Self {
@@ -96,6 +138,9 @@ impl From<Token> for AccessToken {
             id,
             user_id,
             _a: true,
+            amr,
+            auth_time,
+            scopes,
         }
     }
 }
@@ -128,7 +173,7 @@ impl RefreshToken {
 }
 
 impl From<Token> for RefreshToken {
-    fn from(Token { id, user_id }: Token) -> Self {
+    fn from(Token { id, user_id, .. }: Token) -> Self {
         let issued_at = Utc::now().timestamp();
 
         Self {
@@ -147,7 +192,25 @@ impl Token {
     pub fn new(user_id: Uuid) -> Self {
         let id = Uuid::new_v4();
 
-        Self { id, user_id }
+        Self {
+            id,
+            user_id,
+            amr: Vec::new(),
+            auth_time: Utc::now().timestamp(),
+            scopes: Vec::new(),
+        }
+    }
+
+    pub fn with_amr(self, amr: Vec<String>) -> Self {
+        Self { amr, ..self }
+    }
+
+    pub fn with_auth_time(self, auth_time: i64) -> Self {
+        Self { auth_time, ..self }
+    }
+
+    pub fn with_scopes(self, scopes: Vec<String>) -> Self {
+        Self { scopes, ..self }
     }
 
     /// # Return
@@ -0,0 +1,273 @@
+use std::{fmt, str::FromStr};
+
+use chrono::Utc;
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    digest, hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+
+pub const ISSUER: &str = "Madome";
+pub const PERIOD: i64 = 30;
+pub const DIGITS: u32 = 6;
+/// 시간이 약간 어긋난 기기를 위해 앞뒤로 허용하는 step 수
+pub const SKEW: i64 = 1;
+pub const RECOVERY_CODE_COUNT: usize = 10;
+/// 이만큼 틀리면 FAILURE_WINDOW 동안 TOTP, recovery code를 받지 않음, TOTP_MAX_FAILURES가 없으면 사용함
+pub const MAX_FAILURES: u64 = 5;
+/// 첫 실패부터 세는 시간(초), TOTP_FAILURE_WINDOW가 없으면 사용함
+pub const FAILURE_WINDOW: u64 = 60 * 15;
+
+const ALPHABET: base32::Alphabet = base32::Alphabet::RFC4648 { padding: false };
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Totp {
+    /// base32 encoded
+    pub secret: String,
+    /// 등록 후 코드로 확인해야 활성화됨
+    pub enabled: bool,
+    /// 같은 코드를 재사용하지 못하게 마지막으로 사용된 counter를 기록함
+    pub last_counter: i64,
+    /// hashed
+    pub recovery_codes: Vec<String>,
+}
+
+impl Totp {
+    /// # Return
+    /// (Totp, 평문 recovery codes)
+    pub fn new() -> (Self, Vec<String>) {
+        let rng = SystemRandom::new();
+
+        let secret = ring::rand::generate::<[u8; 20]>(&rng).unwrap().expose();
+
+        let recovery_codes = (0..RECOVERY_CODE_COUNT)
+            .map(|_| nanoid::nanoid!(10))
+            .collect::<Vec<_>>();
+
+        let totp = Self {
+            secret: base32::encode(ALPHABET, &secret),
+            enabled: false,
+            last_counter: 0,
+            recovery_codes: recovery_codes.iter().map(|x| hash(x)).collect(),
+        };
+
+        (totp, recovery_codes)
+    }
+
+    pub fn uri(&self, account_name: &str) -> String {
+        format!(
+            "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+            issuer = ISSUER,
+            account = percent_encode(account_name),
+            secret = self.secret,
+            digits = DIGITS,
+            period = PERIOD,
+        )
+    }
+
+    /// 성공하면 last_counter를 갱신함
+    pub fn verify(&mut self, code: &str) -> bool {
+        self.verify_at(code, Utc::now().timestamp())
+    }
+
+    fn verify_at(&mut self, code: &str, timestamp: i64) -> bool {
+        let secret = match base32::decode(ALPHABET, &self.secret) {
+            Some(secret) => secret,
+            None => return false,
+        };
+
+        let current = timestamp / PERIOD;
+
+        let matched = (current - SKEW..=current + SKEW)
+            .filter(|counter| *counter > self.last_counter)
+            .find(|counter| generate(&secret, *counter as u64) == code);
+
+        match matched {
+            Some(counter) => {
+                self.last_counter = counter;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 일치하는 recovery code가 있으면 지우고 true를 반환함
+    pub fn take_recovery_code(&mut self, code: &str) -> bool {
+        let hashed = hash(code);
+
+        let position = self.recovery_codes.iter().position(|x| x == &hashed);
+
+        match position {
+            Some(position) => {
+                self.recovery_codes.remove(position);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// RFC 6238 (HMAC-SHA1)
+fn generate(secret: &[u8], counter: u64) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret);
+    let tag = hmac::sign(&key, &counter.to_be_bytes());
+    let hashed = tag.as_ref();
+
+    let offset = (hashed[hashed.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hashed[offset] & 0x7f,
+        hashed[offset + 1],
+        hashed[offset + 2],
+        hashed[offset + 3],
+    ]);
+
+    format!(
+        "{:0width$}",
+        binary % 10_u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+fn hash(code: &str) -> String {
+    let hashed = digest::digest(&digest::SHA256, code.as_bytes());
+
+    base64::encode_config(hashed, base64::URL_SAFE_NO_PAD)
+}
+
+/// TOTP_ENCRYPTION_KEY, base64로 인코딩된 32 bytes
+///
+/// 저장할 때 secret과 recovery code를 AES-256-GCM으로 암호화함
+#[derive(Clone)]
+pub struct EncryptionKey([u8; 32]);
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptionKey(***)")
+    }
+}
+
+impl FromStr for EncryptionKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let decoded = base64::decode(s.trim()).map_err(|err| err.to_string())?;
+
+        let key = decoded
+            .try_into()
+            .map_err(|x: Vec<u8>| format!("must be 32 bytes, but {} bytes", x.len()))?;
+
+        Ok(Self(key))
+    }
+}
+
+impl EncryptionKey {
+    fn key(&self) -> LessSafeKey {
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &self.0).expect("aes-256-gcm key"))
+    }
+
+    /// nonce와 암호문을 이어 붙여 base64로 인코딩함
+    ///
+    /// aad에는 저장할 위치(redis key)를 넣어서 다른 유저의 값과 바꿔치기할 수 없게 함
+    pub fn seal(&self, aad: &str, plaintext: &[u8]) -> String {
+        let mut nonce = [0; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .expect("generate nonce");
+
+        let mut in_out = plaintext.to_vec();
+
+        self.key()
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad.as_bytes()),
+                &mut in_out,
+            )
+            .expect("seal");
+
+        base64::encode([&nonce[..], &in_out[..]].concat())
+    }
+
+    pub fn open(&self, aad: &str, sealed: &str) -> Option<Vec<u8>> {
+        let sealed = base64::decode(sealed).ok()?;
+
+        if sealed.len() < NONCE_LEN + AES_256_GCM.tag_len() {
+            return None;
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+
+        let mut in_out = ciphertext.to_vec();
+
+        let plaintext = self
+            .key()
+            .open_in_place(nonce, Aad::from(aad.as_bytes()), &mut in_out)
+            .ok()?;
+
+        Some(plaintext.to_vec())
+    }
+}
+
+fn percent_encode(x: &str) -> String {
+    x.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, EncryptionKey, Totp, ALPHABET};
+
+    #[test]
+    fn rfc6238_test_vector() {
+        let secret = b"12345678901234567890";
+
+        assert_eq!(generate(secret, 59 / 30), "287082");
+        assert_eq!(generate(secret, 1111111109 / 30), "081804");
+        assert_eq!(generate(secret, 1234567890 / 30), "005924");
+    }
+
+    #[test]
+    fn reject_reused_code() {
+        let (mut totp, _) = Totp::new();
+        let secret = base32::decode(ALPHABET, &totp.secret).unwrap();
+
+        let timestamp = 1234567890;
+        let code = generate(&secret, (timestamp / 30) as u64);
+
+        assert!(totp.verify_at(&code, timestamp));
+        assert!(!totp.verify_at(&code, timestamp));
+    }
+
+    #[test]
+    fn take_recovery_code_once() {
+        let (mut totp, recovery_codes) = Totp::new();
+
+        assert!(totp.take_recovery_code(&recovery_codes[0]));
+        assert!(!totp.take_recovery_code(&recovery_codes[0]));
+    }
+
+    #[test]
+    fn encryption_key() {
+        let key = base64::encode([1u8; 32]).parse::<EncryptionKey>().unwrap();
+        let other = base64::encode([2u8; 32]).parse::<EncryptionKey>().unwrap();
+
+        let sealed = key.seal("totp:a", b"secret");
+
+        assert!(!sealed.contains("secret"));
+        assert_ne!(sealed, key.seal("totp:a", b"secret"));
+        assert_eq!(key.open("totp:a", &sealed).unwrap(), b"secret");
+        // 다른 유저의 key에 옮겨 담은 값
+        assert!(key.open("totp:b", &sealed).is_none());
+        assert!(other.open("totp:a", &sealed).is_none());
+
+        assert!(base64::encode([1u8; 16]).parse::<EncryptionKey>().is_err());
+        assert!("not base64".parse::<EncryptionKey>().is_err());
+    }
+}
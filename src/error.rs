@@ -1,12 +1,16 @@
-use hyper::{Body, Response, StatusCode};
+use hyper::{header, Body, Response, StatusCode};
+use serde::Serialize;
 use util::http::{SetCookie, SetHeaders};
 
 use crate::{
-    command::{get_user_info, random_code, send_email},
+    command::{get_user_info, random_code, send_email, write_audit},
     usecase::{
         check_access_token, check_and_refresh_token_pair, check_authcode, check_refresh_token,
-        check_token_pair, create_authcode, create_token_pair, delete_token_pair,
-        refresh_token_pair,
+        check_token_pair, check_totp, create_api_key, create_authcode, create_service_token,
+        create_token_pair, create_totp, delete_api_key, delete_token_pair, enable_totp,
+        finish_passkey_authentication, finish_passkey_registration, introspect_tokens,
+        list_audit_events, refresh_token_pair, revoke_session, start_passkey_authentication,
+        start_passkey_registration,
     },
 };
 
@@ -20,6 +24,8 @@ pub enum Error {
     UseCase(#[from] UseCaseError),
     #[error("Repository: {0}")]
     Repository(#[from] RepositoryError),
+    #[error("Audit: {0}")]
+    Audit(#[from] AuditError),
 
     // TODO: 나중에 위치 재선정
     #[error("ReadChunksFromBody")]
@@ -28,9 +34,10 @@ pub enum Error {
     #[error("UserSdk: {0}")]
     UserSdk(#[from] madome_sdk::api::user::Error),
 
-    #[cfg(test)]
-    #[error("{0}")]
-    Test(&'static str),
+    #[error("Too many concurrent requests")]
+    Overloaded,
+    #[error("Request timed out")]
+    Timeout,
 }
 
 type Msg = crate::msg::Error;
@@ -39,6 +46,18 @@ type Msg = crate::msg::Error;
 pub enum RepositoryError {
     #[error("Redis: {0}")]
     Redis(#[from] redis::RedisError),
+    #[error("Json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Can't decrypt {0}")]
+    Decrypt(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuditError {
+    #[error("Io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Json: {0}")]
+    Json(#[from] serde_json::Error),
 }
 
 impl From<redis::RedisError> for Error {
@@ -55,6 +74,8 @@ pub enum CommandError {
     RandomCode(#[from] random_code::Error),
     #[error("SendEmail: {0}")]
     SendEmail(#[from] send_email::Error),
+    #[error("WriteAudit: {0}")]
+    WriteAudit(#[from] write_audit::Error),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -77,10 +98,173 @@ pub enum UseCaseError {
     RefreshTokenPair(#[from] refresh_token_pair::Error),
     #[error("DeleteTokenPair: {0}")]
     DeleteTokenPair(#[from] delete_token_pair::Error),
+    #[error("StartPasskeyRegistration: {0}")]
+    StartPasskeyRegistration(#[from] start_passkey_registration::Error),
+    #[error("FinishPasskeyRegistration: {0}")]
+    FinishPasskeyRegistration(#[from] finish_passkey_registration::Error),
+    #[error("StartPasskeyAuthentication: {0}")]
+    StartPasskeyAuthentication(#[from] start_passkey_authentication::Error),
+    #[error("FinishPasskeyAuthentication: {0}")]
+    FinishPasskeyAuthentication(#[from] finish_passkey_authentication::Error),
+    #[error("CheckTotp: {0}")]
+    CheckTotp(#[from] check_totp::Error),
+    #[error("CreateTotp: {0}")]
+    CreateTotp(#[from] create_totp::Error),
+    #[error("EnableTotp: {0}")]
+    EnableTotp(#[from] enable_totp::Error),
+    #[error("RevokeSession: {0}")]
+    RevokeSession(#[from] revoke_session::Error),
+    #[error("CreateServiceToken: {0}")]
+    CreateServiceToken(#[from] create_service_token::Error),
+    #[error("CreateApiKey: {0}")]
+    CreateApiKey(#[from] create_api_key::Error),
+    #[error("DeleteApiKey: {0}")]
+    DeleteApiKey(#[from] delete_api_key::Error),
+    #[error("ListAuditEvents: {0}")]
+    ListAuditEvents(#[from] list_audit_events::Error),
+    #[error("IntrospectTokens: {0}")]
+    IntrospectTokens(#[from] introspect_tokens::Error),
 }
 
-impl From<Error> for Response<Body> {
-    fn from(error: Error) -> Self {
+impl Error {
+    /// frontend에서 분기할 때 사용하는 변하지 않는 error code
+    ///
+    /// Display 문자열은 바뀔 수 있으니 이걸 사용해야 함
+    pub fn code(&self) -> &'static str {
+        use crate::msg::Error::*;
+        use Error::*;
+        use UseCaseError::*;
+
+        match self {
+            Msg(NotFound) => "not_found",
+            Msg(MethodNotAllowed(_)) => "method_not_allowed",
+            Msg(JsonDeserializePayload(_) | FormDeserializePayload(_)) => "invalid_payload",
+            Msg(RequiredQuery(_)) => "required_query",
+            Msg(PayloadTooLarge(_)) => "payload_too_large",
+            Msg(CrossSiteRequest) => "cross_site_request",
+            Msg(UnauthorizedMetricsToken) => "unauthorized_metrics_token",
+
+            UseCase(
+                CheckAccessToken(err)
+                | CheckAndRefreshTokenPair(check_and_refresh_token_pair::Error::Denied(err, _)),
+            ) => match err {
+                check_access_token::Error::UnauthorizedAccessToken => "unauthorized_access_token",
+                check_access_token::Error::PermissionDenied => "permission_denied",
+                check_access_token::Error::RequiredMfa => "required_mfa",
+                check_access_token::Error::StaleAuthentication => "stale_authentication",
+                check_access_token::Error::InsufficientScope(_) => "insufficient_scope",
+            },
+            UseCase(CheckRefreshToken(check_refresh_token::Error::UnauthorizedRefreshToken)) => {
+                "unauthorized_refresh_token"
+            }
+            UseCase(CheckAuthcode(check_authcode::Error::InvalidAuthcode)) => "invalid_authcode",
+            UseCase(CreateTokenPair(err)) => match err {
+                create_token_pair::Error::NotFoundUser => "not_found_user",
+                create_token_pair::Error::CannotAddedSecretKey => "cannot_add_secret_key",
+            },
+            UseCase(CheckTokenPair(check_token_pair::Error::InvalidTokenPair)) => {
+                "invalid_token_pair"
+            }
+            UseCase(CreateAuthcode(err)) => match err {
+                create_authcode::Error::InvalidEmail => "invalid_email",
+                create_authcode::Error::NotFoundUser => "not_found_user",
+                create_authcode::Error::TooManyCreatedAuthcode => "too_many_created_authcode",
+            },
+            UseCase(RefreshTokenPair(refresh_token_pair::Error::CannotRemovedSecretKey)) => {
+                "cannot_remove_secret_key"
+            }
+            UseCase(DeleteTokenPair(delete_token_pair::Error::InvalidToken)) => "invalid_token",
+            UseCase(StartPasskeyRegistration(start_passkey_registration::Error::Webauthn(_))) => {
+                "webauthn"
+            }
+            UseCase(FinishPasskeyRegistration(err)) => match err {
+                finish_passkey_registration::Error::NotFoundRegistration => {
+                    "not_found_passkey_registration"
+                }
+                finish_passkey_registration::Error::InvalidCredential => "invalid_credential",
+            },
+            UseCase(StartPasskeyAuthentication(start_passkey_authentication::Error::Webauthn(
+                _,
+            ))) => "webauthn",
+            UseCase(FinishPasskeyAuthentication(
+                finish_passkey_authentication::Error::InvalidCredential,
+            )) => "invalid_credential",
+            UseCase(CheckTotp(err)) => match err {
+                check_totp::Error::RequiredTotpCode => "required_totp_code",
+                check_totp::Error::InvalidTotpCode => "invalid_totp_code",
+                check_totp::Error::TooManyTotpAttempts => "too_many_totp_attempts",
+            },
+            UseCase(CreateTotp(create_totp::Error::AlreadyEnabledTotp)) => "already_enabled_totp",
+            UseCase(EnableTotp(err)) => match err {
+                enable_totp::Error::NotFoundTotp => "not_found_totp",
+                enable_totp::Error::InvalidTotpCode => "invalid_totp_code",
+            },
+            UseCase(RevokeSession(err)) => match err {
+                revoke_session::Error::InvalidRevokeLink => "invalid_revoke_link",
+                revoke_session::Error::NotFoundSession => "not_found_session",
+            },
+            // RFC 6749 5.2
+            UseCase(CreateServiceToken(err)) => match err {
+                create_service_token::Error::UnsupportedGrantType => "unsupported_grant_type",
+                create_service_token::Error::InvalidClient => "invalid_client",
+                create_service_token::Error::InvalidScope => "invalid_scope",
+            },
+            UseCase(CreateApiKey(err)) => match err {
+                create_api_key::Error::InvalidName => "invalid_api_key_name",
+                create_api_key::Error::InvalidExpiresIn => "invalid_expires_in",
+                create_api_key::Error::CreatedByApiKey => "created_by_api_key",
+                create_api_key::Error::TooManyApiKeys => "too_many_api_keys",
+            },
+            UseCase(DeleteApiKey(delete_api_key::Error::NotFoundApiKey)) => "not_found_api_key",
+
+            UseCase(ListAuditEvents(list_audit_events::Error::UnsupportedAuditSink(_))) => {
+                "unsupported_audit_sink"
+            }
+            UseCase(IntrospectTokens(introspect_tokens::Error::TooManyTokens(_))) => {
+                "too_many_tokens"
+            }
+
+            Command(CommandError::GetUserInfo(_)) => "get_user_info",
+            Command(CommandError::RandomCode(_)) => "random_code",
+            Command(CommandError::SendEmail(_)) => "send_email",
+            Command(CommandError::WriteAudit(_)) => "write_audit",
+
+            UserSdk(madome_sdk::api::user::Error::GetUser(
+                madome_sdk::api::user::get_user::Error::NotFoundUser,
+            )) => "not_found_user",
+            // 유저 서비스 요청은 get_user_info에서만 하므로 같은 code를 사용함
+            UserSdk(_) => "get_user_info",
+
+            Repository(_) => "repository",
+            Audit(_) => "audit",
+            ReadChunksFromBody(_) => "read_body",
+            Overloaded => "overloaded",
+            Timeout => "timeout",
+        }
+    }
+
+    /// 클라이언트에게 보여줄 메시지
+    ///
+    /// 에러 체인(`UseCase: CheckAccessToken: ...`)은 로그에만 남기고, 응답에는 마지막 에러의 메시지만 담음
+    ///
+    /// redis, sdk 등 외부 에러에서 온 메시지는 내부 정보를 담고 있을 수 있으므로 None
+    fn client_message(&self) -> Option<String> {
+        use Error::*;
+
+        match self {
+            Msg(err) => Some(err.to_string()),
+            UserSdk(
+                err @ madome_sdk::api::user::Error::GetUser(
+                    madome_sdk::api::user::get_user::Error::NotFoundUser,
+                ),
+            ) => Some(err.to_string()),
+            UseCase(err) => std::error::Error::source(err).map(ToString::to_string),
+            Overloaded | Timeout => Some(self.to_string()),
+            _ => None,
+        }
+    }
+
+    pub fn to_http(self, request_id: &str) -> Response<Body> {
         use crate::msg::Error::*;
         use check_access_token::Error::*;
         use check_authcode::Error::*;
@@ -90,105 +274,271 @@ impl From<Error> for Response<Body> {
         use Error::*;
         use UseCaseError::*;
 
-        let response = Response::builder();
+        // 갱신한 token pair는 거절하더라도 돌려줘야 함
+        if let UseCase(CheckAndRefreshTokenPair(check_and_refresh_token_pair::Error::Denied(
+            err,
+            token_pair,
+        ))) = self
+        {
+            let (parts, body) = Error::from(err).to_http(request_id).into_parts();
 
-        // TODO: 복잡해지면 분리하자
-        match error {
-            Msg(JsonDeserializePayload(err)) => response
-                .status(StatusCode::BAD_REQUEST)
-                .body(err.to_string().into()),
-
-            Msg(err @ NotFound) => response
-                .status(StatusCode::NOT_FOUND)
-                .body(err.to_string().into()),
-
-            UseCase(CheckAccessToken(err @ PermissionDenied)) => response
-                .status(StatusCode::FORBIDDEN)
-                .body(err.to_string().into()),
-
-            UseCase(CheckAccessToken(err @ UnauthorizedAccessToken)) => response
-                .status(StatusCode::UNAUTHORIZED)
-                .body(err.to_string().into()),
-
-            UseCase(CheckRefreshToken(err @ UnauthorizedRefreshToken)) => response
-                .status(StatusCode::UNAUTHORIZED)
-                .body(err.to_string().into()),
-
-            UseCase(CheckTokenPair(err @ InvalidTokenPair)) => response
-                .status(StatusCode::UNAUTHORIZED)
-                .body(err.to_string().into()),
-
-            UseCase(CheckAuthcode(err @ InvalidAuthcode)) => response
-                .status(StatusCode::NOT_FOUND)
-                .body(err.to_string().into()),
-
-            UseCase(CreateAuthcode(err @ InvalidEmail)) => response
-                .status(StatusCode::BAD_REQUEST)
-                .body(err.to_string().into()),
-
-            UseCase(CreateAuthcode(err @ TooManyCreatedAuthcode)) => response
-                .status(StatusCode::TOO_MANY_REQUESTS)
-                .body(err.to_string().into()),
-
-            UseCase(CreateAuthcode(err @ create_authcode::Error::NotFoundUser)) => response
-                .status(StatusCode::NOT_FOUND)
-                .body(err.to_string().into()),
-
-            UseCase(CreateTokenPair(err @ create_token_pair::Error::NotFoundUser)) => response
-                .status(StatusCode::NOT_FOUND)
-                .body(err.to_string().into()),
-
-            UseCase(CheckAndRefreshTokenPair(
-                err @ check_and_refresh_token_pair::Error::PermissionDenied(_),
-            )) => {
-                let err_str = err.to_string();
-                let check_and_refresh_token_pair::Error::PermissionDenied(token_pair) = err;
-
-                /* let t = match err {
-                    check_and_refresh_token_pair::Error::PermissionDenied(t) => t,
-                    // _ => unreachable!(),
-                }; */
-
-                response
-                    .status(StatusCode::FORBIDDEN)
-                    .headers(SetCookie::from(token_pair).iter())
-                    .body(err_str.into())
-            }
-
-            UseCase(DeleteTokenPair(err @ delete_token_pair::Error::InvalidToken)) => response
-                .status(StatusCode::BAD_REQUEST)
-                .body(err.to_string().into()),
-
-            UserSdk(ref err) => {
-                use madome_sdk::api::{
-                    user::{get_user, Error as UserError},
-                    BaseError,
-                };
-
-                match err {
-                    UserError::Base(err) => match err {
-                        BaseError::Undefined(code, body) => {
-                            response.status(code).body(body.to_string().into())
-                        }
-                        _ => response
-                            .status(StatusCode::INTERNAL_SERVER_ERROR)
-                            .body(err.to_string().into()),
-                    },
-                    UserError::GetUser(err) => match err {
-                        get_user::Error::NotFoundUser => response
-                            .status(StatusCode::NOT_FOUND)
-                            .body(err.to_string().into()),
-                    },
-                    _ => response
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .body(err.to_string().into()),
-                }
+            let mut response = Response::builder()
+                .status(parts.status)
+                .headers(SetCookie::from(token_pair).iter());
+
+            for (name, value) in parts.headers.iter() {
+                response = response.header(name, value);
             }
 
-            err => response
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(err.to_string().into()),
+            return response.body(body).unwrap();
         }
-        .unwrap()
+
+        let code = self.code();
+        let message = self.client_message();
+        // 내부 에러가 아니라 잠시 뒤에 다시 시도하면 되는 경우
+        let retryable = matches!(self, Overloaded | Timeout);
+
+        let mut response = Response::builder();
+
+        // TODO: 복잡해지면 분리하자
+        let status = match self {
+            Msg(JsonDeserializePayload(_) | FormDeserializePayload(_)) => StatusCode::BAD_REQUEST,
+
+            Msg(RequiredQuery(_)) => StatusCode::BAD_REQUEST,
+
+            Msg(NotFound) => StatusCode::NOT_FOUND,
+
+            Msg(MethodNotAllowed(allow)) => {
+                let allow = allow
+                    .iter()
+                    .map(|x| x.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                response = response.header(header::ALLOW, allow);
+
+                StatusCode::METHOD_NOT_ALLOWED
+            }
+
+            Msg(PayloadTooLarge(_)) => StatusCode::PAYLOAD_TOO_LARGE,
+
+            Msg(CrossSiteRequest) => StatusCode::FORBIDDEN,
+
+            Msg(UnauthorizedMetricsToken) => StatusCode::UNAUTHORIZED,
+
+            Overloaded => {
+                response = response.header(header::RETRY_AFTER, "1");
+
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+
+            Timeout => StatusCode::SERVICE_UNAVAILABLE,
+
+            UseCase(CheckAccessToken(PermissionDenied)) => StatusCode::FORBIDDEN,
+
+            UseCase(CheckAccessToken(RequiredMfa)) => StatusCode::FORBIDDEN,
+
+            // RFC 9470
+            UseCase(CheckAccessToken(StaleAuthentication)) => {
+                response = response.header(
+                    header::WWW_AUTHENTICATE,
+                    r#"Bearer error="insufficient_user_authentication""#,
+                );
+
+                StatusCode::UNAUTHORIZED
+            }
+
+            UseCase(CheckAccessToken(UnauthorizedAccessToken)) => StatusCode::UNAUTHORIZED,
+
+            // RFC 6750 3.1
+            UseCase(CheckAccessToken(InsufficientScope(scope))) => {
+                response = response.header(
+                    header::WWW_AUTHENTICATE,
+                    format!(r#"Bearer error="insufficient_scope", scope="{}""#, scope),
+                );
+
+                StatusCode::FORBIDDEN
+            }
+
+            UseCase(CheckRefreshToken(UnauthorizedRefreshToken)) => StatusCode::UNAUTHORIZED,
+
+            UseCase(CheckTokenPair(InvalidTokenPair)) => StatusCode::UNAUTHORIZED,
+
+            UseCase(CheckAuthcode(InvalidAuthcode)) => StatusCode::NOT_FOUND,
+
+            UseCase(CreateAuthcode(InvalidEmail)) => StatusCode::BAD_REQUEST,
+
+            UseCase(CreateAuthcode(TooManyCreatedAuthcode)) => StatusCode::TOO_MANY_REQUESTS,
+
+            UseCase(CreateAuthcode(create_authcode::Error::NotFoundUser)) => StatusCode::NOT_FOUND,
+
+            UseCase(CreateTokenPair(create_token_pair::Error::NotFoundUser)) => {
+                StatusCode::NOT_FOUND
+            }
+
+            UseCase(DeleteTokenPair(delete_token_pair::Error::InvalidToken)) => {
+                StatusCode::BAD_REQUEST
+            }
+
+            UseCase(FinishPasskeyRegistration(
+                finish_passkey_registration::Error::NotFoundRegistration,
+            )) => StatusCode::NOT_FOUND,
+
+            UseCase(FinishPasskeyRegistration(
+                finish_passkey_registration::Error::InvalidCredential,
+            )) => StatusCode::BAD_REQUEST,
+
+            UseCase(FinishPasskeyAuthentication(
+                finish_passkey_authentication::Error::InvalidCredential,
+            )) => StatusCode::UNAUTHORIZED,
+
+            UseCase(CheckTotp(check_totp::Error::RequiredTotpCode)) => StatusCode::UNAUTHORIZED,
+
+            UseCase(CheckTotp(check_totp::Error::InvalidTotpCode)) => StatusCode::UNAUTHORIZED,
+
+            UseCase(CheckTotp(check_totp::Error::TooManyTotpAttempts)) => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+
+            UseCase(CreateTotp(create_totp::Error::AlreadyEnabledTotp)) => StatusCode::CONFLICT,
+
+            UseCase(EnableTotp(enable_totp::Error::NotFoundTotp)) => StatusCode::NOT_FOUND,
+
+            UseCase(EnableTotp(enable_totp::Error::InvalidTotpCode)) => StatusCode::BAD_REQUEST,
+
+            UseCase(RevokeSession(revoke_session::Error::InvalidRevokeLink)) => {
+                StatusCode::BAD_REQUEST
+            }
+
+            UseCase(RevokeSession(revoke_session::Error::NotFoundSession)) => StatusCode::NOT_FOUND,
+
+            UseCase(CreateServiceToken(create_service_token::Error::UnsupportedGrantType)) => {
+                StatusCode::BAD_REQUEST
+            }
+
+            UseCase(CreateServiceToken(create_service_token::Error::InvalidClient)) => {
+                response = response.header(header::WWW_AUTHENTICATE, "Basic");
+
+                StatusCode::UNAUTHORIZED
+            }
+
+            UseCase(CreateServiceToken(create_service_token::Error::InvalidScope)) => {
+                StatusCode::BAD_REQUEST
+            }
+
+            UseCase(CreateApiKey(create_api_key::Error::InvalidName)) => StatusCode::BAD_REQUEST,
+
+            UseCase(CreateApiKey(create_api_key::Error::InvalidExpiresIn)) => {
+                StatusCode::BAD_REQUEST
+            }
+
+            UseCase(CreateApiKey(create_api_key::Error::CreatedByApiKey)) => StatusCode::FORBIDDEN,
+
+            UseCase(CreateApiKey(create_api_key::Error::TooManyApiKeys)) => StatusCode::CONFLICT,
+
+            UseCase(DeleteApiKey(delete_api_key::Error::NotFoundApiKey)) => StatusCode::NOT_FOUND,
+
+            UseCase(ListAuditEvents(list_audit_events::Error::UnsupportedAuditSink(_))) => {
+                StatusCode::NOT_IMPLEMENTED
+            }
+
+            UseCase(IntrospectTokens(introspect_tokens::Error::TooManyTokens(_))) => {
+                StatusCode::BAD_REQUEST
+            }
+
+            Command(CommandError::GetUserInfo(get_user_info::Error::Undefined(code, _))) => code,
+
+            // user 서버에 직접 요청하므로 NotFoundUser 외의 sdk 에러는 만들어지지 않음
+            UserSdk(madome_sdk::api::user::Error::GetUser(
+                madome_sdk::api::user::get_user::Error::NotFoundUser,
+            )) => StatusCode::NOT_FOUND,
+
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        // 내부 에러 메시지는 로그에만 남기고 응답에는 숨김
+        let message = match message {
+            Some(message) if !status.is_server_error() || retryable => message,
+            _ => status
+                .canonical_reason()
+                .unwrap_or("Internal Server Error")
+                .to_string(),
+        };
+
+        let body = ErrorBody {
+            code,
+            message,
+            request_id,
+        };
+        let serialized = serde_json::to_vec(&body).expect("json serialize");
+
+        response
+            .status(status)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    code: &'static str,
+    message: String,
+    request_id: &'a str,
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{body, header, StatusCode};
+    use serde_json::Value;
+
+    use crate::usecase::check_access_token;
+
+    #[tokio::test]
+    async fn json_error_body() {
+        let err = crate::Error::from(check_access_token::Error::StaleAuthentication);
+
+        let response = err.to_http("request-id");
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        assert!(response.headers().contains_key(header::WWW_AUTHENTICATE));
+
+        let body = body::to_bytes(response.into_body()).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["code"], "stale_authentication");
+        assert_eq!(body["message"], "Stale authentication");
+        assert_eq!(body["request_id"], "request-id");
+    }
+
+    #[tokio::test]
+    async fn hide_internal_message() {
+        let err = crate::Error::from(redis::RedisError::from((
+            redis::ErrorKind::IoError,
+            "connection refused",
+            "10.0.0.1:6379".to_string(),
+        )));
+
+        let response = err.to_http("request-id");
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let body = body::to_bytes(response.into_body()).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["code"], "repository");
+        assert_eq!(body["message"], "Internal Server Error");
+    }
+
+    #[test]
+    fn overloaded() {
+        let response = crate::Error::Overloaded.to_http("request-id");
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "1");
     }
 }
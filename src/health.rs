@@ -0,0 +1,235 @@
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use sai::{Component, Injected};
+use serde::Serialize;
+
+use crate::{config::Config, database::DatabaseSet};
+
+/// readiness 검사 하나당 기다리는 최대 시간
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[cfg_attr(test, derive(Default))]
+#[derive(Component)]
+pub struct Health {
+    #[injected]
+    database: Injected<DatabaseSet>,
+
+    #[injected]
+    config: Injected<Config>,
+
+    http: reqwest::Client,
+
+    /// graceful shutdown 중이면 true
+    shutting_down: AtomicBool,
+}
+
+/// GET /healthz
+#[derive(Debug, Serialize)]
+pub struct Liveness {
+    pub status: &'static str,
+}
+
+/// GET /readyz
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: BTreeMap<&'static str, Check>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub ok: bool,
+    /// 실패해도 ready에 영향을 주지 않음
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub informational: bool,
+    /// 내부 에러 메시지는 로그에만 남김
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<&'static str>,
+}
+
+impl Check {
+    fn ok() -> Self {
+        Self {
+            ok: true,
+            informational: false,
+            error: None,
+        }
+    }
+
+    fn error(error: &'static str) -> Self {
+        Self {
+            ok: false,
+            informational: false,
+            error: Some(error),
+        }
+    }
+
+    fn informational(self) -> Self {
+        Self {
+            informational: true,
+            ..self
+        }
+    }
+}
+
+impl Health {
+    pub fn liveness(&self) -> Liveness {
+        Liveness { status: "ok" }
+    }
+
+    pub async fn readiness(&self) -> Readiness {
+        let mut checks = BTreeMap::new();
+
+        // k8s가 endpoint에서 빼는 동안 새로운 요청이 들어오지 않게 함
+        checks.insert(
+            "shutdown",
+            if self.shutting_down.load(Ordering::SeqCst) {
+                Check::error("shutting down")
+            } else {
+                Check::ok()
+            },
+        );
+
+        let (redis, user_service) = tokio::join!(self.check_redis(), self.check_user_service());
+
+        checks.insert("redis", redis);
+        // user 서버가 잠깐 죽었다고 모든 auth pod를 endpoint에서 빼면 token 확인까지 막힘
+        checks.insert("user_service", user_service.informational());
+
+        #[cfg(feature = "aws-ses")]
+        checks.insert("aws_ses", self.check_aws_ses());
+
+        Readiness {
+            ready: checks.values().all(|check| check.ok || check.informational),
+            checks,
+        }
+    }
+
+    /// 이후로는 readiness가 실패함
+    pub fn shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    async fn check_redis(&self) -> Check {
+        let ping = async {
+            let mut redis = self.database.redis().await?;
+
+            redis::cmd("PING")
+                .query_async::<_, String>(&mut redis)
+                .await
+        };
+
+        match tokio::time::timeout(CHECK_TIMEOUT, ping).await {
+            Ok(Ok(_)) => Check::ok(),
+            Ok(Err(err)) => {
+                log::warn!("readiness: redis: {}", err);
+
+                Check::error("unavailable")
+            }
+            Err(_) => Check::error("timeout"),
+        }
+    }
+
+    /// 응답이 오기만 하면 됨
+    async fn check_user_service(&self) -> Check {
+        let r = self
+            .http
+            .get(self.config.madome_user_url())
+            .timeout(CHECK_TIMEOUT)
+            .send()
+            .await;
+
+        match r {
+            Ok(_) => Check::ok(),
+            Err(err) if err.is_timeout() => Check::error("timeout"),
+            Err(err) => {
+                log::warn!("readiness: user service: {}", err);
+
+                Check::error("unavailable")
+            }
+        }
+    }
+
+    #[cfg(feature = "aws-ses")]
+    fn check_aws_ses(&self) -> Check {
+        let aws_config = self.config.aws_config();
+
+        if aws_config.region().is_none() {
+            Check::error("region is not set")
+        } else if aws_config.credentials_provider().is_none() {
+            Check::error("credentials are not set")
+        } else {
+            Check::ok()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sai::Injected;
+
+    use crate::{config::Config, database::DatabaseSet};
+
+    use super::Health;
+
+    /// 아무것도 listen하지 않는 port
+    fn health(redis_url: &str, madome_user_url: &str) -> Health {
+        let mut config = Config::default();
+        config.set_madome_user_url(madome_user_url);
+
+        Health {
+            database: Injected::new(DatabaseSet::from_redis_url(redis_url)),
+            config: Injected::new(config),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn user_service_is_informational() {
+        let health = health("redis://127.0.0.1:1", "http://127.0.0.1:1");
+
+        let r = health.readiness().await;
+
+        let user_service = &r.checks["user_service"];
+        assert!(!user_service.ok);
+        assert!(user_service.informational);
+
+        // redis는 필수
+        assert!(!r.checks["redis"].ok);
+        assert!(!r.ready);
+    }
+
+    #[tokio::test]
+    async fn hide_internal_error() {
+        let health = health("redis://127.0.0.1:1", "http://127.0.0.1:1");
+
+        let r = health.readiness().await;
+        let serialized = serde_json::to_value(&r).unwrap();
+
+        assert_eq!(serialized["checks"]["redis"]["error"], "unavailable");
+        assert_eq!(serialized["checks"]["user_service"]["error"], "unavailable");
+        assert_eq!(serialized["checks"]["user_service"]["informational"], true);
+        assert!(serialized["checks"]["shutdown"]
+            .get("informational")
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn shutdown() {
+        let health = health("redis://127.0.0.1:1", "http://127.0.0.1:1");
+
+        assert!(health.readiness().await.checks["shutdown"].ok);
+
+        health.shutdown();
+
+        let r = health.readiness().await;
+
+        assert!(!r.checks["shutdown"].ok);
+        assert_eq!(r.checks["shutdown"].error, Some("shutting down"));
+        assert!(!r.ready);
+    }
+}
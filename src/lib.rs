@@ -1,18 +1,30 @@
 extern crate util;
 
 pub mod app;
+pub mod audit;
+pub mod cli;
 pub mod command;
 pub mod config;
+pub mod cookie;
+pub mod cors;
+pub mod csrf;
 pub mod database;
 pub mod entity;
 pub mod error;
+pub mod health;
+pub mod listener;
+pub mod metrics;
 pub mod model;
 pub mod msg;
+pub mod openapi;
 pub mod registry;
 pub mod repository;
+pub mod router;
+pub mod telemetry;
+pub mod tls;
 pub mod usecase;
 
-pub use registry::RootRegistry;
+pub use registry::{CliRegistry, RootRegistry};
 
 use error::Error;
 
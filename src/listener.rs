@@ -0,0 +1,293 @@
+use std::{
+    future::Future,
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::{future, stream, Stream, StreamExt};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{TcpListener, TcpStream, UnixListener, UnixStream},
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::{Instant, Sleep},
+};
+use tokio_rustls::{server::TlsStream, TlsAcceptor};
+
+/// accept가 실패하면(fd 부족 등) 잠깐 쉬었다가 다시 시도함
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(100);
+
+/// 이 시간 안에 handshake를 끝내지 못하면 연결을 끊음
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 동시에 진행하는 handshake 수
+const MAX_TLS_HANDSHAKES: usize = 64;
+
+/// msg::client에서 클라이언트의 주소로 사용함
+pub trait RemoteAddr {
+    fn remote_addr(&self) -> Option<SocketAddr>;
+}
+
+impl RemoteAddr for TcpStream {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr().ok()
+    }
+}
+
+impl RemoteAddr for TlsStream<TcpStream> {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.get_ref().0.peer_addr().ok()
+    }
+}
+
+impl RemoteAddr for UnixStream {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+}
+
+/// 연결이 끝나면 permit을 돌려줌
+///
+/// idle_timeout 동안 읽거나 쓴 게 없으면 TimedOut으로 연결을 끝냄
+pub struct Limited<IO> {
+    io: IO,
+    idle_timeout: Duration,
+    idle: Pin<Box<Sleep>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<IO> Limited<IO> {
+    fn new(io: IO, idle_timeout: Duration, permit: OwnedSemaphorePermit) -> Self {
+        Self {
+            io,
+            idle_timeout,
+            idle: Box::pin(tokio::time::sleep(idle_timeout)),
+            _permit: permit,
+        }
+    }
+
+    /// 진행이 있으면 idle을 다시 시작하고, 없으면 idle이 끝났는지 확인함
+    fn poll_idle<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        poll: Poll<io::Result<T>>,
+    ) -> Poll<io::Result<T>> {
+        match poll {
+            Poll::Ready(r) => {
+                let deadline = Instant::now() + self.idle_timeout;
+                self.idle.as_mut().reset(deadline);
+
+                Poll::Ready(r)
+            }
+            Poll::Pending => match self.idle.as_mut().poll(cx) {
+                Poll::Ready(()) => {
+                    Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, "idle timeout")))
+                }
+                Poll::Pending => Poll::Pending,
+            },
+        }
+    }
+}
+
+impl<IO: RemoteAddr> RemoteAddr for Limited<IO> {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.io.remote_addr()
+    }
+}
+
+impl<IO: AsyncRead + Unpin> AsyncRead for Limited<IO> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let poll = Pin::new(&mut self.io).poll_read(cx, buf);
+        self.poll_idle(cx, poll)
+    }
+}
+
+impl<IO: AsyncWrite + Unpin> AsyncWrite for Limited<IO> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.io).poll_write(cx, buf);
+        self.poll_idle(cx, poll)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.io).poll_write_vectored(cx, bufs);
+        self.poll_idle(cx, poll)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
+
+/// 연결 수가 max_connections에 닿으면 넘는 연결은 accept한 뒤 바로 끊음
+///
+/// accept를 멈추면 backlog에 쌓인 연결이 끝없이 기다리게 됨
+pub fn limit<IO>(
+    incoming: impl Stream<Item = io::Result<IO>>,
+    max_connections: usize,
+    idle_timeout: Duration,
+) -> impl Stream<Item = io::Result<Limited<IO>>> {
+    let semaphore = Arc::new(Semaphore::new(max_connections));
+
+    incoming.filter_map(move |r| {
+        let r = match r {
+            Ok(io) => match Arc::clone(&semaphore).try_acquire_owned() {
+                Ok(permit) => Some(Ok(Limited::new(io, idle_timeout, permit))),
+                Err(_) => {
+                    log::warn!("connection shed: max_connections = {}", max_connections);
+                    crate::metrics::CONNECTIONS_SHED.inc();
+
+                    // drop하면서 연결을 닫음
+                    None
+                }
+            },
+            Err(err) => Some(Err(err)),
+        };
+
+        future::ready(r)
+    })
+}
+
+fn accept(listener: TcpListener) -> impl Stream<Item = TcpStream> {
+    stream::unfold(listener, |listener| async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => return Some((stream, listener)),
+                Err(err) => {
+                    log::error!("accept: {}", err);
+
+                    tokio::time::sleep(ACCEPT_ERROR_DELAY).await;
+                }
+            }
+        }
+    })
+}
+
+pub fn tcp(listener: TcpListener) -> impl Stream<Item = io::Result<TcpStream>> {
+    accept(listener).map(Ok)
+}
+
+pub fn tls(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+) -> impl Stream<Item = io::Result<TlsStream<TcpStream>>> {
+    accept(listener)
+        .map(move |stream| {
+            let acceptor = acceptor.clone();
+
+            tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, async move {
+                acceptor.accept(stream).await
+            })
+        })
+        // handshake가 느린 연결이 다른 연결을 막지 않게 함
+        .buffer_unordered(MAX_TLS_HANDSHAKES)
+        .filter_map(|r| async move {
+            match r {
+                Ok(Ok(stream)) => Some(Ok(stream)),
+                Ok(Err(err)) => {
+                    log::debug!("tls handshake: {}", err);
+                    None
+                }
+                Err(_) => {
+                    log::debug!("tls handshake: timed out");
+                    None
+                }
+            }
+        })
+}
+
+pub fn unix(listener: UnixListener) -> impl Stream<Item = io::Result<UnixStream>> {
+    stream::unfold(listener, |listener| async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => return Some((Ok(stream), listener)),
+                Err(err) => {
+                    log::error!("accept: {}", err);
+
+                    tokio::time::sleep(ACCEPT_ERROR_DELAY).await;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::StreamExt;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::{limit, tcp};
+
+    const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+    #[tokio::test]
+    async fn shed_excess_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut incoming = Box::pin(limit(tcp(listener), 1, IDLE_TIMEOUT));
+
+        let _first = TcpStream::connect(addr).await.unwrap();
+        let accepted = incoming.next().await.unwrap().unwrap();
+
+        // 넘는 연결은 기다리지 않고 닫힘
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        let next = tokio::time::timeout(Duration::from_millis(200), incoming.next());
+
+        assert!(next.await.is_err());
+        assert_eq!(second.read(&mut [0; 1]).await.unwrap(), 0);
+
+        // 연결이 끝나면 다시 받음
+        drop(accepted);
+
+        let _third = TcpStream::connect(addr).await.unwrap();
+        let next = tokio::time::timeout(Duration::from_secs(1), incoming.next());
+
+        assert!(next.await.unwrap().unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn idle_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut incoming = Box::pin(limit(tcp(listener), 1, Duration::from_millis(100)));
+
+        let _client = TcpStream::connect(addr).await.unwrap();
+        let mut accepted = incoming.next().await.unwrap().unwrap();
+
+        let err = accepted.read(&mut [0; 1]).await.unwrap_err();
+
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+        // idle로 끊긴 연결의 permit도 돌려받음
+        drop(accepted);
+
+        let _client = TcpStream::connect(addr).await.unwrap();
+
+        assert!(incoming.next().await.unwrap().is_ok());
+    }
+}
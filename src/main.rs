@@ -1,15 +1,36 @@
-use log::Level;
-use madome_auth::{release, RootRegistry};
+use madome_auth::{config, release, telemetry, RootRegistry};
 use sai::System;
 use tokio::signal::{self, unix::SignalKind};
 
 #[tokio::main]
 async fn main() {
+    // OTEL_EXPORTER_OTLP_ENDPOINT를 .env에서 읽을 수 있게 Config보다 먼저 불러옴
+    dotenv::dotenv().ok();
+
+    if std::env::args().any(|x| x == "--check-config") {
+        match config::check() {
+            Ok(report) => println!("{}", report),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+
+        return;
+    }
+
     println!("Hello, world!");
 
-    let log_level = if release() { Level::Info } else { Level::Debug };
+    // config file이나 *_FILE로 지정한 값도 telemetry가 읽을 수 있게 함
+    config::export_env();
 
-    simple_logger::init_with_level(log_level).unwrap();
+    let log_filter = if release() {
+        "info"
+    } else {
+        "madome_auth=debug,info"
+    };
+
+    telemetry::init(log_filter);
 
     let mut system = System::<RootRegistry>::new();
 
@@ -25,4 +46,6 @@ async fn main() {
     system.stop().await;
 
     log::info!("gracefully shutdown the app");
+
+    telemetry::shutdown();
 }
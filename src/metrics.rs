@@ -0,0 +1,191 @@
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_histogram_vec, register_int_counter, register_int_counter_vec, Encoder, HistogramVec,
+    IntCounter, IntCounterVec, TextEncoder,
+};
+use ring::constant_time;
+
+use crate::msg;
+
+pub static HTTP_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "madome_auth_http_requests_total",
+        "Number of http requests by msg and status",
+        &["msg", "status"]
+    )
+    .unwrap()
+});
+
+pub static HTTP_REQUEST_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "madome_auth_http_request_duration_seconds",
+        "Latency of http requests by msg and status",
+        &["msg", "status"]
+    )
+    .unwrap()
+});
+
+pub static CONNECTIONS_SHED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "madome_auth_connections_shed_total",
+        "Number of connections closed over max_connections"
+    )
+    .unwrap()
+});
+
+pub static AUTHCODES_CREATED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "madome_auth_authcodes_created_total",
+        "Number of created authcodes"
+    )
+    .unwrap()
+});
+
+pub static AUTHCODES_FAILED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "madome_auth_authcodes_failed_total",
+        "Number of invalid authcodes"
+    )
+    .unwrap()
+});
+
+pub static TOKENS_ISSUED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "madome_auth_tokens_issued_total",
+        "Number of issued token pairs by subject",
+        &["subject"]
+    )
+    .unwrap()
+});
+
+pub static TOKEN_REFRESHES: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "madome_auth_token_refreshes_total",
+        "Number of refreshed token pairs"
+    )
+    .unwrap()
+});
+
+pub static TOKEN_REVOCATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "madome_auth_token_revocations_total",
+        "Number of revoked token pairs by reason",
+        &["reason"]
+    )
+    .unwrap()
+});
+
+pub static AUTH_DENIED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "madome_auth_denied_total",
+        "Number of permission denied or unauthorized outcomes by error code",
+        &["outcome", "code"]
+    )
+    .unwrap()
+});
+
+pub static REDIS_COMMAND_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "madome_auth_redis_command_duration_seconds",
+        "Latency of redis commands",
+        &["command"],
+        vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]
+    )
+    .unwrap()
+});
+
+pub static USER_SERVICE_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "madome_auth_user_service_request_duration_seconds",
+        "Latency of requests to the user service",
+        &["status"]
+    )
+    .unwrap()
+});
+
+/// PermissionDenied, Unauthorized로 끝난 요청을 셈
+pub fn record_error(err: &crate::Error) {
+    let code = err.code();
+
+    let outcome = match code {
+        "permission_denied" | "required_mfa" => "permission_denied",
+        "unauthorized_access_token"
+        | "unauthorized_refresh_token"
+        | "invalid_token_pair"
+        | "stale_authentication"
+        | "unauthorized_metrics_token" => "unauthorized",
+        _ => return,
+    };
+
+    AUTH_DENIED.with_label_values(&[outcome, code]).inc();
+}
+
+/// GET /metrics
+#[derive(Debug)]
+pub struct Metrics(pub Vec<u8>);
+
+/// /metrics는 public port에서 열리므로 METRICS_TOKEN을 아는 scraper에게만 보여줌
+///
+/// METRICS_TOKEN이 설정되지 않았으면 route가 없는 것처럼 응답함
+pub fn authorize(token: Option<&str>, metrics_token: Option<&str>) -> crate::Result<()> {
+    let metrics_token = metrics_token.ok_or(msg::Error::NotFound)?;
+
+    let authorized = token
+        .map(|token| {
+            constant_time::verify_slices_are_equal(token.as_bytes(), metrics_token.as_bytes())
+                .is_ok()
+        })
+        .unwrap_or(false);
+
+    if authorized {
+        Ok(())
+    } else {
+        Err(msg::Error::UnauthorizedMetricsToken.into())
+    }
+}
+
+pub fn gather() -> Metrics {
+    let encoder = TextEncoder::new();
+    let mut buf = Vec::new();
+
+    encoder
+        .encode(&prometheus::gather(), &mut buf)
+        .expect("encode metrics");
+
+    Metrics(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::usecase::check_access_token;
+
+    use super::{authorize, gather, record_error};
+
+    #[test]
+    fn authorize_metrics_token() {
+        assert!(authorize(Some("secret1234"), Some("secret1234")).is_ok());
+
+        let r = authorize(Some("secret"), Some("secret1234")).unwrap_err();
+        assert_eq!(r.code(), "unauthorized_metrics_token");
+
+        let r = authorize(None, Some("secret1234")).unwrap_err();
+        assert_eq!(r.code(), "unauthorized_metrics_token");
+
+        // METRICS_TOKEN이 없으면 열지 않음
+        let r = authorize(Some("secret1234"), None).unwrap_err();
+        assert_eq!(r.code(), "not_found");
+    }
+
+    #[test]
+    fn record_permission_denied() {
+        let err = crate::Error::from(check_access_token::Error::PermissionDenied);
+
+        record_error(&err);
+
+        let metrics = String::from_utf8(gather().0).unwrap();
+
+        assert!(metrics.contains(
+            r#"madome_auth_denied_total{code="permission_denied",outcome="permission_denied"}"#
+        ));
+    }
+}
@@ -3,10 +3,16 @@ use madome_sdk::api::cookie::{MADOME_ACCESS_TOKEN, MADOME_REFRESH_TOKEN};
 use util::http::{SetCookie, SetCookieOptions, SetHeaders};
 
 use crate::{
+    health::{Liveness, Readiness},
     into_model,
+    metrics::Metrics,
+    openapi::OpenApi,
     usecase::{
-        check_access_token, check_and_refresh_token_pair, create_authcode, create_token_pair,
-        delete_token_pair, refresh_token_pair,
+        check_access_token, check_and_refresh_token_pair, check_service_token, clear_authcodes,
+        create_api_key, create_authcode, create_service_token, create_token_pair, create_totp,
+        delete_api_key, delete_token_pair, enable_totp, finish_passkey_registration,
+        introspect_tokens, list_api_keys, list_audit_events, list_sessions, refresh_token_pair,
+        revoke_session, revoke_sessions, start_passkey_authentication, start_passkey_registration,
     },
 };
 
@@ -21,9 +27,40 @@ into_model![
     (TokenPair, TokenPair),
     (CreateAuthcode, create_authcode::Model),
     (CheckAccessToken, check_access_token::Model),
+    (
+        CheckAndRefreshTokenPair,
+        check_and_refresh_token_pair::Model
+    ),
+    (IntrospectTokens, introspect_tokens::Model),
     (RefreshTokenPair, refresh_token_pair::Model),
     (CreateTokenPair, create_token_pair::Model),
     (DeleteTokenPair, delete_token_pair::Model),
+    (StartPasskeyRegistration, start_passkey_registration::Model),
+    (
+        FinishPasskeyRegistration,
+        finish_passkey_registration::Model
+    ),
+    (
+        StartPasskeyAuthentication,
+        start_passkey_authentication::Model
+    ),
+    (CreateTotp, create_totp::Model),
+    (EnableTotp, enable_totp::Model),
+    (ConfirmRevokeSession, revoke_session::Confirmation),
+    (RevokeSession, revoke_session::Model),
+    (ListSessions, list_sessions::Model),
+    (RevokeSessions, revoke_sessions::Model),
+    (ClearAuthcodes, clear_authcodes::Model),
+    (ListAuditEvents, list_audit_events::Model),
+    (CheckServiceToken, check_service_token::Model),
+    (CreateServiceToken, create_service_token::Model),
+    (CreateApiKey, create_api_key::Model),
+    (ListApiKeys, list_api_keys::Model),
+    (DeleteApiKey, delete_api_key::Model),
+    (OpenApi, OpenApi),
+    (Metrics, Metrics),
+    (Liveness, Liveness),
+    (Readiness, Readiness),
 ];
 
 pub trait Presenter: Sized {
@@ -41,10 +78,10 @@ impl Presenter for create_authcode::Model {
     }
 }
 
+/// Domain, Max-Age, SameSite는 응답하기 전에 설정의 값으로 붙임 (CookieOptions::apply)
 impl From<TokenPair> for SetCookie {
     fn from(model: TokenPair) -> Self {
         let set_cookie_options = SetCookieOptions::new()
-            .domain("madome.app")
             .path("/")
             .http_only(true)
             .secure(true);
@@ -53,12 +90,12 @@ impl From<TokenPair> for SetCookie {
             .set(
                 MADOME_ACCESS_TOKEN,
                 model.access_token,
-                set_cookie_options.clone().max_age(3600 * 24 * 7),
+                set_cookie_options.clone(),
             )
             .set(
                 MADOME_REFRESH_TOKEN,
                 model.refresh_token,
-                set_cookie_options.max_age(3600 * 24 * 7),
+                set_cookie_options,
             )
     }
 }
@@ -115,6 +152,19 @@ impl Presenter for check_and_refresh_token_pair::Model {
     }
 }
 
+impl Presenter for introspect_tokens::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CACHE_CONTROL, "no-store")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
 impl Presenter for refresh_token_pair::Model {
     fn to_http(self, response: ResponseBuilder) -> Response<Body> {
         let token_pair = TokenPair {
@@ -140,7 +190,6 @@ impl Presenter for create_token_pair::Model {
 impl Presenter for delete_token_pair::Model {
     fn to_http(self, response: ResponseBuilder) -> Response<Body> {
         let max_age_0 = SetCookieOptions::new()
-            .domain("madome.app")
             .path("/")
             .http_only(true)
             .secure(true)
@@ -159,6 +208,255 @@ impl Presenter for delete_token_pair::Model {
     }
 }
 
+impl Presenter for start_passkey_registration::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for finish_passkey_registration::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        response
+            .status(StatusCode::CREATED)
+            .body(Body::empty())
+            .unwrap()
+    }
+}
+
+impl Presenter for start_passkey_authentication::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for create_totp::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::CREATED)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for enable_totp::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        response
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap()
+    }
+}
+
+impl Presenter for revoke_session::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        // 메일의 링크를 눌러서 브라우저로 들어오는 요청임
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body("The session has been revoked.".into())
+            .unwrap()
+    }
+}
+
+impl Presenter for revoke_session::Confirmation {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        // action이 없으면 query string의 token과 함께 지금 주소로 POST함
+        const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Revoke session</title></head>
+<body>
+<p>If you did not sign in from this device, revoke the session.</p>
+<form method="post"><button type="submit">Revoke session</button></form>
+</body>
+</html>
+"#;
+
+        // POST에 Origin이 붙어야 CSRF 확인을 통과함
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+            .header(header::CACHE_CONTROL, "no-store")
+            .header(header::REFERRER_POLICY, "same-origin")
+            .body(PAGE.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for list_sessions::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for revoke_sessions::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for clear_authcodes::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for list_audit_events::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for check_service_token::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for create_service_token::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            // RFC 6749 5.1
+            .header(header::CACHE_CONTROL, "no-store")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for create_api_key::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::CREATED)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CACHE_CONTROL, "no-store")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for list_api_keys::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for delete_api_key::Model {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        response
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap()
+    }
+}
+
+impl Presenter for OpenApi {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(self.0).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for Metrics {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)
+            .body(self.0.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for Liveness {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
+impl Presenter for Readiness {
+    fn to_http(self, response: ResponseBuilder) -> Response<Body> {
+        let serialized = serde_json::to_vec(&self).expect("json serialize");
+
+        let status = if self.ready {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+
+        response
+            .status(status)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serialized.into())
+            .unwrap()
+    }
+}
+
 #[macro_export]
 macro_rules! into_model {
     ($(($member:ident, $from:ty)),*,) => {
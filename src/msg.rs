@@ -1,20 +1,50 @@
-use std::convert::TryInto;
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+use either::Either;
+use futures_util::{future::BoxFuture, FutureExt};
 
-use hyper::{http::response::Builder as ResponseBuilder, Body, Method, Request};
+use hyper::{
+    body::HttpBody, header, http::response::Builder as ResponseBuilder, Body, Method, Request,
+};
+use once_cell::sync::Lazy;
 use serde::de::DeserializeOwned;
+use uuid::Uuid;
 
-use util::{r#async::AsyncTryFrom, IntoPayload, ReadChunks};
+use util::{r#async::AsyncTryFrom, IntoPayload};
 
+use crate::entity::session::Client;
+use crate::router::{self, Match, Params, Router};
 use crate::usecase::{
-    check_access_token, check_authcode, create_authcode, delete_token_pair, refresh_token_pair,
+    check_access_token, check_and_refresh_token_pair, check_authcode, clear_authcodes,
+    create_api_key, create_authcode, create_service_token, create_totp, delete_api_key,
+    delete_token_pair, enable_totp, finish_passkey_authentication, finish_passkey_registration,
+    introspect_tokens, list_api_keys, list_audit_events, list_sessions, refresh_token_pair,
+    revoke_session, revoke_sessions, start_passkey_authentication, start_passkey_registration,
 };
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Not found")]
     NotFound,
+    #[error("Method not allowed")]
+    MethodNotAllowed(Vec<Method>),
     #[error("Json deserialize: {0}")]
     JsonDeserializePayload(serde_json::Error),
+    #[error("Form deserialize: {0}")]
+    FormDeserializePayload(serde_urlencoded::de::Error),
+    #[error("Required query: {0}")]
+    RequiredQuery(&'static str),
+    #[error("Payload too large: limit is {0} bytes")]
+    PayloadTooLarge(usize),
+    #[error("Cross-site request rejected")]
+    CrossSiteRequest,
+    #[error("Unauthorized metrics token")]
+    UnauthorizedMetricsToken,
 }
 
 /// Msg의 Payload는 같은 이름의 usecase의 Payload와는 관계가 없음
@@ -27,37 +57,485 @@ pub enum Msg {
     CreateTokenPair(check_authcode::Payload),
     // RefreshTokenPair(check_token_pair::Payload),
     CheckAccessToken(check_access_token::Payload),
+    CheckAndRefreshTokenPair(check_and_refresh_token_pair::Payload),
+    IntrospectTokens(introspect_tokens::Payload),
     RefreshTokenPair(refresh_token_pair::Payload),
     DeleteTokenPair(delete_token_pair::Payload),
+    StartPasskeyRegistration(start_passkey_registration::Payload),
+    FinishPasskeyRegistration(finish_passkey_registration::Payload),
+    StartPasskeyAuthentication(start_passkey_authentication::Payload),
+    CreateTokenPairByPasskey(finish_passkey_authentication::Payload),
+    CreateTotp(create_totp::Payload),
+    EnableTotp(enable_totp::Payload),
+    ConfirmRevokeSession,
+    RevokeSession(revoke_session::Payload),
+    ListSessions(list_sessions::Payload),
+    RevokeSessions(revoke_sessions::Payload),
+    ClearAuthcodes(clear_authcodes::Payload),
+    ListAuditEvents(list_audit_events::Payload),
+    CreateServiceToken(create_service_token::Payload),
+    CreateApiKey(create_api_key::Payload),
+    ListApiKeys(list_api_keys::Payload),
+    DeleteApiKey(delete_api_key::Payload),
+    OpenApi,
+    /// `Authorization: Bearer <METRICS_TOKEN>`
+    Metrics(Option<String>),
+    Liveness,
+    Readiness,
 }
 
 impl Msg {
+    /// span, log에 사용함
+    pub fn name(&self) -> &'static str {
+        match self {
+            Msg::CreateAuthcode(_) => "CreateAuthcode",
+            Msg::CreateTokenPair(_) => "CreateTokenPair",
+            Msg::CheckAccessToken(_) => "CheckAccessToken",
+            Msg::CheckAndRefreshTokenPair(_) => "CheckAndRefreshTokenPair",
+            Msg::IntrospectTokens(_) => "IntrospectTokens",
+            Msg::RefreshTokenPair(_) => "RefreshTokenPair",
+            Msg::DeleteTokenPair(_) => "DeleteTokenPair",
+            Msg::StartPasskeyRegistration(_) => "StartPasskeyRegistration",
+            Msg::FinishPasskeyRegistration(_) => "FinishPasskeyRegistration",
+            Msg::StartPasskeyAuthentication(_) => "StartPasskeyAuthentication",
+            Msg::CreateTokenPairByPasskey(_) => "CreateTokenPairByPasskey",
+            Msg::CreateTotp(_) => "CreateTotp",
+            Msg::EnableTotp(_) => "EnableTotp",
+            Msg::ConfirmRevokeSession => "ConfirmRevokeSession",
+            Msg::RevokeSession(_) => "RevokeSession",
+            Msg::ListSessions(_) => "ListSessions",
+            Msg::RevokeSessions(_) => "RevokeSessions",
+            Msg::ClearAuthcodes(_) => "ClearAuthcodes",
+            Msg::ListAuditEvents(_) => "ListAuditEvents",
+            Msg::CreateServiceToken(_) => "CreateServiceToken",
+            Msg::CreateApiKey(_) => "CreateApiKey",
+            Msg::ListApiKeys(_) => "ListApiKeys",
+            Msg::DeleteApiKey(_) => "DeleteApiKey",
+            Msg::OpenApi => "OpenApi",
+            Msg::Metrics(_) => "Metrics",
+            Msg::Liveness => "Liveness",
+            Msg::Readiness => "Readiness",
+        }
+    }
+
     pub async fn from_http(
-        request: Request<Body>,
+        mut request: Request<Body>,
         response: ResponseBuilder,
+        base_path: &str,
     ) -> crate::Result<(Self, ResponseBuilder)> {
-        let method = request.method().clone();
-        let path = request.uri().path();
+        let path = router::strip_prefix(request.uri().path(), base_path);
+
+        let (parse, params) = match ROUTER.find(request.method(), path) {
+            Match::Found(parse, params) => (parse, params),
+            Match::MethodNotAllowed(allow) => return Err(Error::MethodNotAllowed(allow).into()),
+            Match::NotFound => return Err(Error::NotFound.into()),
+        };
+
+        // payload를 만들 때 param()으로 꺼내 씀
+        request.extensions_mut().insert(params);
+
+        let msg = parse(request).await?;
+
+        Ok((msg, response))
+    }
+}
+
+type Parse = fn(Request<Body>) -> BoxFuture<'static, crate::Result<Msg>>;
+
+macro_rules! parse {
+    ($request:ident => $msg:expr) => {
+        |$request: Request<Body>| -> BoxFuture<'static, crate::Result<Msg>> {
+            async move { Ok::<_, crate::Error>($msg) }.boxed()
+        }
+    };
+}
+
+/// route를 추가할 때는 여기에만 추가하면 됨
+pub static ROUTER: Lazy<Router<Parse>> = Lazy::new(|| {
+    Router::<Parse>::new()
+        .route(
+            Method::GET,
+            "/auth/token",
+            parse!(request => Msg::CheckAccessToken(request.try_into()?)),
+        )
+        .route(
+            Method::POST,
+            "/auth/token",
+            parse!(request => Msg::CreateTokenPair(request.into_payload(()).await?)),
+        )
+        .route(
+            Method::PATCH,
+            "/auth/token",
+            parse!(request => Msg::RefreshTokenPair(request.try_into()?)),
+        )
+        .route(
+            Method::DELETE,
+            "/auth/token",
+            parse!(request => Msg::DeleteTokenPair(request.try_into()?)),
+        )
+        // gateway가 확인과 갱신을 한번에 요청함
+        .route(
+            Method::POST,
+            "/auth/token/check",
+            parse!(request => Msg::CheckAndRefreshTokenPair(request.try_into()?)),
+        )
+        .route(
+            Method::POST,
+            "/auth/token/introspect",
+            parse!(request => Msg::IntrospectTokens(request.into_payload(()).await?)),
+        )
+        .route(
+            Method::POST,
+            "/auth/code",
+            parse!(request => Msg::CreateAuthcode(request.into_payload(()).await?)),
+        )
+        .route(
+            Method::POST,
+            "/auth/webauthn/registration",
+            parse!(request => Msg::StartPasskeyRegistration(request.try_into()?)),
+        )
+        .route(
+            Method::PATCH,
+            "/auth/webauthn/registration",
+            parse!(request => Msg::FinishPasskeyRegistration(request.into_payload(()).await?)),
+        )
+        .route(
+            Method::POST,
+            "/auth/webauthn/authentication",
+            parse!(request => {
+                Msg::StartPasskeyAuthentication(Wrap::async_try_from(request).await?.inner())
+            }),
+        )
+        .route(
+            Method::PATCH,
+            "/auth/webauthn/authentication",
+            parse!(request => Msg::CreateTokenPairByPasskey(request.into_payload(()).await?)),
+        )
+        .route(
+            Method::POST,
+            "/auth/totp",
+            parse!(request => Msg::CreateTotp(request.try_into()?)),
+        )
+        .route(
+            Method::PATCH,
+            "/auth/totp",
+            parse!(request => Msg::EnableTotp(request.into_payload(()).await?)),
+        )
+        // 새 기기 로그인 알림 메일의 링크, 메일 보안 검사가 링크를 열어봐도 revoke되지 않게 GET은 확인만 받음
+        .route(
+            Method::GET,
+            "/auth/sessions/revoke",
+            parse!(_request => Msg::ConfirmRevokeSession),
+        )
+        .route(
+            Method::POST,
+            "/auth/sessions/revoke",
+            parse!(request => Msg::RevokeSession(request.try_into()?)),
+        )
+        // admin
+        .route(
+            Method::GET,
+            "/auth/admin/sessions",
+            parse!(request => Msg::ListSessions(request.try_into()?)),
+        )
+        .route(
+            Method::DELETE,
+            "/auth/admin/sessions",
+            parse!(request => Msg::RevokeSessions(request.try_into()?)),
+        )
+        .route(
+            Method::DELETE,
+            "/auth/admin/sessions/{session_id}",
+            parse!(request => Msg::RevokeSessions(request.try_into()?)),
+        )
+        .route(
+            Method::DELETE,
+            "/auth/admin/authcodes",
+            parse!(request => Msg::ClearAuthcodes(request.try_into()?)),
+        )
+        .route(
+            Method::GET,
+            "/auth/admin/audit",
+            parse!(request => Msg::ListAuditEvents(request.try_into()?)),
+        )
+        .route(
+            Method::POST,
+            "/auth/api-keys",
+            parse!(request => Msg::CreateApiKey(request.into_payload(()).await?)),
+        )
+        .route(
+            Method::GET,
+            "/auth/api-keys",
+            parse!(request => Msg::ListApiKeys(request.try_into()?)),
+        )
+        .route(
+            Method::DELETE,
+            "/auth/api-keys",
+            parse!(request => Msg::DeleteApiKey(request.try_into()?)),
+        )
+        .route(
+            Method::DELETE,
+            "/auth/api-keys/{id}",
+            parse!(request => Msg::DeleteApiKey(request.try_into()?)),
+        )
+        // service client
+        .route(
+            Method::POST,
+            "/auth/service/token",
+            parse!(request => Msg::CreateServiceToken(request.into_payload(()).await?)),
+        )
+        .route(
+            Method::GET,
+            "/auth/openapi.json",
+            parse!(_request => Msg::OpenApi),
+        )
+        .route(
+            Method::GET,
+            "/metrics",
+            parse!(request => Msg::Metrics(bearer_token(&request))),
+        )
+        .route(Method::GET, "/healthz", parse!(_request => Msg::Liveness))
+        .route(Method::GET, "/readyz", parse!(_request => Msg::Readiness))
+});
 
-        // log::debug!("request headers = {:?}", request.headers());
+/// `/auth/api-keys/{id}`의 id
+pub fn param<'a>(request: &'a Request<Body>, name: &str) -> Option<&'a str> {
+    request.extensions().get::<Params>()?.get(name)
+}
+
+/// admin api에서 대상 유저를 `?user_id=` 또는 `?email=`로 받음
+pub fn user_id_or_email(qs: &HashMap<&str, &str>) -> crate::Result<Either<Uuid, String>> {
+    if let Some(user_id) = qs.get("user_id").and_then(|v| v.parse().ok()) {
+        return Ok(Either::Left(user_id));
+    }
 
-        // cfg(feature = "production")
-        // TODO: 이걸 써야하는 곳을 잘 생각해 인증쪽에서
+    match qs.get("email") {
+        Some(email) if !email.is_empty() => Ok(Either::Right(email.to_string())),
+        _ => Err(Error::RequiredQuery("user_id or email").into()),
+    }
+}
+
+/// `Authorization: Bearer <token>`
+pub fn bearer_token(request: &Request<Body>) -> Option<String> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+}
+
+/// `Authorization: Basic <base64(client_id:client_secret)>` (RFC 6749 2.3.1)
+///
+/// client_id와 client_secret은 application/x-www-form-urlencoded로 인코딩된 뒤에 base64로 인코딩됨
+pub fn basic_credentials(request: &Request<Body>) -> Option<(String, String)> {
+    let encoded = request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?
+        .trim();
+
+    let decoded = String::from_utf8(base64::decode(encoded).ok()?).ok()?;
+    let (id, secret) = decoded.split_once(':')?;
+
+    let form_decode = |x: &str| {
+        percent_encoding::percent_decode_str(&x.replace('+', " "))
+            .decode_utf8()
+            .ok()
+            .map(|x| x.into_owned())
+    };
+
+    Some((form_decode(id)?, form_decode(secret)?))
+}
+
+/// 클라이언트가 직접 알려주는 기기 이름
+pub const DEVICE_NAME: &str = "x-madome-device-name";
+
+/// 요청마다 붙는 id, 에러 응답과 로그에서 요청을 찾을 때 사용함
+pub const REQUEST_ID: &str = "x-request-id";
+
+tokio::task_local! {
+    /// 지금 처리하고 있는 요청의 id, 다른 서비스에 요청할 때 X-Request-Id로 넘겨줌
+    pub static CURRENT_REQUEST_ID: String;
+
+    /// 지금 처리하고 있는 요청의 클라이언트, audit log에 사용함
+    pub static CURRENT_CLIENT: Client;
+}
+
+/// 클라이언트나 gateway가 보낸 X-Request-Id가 있으면 그대로 쓰고 없으면 새로 만듦
+pub fn request_id(request: &Request<Body>) -> String {
+    request
+        .headers()
+        .get(REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= 128)
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+/// X-Forwarded-For를 믿을 수 있는 reverse proxy, `10.0.0.1` 또는 `10.0.0.0/8`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrustedProxy {
+    addr: IpAddr,
+    prefix: u8,
+}
 
-        let msg = match (method, path) {
-            (Method::GET, "/auth/token") => Msg::CheckAccessToken(request.try_into()?),
-            (Method::POST, "/auth/token") => {
-                Msg::CreateTokenPair(Wrap::async_try_from(request).await?.inner())
+impl TrustedProxy {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let (addr, ip, bits) = match (self.addr, ip) {
+            (IpAddr::V4(addr), IpAddr::V4(ip)) => {
+                (u32::from(addr) as u128, u32::from(ip) as u128, 32)
             }
-            (Method::PATCH, "/auth/token") => Msg::RefreshTokenPair(request.try_into()?),
-            (Method::DELETE, "/auth/token") => Msg::DeleteTokenPair(request.try_into()?),
-            (Method::POST, "/auth/code") => Msg::CreateAuthcode(request.into_payload(()).await?),
+            (IpAddr::V6(addr), IpAddr::V6(ip)) => (u128::from(addr), u128::from(ip), 128),
+            _ => return false,
+        };
+
+        let shift = bits - self.prefix as u32;
+
+        shift >= bits || addr >> shift == ip >> shift
+    }
+}
 
-            _ => return Err(Error::NotFound.into()),
+impl FromStr for TrustedProxy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
         };
 
-        Ok((msg, response))
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|_| format!("invalid address `{}`", s))?;
+        let bits = if addr.is_ipv4() { 32 } else { 128 };
+
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|x| *x <= bits)
+                .ok_or_else(|| format!("invalid prefix `{}`", s))?,
+            None => bits,
+        };
+
+        Ok(Self { addr, prefix })
+    }
+}
+
+/// HttpServer가 request extension으로 넣어줌, 없으면 아무 proxy도 믿지 않음
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrustedProxies(pub Vec<TrustedProxy>);
+
+impl TrustedProxies {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.0.iter().any(|x| x.contains(ip))
+    }
+
+    /// 오른쪽부터 믿을 수 있는 proxy를 건너뛰고 처음 나오는 주소가 실제 클라이언트의 주소임
+    ///
+    /// 믿을 수 없는 peer가 보낸 X-Forwarded-For는 클라이언트가 마음대로 넣을 수 있으므로 무시함
+    pub fn client_ip(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        let mut ip = peer;
+
+        if !self.contains(ip) {
+            return ip;
+        }
+
+        for hop in forwarded_for.unwrap_or_default().rsplit(',') {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(hop) => {
+                    ip = hop;
+
+                    if !self.contains(hop) {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+
+        ip
+    }
+}
+
+pub fn client(request: &Request<Body>) -> Client {
+    let get_header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+    };
+
+    let forwarded_for = get_header("x-forwarded-for");
+
+    let ip = request.extensions().get::<SocketAddr>().map(|peer| {
+        request
+            .extensions()
+            .get::<TrustedProxies>()
+            .cloned()
+            .unwrap_or_default()
+            .client_ip(peer.ip(), forwarded_for.as_deref())
+    });
+
+    Client {
+        user_agent: get_header(header::USER_AGENT.as_str()),
+        ip: ip.map(|ip| ip.to_string()),
+        device_name: get_header(DEVICE_NAME),
+    }
+}
+
+/// MAX_BODY_SIZE가 없으면 사용함
+pub const DEFAULT_MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Wrap이 읽을 수 있는 body의 최대 크기
+///
+/// HttpServer가 request extension으로 넣어줌
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimit(pub usize);
+
+impl Default for BodyLimit {
+    fn default() -> Self {
+        Self(DEFAULT_MAX_BODY_SIZE)
+    }
+}
+
+/// content-length를 믿지 않고 읽으면서 크기를 확인함
+async fn read_body(request: &mut Request<Body>) -> crate::Result<Vec<u8>> {
+    let BodyLimit(limit) = request
+        .extensions()
+        .get::<BodyLimit>()
+        .copied()
+        .unwrap_or_default();
+
+    let content_length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+
+    if matches!(content_length, Some(x) if x > limit) {
+        return Err(Error::PayloadTooLarge(limit).into());
+    }
+
+    let mut buf = Vec::with_capacity(content_length.unwrap_or(0));
+
+    while let Some(chunk) = request.body_mut().data().await {
+        let chunk = chunk?;
+
+        if buf.len() + chunk.len() > limit {
+            return Err(Error::PayloadTooLarge(limit).into());
+        }
+
+        buf.extend_from_slice(&chunk);
     }
+
+    Ok(buf)
 }
 
 pub struct Wrap<P>(pub P);
@@ -76,7 +554,7 @@ where
     type Error = crate::Error;
 
     async fn async_try_from(mut request: Request<Body>) -> Result<Self, Self::Error> {
-        let chunks = request.body_mut().read_chunks().await?;
+        let chunks = read_body(&mut request).await?;
 
         let payload =
             serde_json::from_slice::<P>(&chunks).map_err(Error::JsonDeserializePayload)?;
@@ -84,3 +562,296 @@ where
         Ok(Wrap(payload))
     }
 }
+
+/// application/x-www-form-urlencoded body
+pub struct Form<P>(pub P);
+
+impl<P> Form<P> {
+    pub fn inner(self) -> P {
+        self.0
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> AsyncTryFrom<Request<Body>> for Form<P>
+where
+    P: DeserializeOwned,
+{
+    type Error = crate::Error;
+
+    async fn async_try_from(mut request: Request<Body>) -> Result<Self, Self::Error> {
+        let chunks = read_body(&mut request).await?;
+
+        let payload =
+            serde_urlencoded::from_bytes::<P>(&chunks).map_err(Error::FormDeserializePayload)?;
+
+        Ok(Form(payload))
+    }
+}
+
+/// content-type으로 Wrap과 Form 중에 골라서 읽음
+pub fn is_form(request: &Request<Body>) -> bool {
+    request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.starts_with("application/x-www-form-urlencoded"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{header, http::response::Builder as ResponseBuilder, Body, Method, Request};
+    use madome_sdk::api::cookie::{MADOME_ACCESS_TOKEN, MADOME_REFRESH_TOKEN};
+    use serde_json::Value;
+    use util::r#async::AsyncTryFrom;
+
+    use std::net::{IpAddr, SocketAddr};
+
+    use super::{BodyLimit, Error, Msg, TrustedProxies, TrustedProxy, Wrap};
+
+    #[tokio::test]
+    async fn check_and_refresh_token_pair() {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/v1/auth/token/check?role=1&mfa=true&max_age=60&scope=a&scope=b")
+            .header(
+                header::COOKIE,
+                format!(
+                    "{}=access; {}=refresh",
+                    MADOME_ACCESS_TOKEN, MADOME_REFRESH_TOKEN
+                ),
+            )
+            .header("x-forwarded-for", "1.2.3.4")
+            .extension(SocketAddr::from(([10, 0, 0, 1], 40000)))
+            .extension(
+                "10.0.0.0/8"
+                    .parse::<TrustedProxy>()
+                    .map(|x| TrustedProxies(vec![x]))
+                    .unwrap(),
+            )
+            .body(Body::empty())
+            .unwrap();
+
+        let (msg, _) = Msg::from_http(request, ResponseBuilder::new(), "")
+            .await
+            .unwrap();
+
+        match msg {
+            Msg::CheckAndRefreshTokenPair(payload) => {
+                assert_eq!(payload.access_token, "access");
+                assert_eq!(payload.refresh_token, "refresh");
+                assert_eq!(payload.minimum_role, Some(1));
+                assert!(payload.require_mfa);
+                assert_eq!(payload.max_age, Some(60));
+                assert_eq!(payload.scopes, ["a", "b"]);
+                assert_eq!(payload.client.ip.as_deref(), Some("1.2.3.4"));
+            }
+            msg => panic!("unexpected msg: {}", msg.name()),
+        }
+
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri("/auth/token/check")
+            .body(Body::empty())
+            .unwrap();
+
+        let r = Msg::from_http(request, ResponseBuilder::new(), "").await;
+
+        assert!(matches!(
+            r,
+            Err(crate::Error::Msg(Error::MethodNotAllowed(allow))) if allow == [Method::POST]
+        ));
+    }
+
+    #[tokio::test]
+    async fn revoke_session() {
+        let request = |method| {
+            Request::builder()
+                .method(method)
+                .uri("/auth/sessions/revoke?token=abc")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        // 메일의 링크를 여는 것만으로는 revoke하지 않음
+        let (msg, _) = Msg::from_http(request(Method::GET), ResponseBuilder::new(), "")
+            .await
+            .unwrap();
+
+        assert!(matches!(msg, Msg::ConfirmRevokeSession));
+
+        let (msg, _) = Msg::from_http(request(Method::POST), ResponseBuilder::new(), "")
+            .await
+            .unwrap();
+
+        match msg {
+            Msg::RevokeSession(payload) => assert_eq!(payload.token, "abc"),
+            msg => panic!("unexpected msg: {}", msg.name()),
+        }
+    }
+
+    #[tokio::test]
+    async fn service_token_form_with_basic() {
+        // "madome library:s3cr+t" (RFC 6749 2.3.1)
+        let credentials = base64::encode("madome+library:s3cr%2Bt");
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/auth/service/token")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(header::AUTHORIZATION, format!("Basic {}", credentials))
+            .body(Body::from(
+                "grant_type=client_credentials&scope=library%3Aread",
+            ))
+            .unwrap();
+
+        let (msg, _) = Msg::from_http(request, ResponseBuilder::new(), "")
+            .await
+            .unwrap();
+
+        match msg {
+            Msg::CreateServiceToken(payload) => {
+                assert_eq!(payload.grant_type, "client_credentials");
+                assert_eq!(payload.client_id, "madome library");
+                assert_eq!(payload.client_secret, "s3cr+t");
+                assert_eq!(payload.scope.as_deref(), Some("library:read"));
+            }
+            msg => panic!("unexpected msg: {}", msg.name()),
+        }
+    }
+
+    #[tokio::test]
+    async fn error_service_token_with_two_client_authentications() {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/auth/service/token")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(
+                header::AUTHORIZATION,
+                format!("Basic {}", base64::encode("madome-library:secret")),
+            )
+            .body(Body::from(
+                "grant_type=client_credentials&client_id=madome-library&client_secret=secret",
+            ))
+            .unwrap();
+
+        let r = Msg::from_http(request, ResponseBuilder::new(), "").await;
+
+        assert_eq!(r.err().unwrap().code(), "invalid_client");
+    }
+
+    #[test]
+    fn client_ip() {
+        let trusted = TrustedProxies(vec![
+            "10.0.0.0/8".parse().unwrap(),
+            "192.168.0.1".parse().unwrap(),
+        ]);
+        let ip = |x: &str| x.parse::<IpAddr>().unwrap();
+
+        // 믿을 수 없는 peer가 보낸 X-Forwarded-For는 무시함
+        assert_eq!(
+            trusted.client_ip(ip("8.8.8.8"), Some("1.2.3.4")),
+            ip("8.8.8.8")
+        );
+        assert_eq!(
+            TrustedProxies::default().client_ip(ip("10.0.0.1"), Some("1.2.3.4")),
+            ip("10.0.0.1")
+        );
+
+        assert_eq!(
+            trusted.client_ip(ip("10.0.0.1"), Some("1.2.3.4")),
+            ip("1.2.3.4")
+        );
+        // 클라이언트가 앞에 붙인 주소는 건너뜀
+        assert_eq!(
+            trusted.client_ip(ip("10.0.0.1"), Some("5.6.7.8, 1.2.3.4, 192.168.0.1")),
+            ip("1.2.3.4")
+        );
+        assert_eq!(
+            trusted.client_ip(ip("10.0.0.1"), Some("garbage")),
+            ip("10.0.0.1")
+        );
+        assert_eq!(trusted.client_ip(ip("10.0.0.1"), None), ip("10.0.0.1"));
+
+        assert!(!trusted.contains(ip("192.168.0.2")));
+        assert!(!trusted.contains(ip("::1")));
+        assert!("10.0.0.0/33".parse::<TrustedProxy>().is_err());
+        assert!("0.0.0.0/0"
+            .parse::<TrustedProxy>()
+            .unwrap()
+            .contains(ip("8.8.8.8")));
+    }
+
+    #[tokio::test]
+    async fn client_without_trusted_proxy() {
+        let request = Request::builder()
+            .header("x-forwarded-for", "1.2.3.4")
+            .extension(SocketAddr::from(([8, 8, 8, 8], 40000)))
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(super::client(&request).ip.as_deref(), Some("8.8.8.8"));
+    }
+
+    #[tokio::test]
+    async fn error_invalid_max_age() {
+        for max_age in ["-1", "abc"] {
+            let request = Request::builder()
+                .method(Method::GET)
+                .uri(format!("/auth/token?max_age={}", max_age))
+                .body(Body::empty())
+                .unwrap();
+
+            let r = Msg::from_http(request, ResponseBuilder::new(), "").await;
+
+            assert!(matches!(
+                r,
+                Err(crate::Error::Msg(Error::RequiredQuery("max_age")))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn error_invalid_session_id() {
+        for uri in [
+            "/auth/admin/sessions?user_id=00000000-0000-0000-0000-000000000000&session_id=abc",
+            "/auth/admin/sessions/abc?user_id=00000000-0000-0000-0000-000000000000",
+        ] {
+            let request = Request::builder()
+                .method(Method::DELETE)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+
+            let r = Msg::from_http(request, ResponseBuilder::new(), "").await;
+
+            assert!(matches!(
+                r,
+                Err(crate::Error::Msg(Error::RequiredQuery("session_id")))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn error_payload_too_large() {
+        let body = Body::wrap_stream(futures_util::stream::iter(vec![
+            Ok::<_, std::io::Error>(r#"{"email":"#.to_string()),
+            Ok(r#""user@madome.app"}"#.to_string()),
+        ]));
+
+        let mut request = Request::new(body);
+        request.extensions_mut().insert(BodyLimit(16));
+
+        let r = Wrap::<Value>::async_try_from(request).await;
+
+        assert!(matches!(
+            r,
+            Err(crate::Error::Msg(Error::PayloadTooLarge(16)))
+        ));
+
+        let request = Request::new(Body::from(r#"{"email":"user@madome.app"}"#));
+
+        assert!(Wrap::<Value>::async_try_from(request).await.is_ok());
+    }
+}
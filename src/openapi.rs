@@ -0,0 +1,1545 @@
+use madome_sdk::api::cookie::{MADOME_ACCESS_TOKEN, MADOME_REFRESH_TOKEN};
+use once_cell::sync::Lazy;
+use serde_json::{json, Map, Value};
+
+use crate::{csrf, msg, usecase::introspect_tokens};
+
+/// `GET /auth/openapi.json`
+///
+/// route를 추가하거나 바꾸면 여기도 바꿔야 함 (tests::routes)
+pub static SPEC: Lazy<Value> = Lazy::new(spec);
+
+pub struct OpenApi(pub &'static Value);
+
+/// 모든 에러 응답의 body
+///
+/// Error::code()가 만들 수 있는 code와 같아야 함 (tests::error_codes_match_error)
+const ERROR_CODES: [&str; 48] = [
+    "not_found",
+    "method_not_allowed",
+    "invalid_payload",
+    "required_query",
+    "payload_too_large",
+    "cross_site_request",
+    "unauthorized_metrics_token",
+    "unauthorized_access_token",
+    "permission_denied",
+    "required_mfa",
+    "stale_authentication",
+    "insufficient_scope",
+    "unauthorized_refresh_token",
+    "invalid_authcode",
+    "not_found_user",
+    "cannot_add_secret_key",
+    "invalid_token_pair",
+    "invalid_email",
+    "too_many_created_authcode",
+    "cannot_remove_secret_key",
+    "invalid_token",
+    "webauthn",
+    "not_found_passkey_registration",
+    "invalid_credential",
+    "required_totp_code",
+    "invalid_totp_code",
+    "too_many_totp_attempts",
+    "already_enabled_totp",
+    "not_found_totp",
+    "invalid_revoke_link",
+    "not_found_session",
+    "unsupported_grant_type",
+    "invalid_client",
+    "invalid_scope",
+    "invalid_api_key_name",
+    "invalid_expires_in",
+    "created_by_api_key",
+    "too_many_api_keys",
+    "not_found_api_key",
+    "too_many_tokens",
+    "unsupported_audit_sink",
+    "get_user_info",
+    "send_email",
+    "repository",
+    "audit",
+    "read_body",
+    "overloaded",
+    "timeout",
+];
+
+/// access token이 필요한 route에서 나올 수 있음
+const ACCESS_TOKEN_ERRORS: [(u16, &str); 5] = [
+    (401, "unauthorized_access_token"),
+    (401, "stale_authentication"),
+    (403, "permission_denied"),
+    (403, "required_mfa"),
+    (403, "insufficient_scope"),
+];
+
+struct Operation {
+    method: &'static str,
+    path: &'static str,
+    /// Msg의 이름
+    id: &'static str,
+    summary: &'static str,
+    tag: &'static str,
+    /// 바깥은 OR, 안쪽은 AND
+    security: &'static [&'static [&'static str]],
+    parameters: Vec<Value>,
+    body: Option<Value>,
+    /// (status, description, schema)
+    responses: Vec<(u16, &'static str, Option<Value>)>,
+    /// Set-Cookie로 token pair를 줌
+    sets_cookies: bool,
+    errors: Vec<(u16, &'static str)>,
+}
+
+impl Default for Operation {
+    fn default() -> Self {
+        Self {
+            method: "get",
+            path: "/",
+            id: "",
+            summary: "",
+            tag: "auth",
+            security: &[],
+            parameters: Vec::new(),
+            body: None,
+            responses: Vec::new(),
+            sets_cookies: false,
+            errors: Vec::new(),
+        }
+    }
+}
+
+fn query(name: &str, schema: Value, description: &str) -> Value {
+    json!({
+        "name": name,
+        "in": "query",
+        "required": false,
+        "schema": schema,
+        "description": description,
+    })
+}
+
+fn path_param(name: &str, description: &str) -> Value {
+    json!({
+        "name": name,
+        "in": "path",
+        "required": true,
+        "schema": { "type": "string", "format": "uuid" },
+        "description": description,
+    })
+}
+
+/// admin api의 대상 유저
+fn user_params() -> Vec<Value> {
+    vec![
+        query(
+            "user_id",
+            json!({ "type": "string", "format": "uuid" }),
+            "user_id 또는 email 중 하나는 있어야 함",
+        ),
+        query(
+            "email",
+            json!({ "type": "string" }),
+            "user_id가 없으면 사용함",
+        ),
+    ]
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn object(required: &[&str], properties: Value) -> Value {
+    json!({
+        "type": "object",
+        "required": required,
+        "properties": properties,
+    })
+}
+
+fn operations() -> Vec<Operation> {
+    // GET /auth/token과 POST /auth/token/check
+    let check_params = || {
+        vec![
+            query(
+                "role",
+                json!({ "type": "integer", "minimum": 0, "maximum": 255 }),
+                "이 role 이상이어야 함",
+            ),
+            query(
+                "mfa",
+                json!({ "type": "boolean", "default": false }),
+                "true이면 mfa를 거친 token이어야 함",
+            ),
+            query(
+                "max_age",
+                json!({ "type": "integer", "minimum": 0 }),
+                "마지막으로 인증한 지 max_age초가 지났으면 다시 인증해야 함",
+            ),
+            json!({
+                "name": "scope",
+                "in": "query",
+                "required": false,
+                "schema": { "type": "array", "items": { "type": "string" } },
+                "style": "form",
+                "explode": true,
+                "description": "모두 가지고 있어야 함",
+            }),
+        ]
+    };
+
+    let admin = || {
+        let mut errors = ACCESS_TOKEN_ERRORS.to_vec();
+        errors.push((400, "required_query"));
+        errors
+    };
+
+    vec![
+        Operation {
+            method: "get",
+            path: "/auth/token",
+            id: "CheckAccessToken",
+            summary: "access token을 확인함",
+            security: &[&["accessTokenCookie"], &["bearer"]],
+            parameters: check_params(),
+            responses: vec![(
+                200,
+                "service token이면 service client",
+                Some(json!({
+                    "oneOf": [schema_ref("CheckAccessToken"), schema_ref("CheckServiceToken")],
+                })),
+            )],
+            errors: [ACCESS_TOKEN_ERRORS.to_vec(), vec![(400, "required_query")]].concat(),
+            ..Default::default()
+        },
+        Operation {
+            method: "post",
+            path: "/auth/token",
+            id: "CreateTokenPair",
+            summary: "authcode로 로그인함",
+            body: Some(schema_ref("CheckAuthcode")),
+            responses: vec![(201, "로그인 성공", None)],
+            sets_cookies: true,
+            errors: vec![
+                (404, "invalid_authcode"),
+                (404, "not_found_user"),
+                (401, "required_totp_code"),
+                (401, "invalid_totp_code"),
+                (429, "too_many_totp_attempts"),
+            ],
+            ..Default::default()
+        },
+        Operation {
+            method: "patch",
+            path: "/auth/token",
+            id: "RefreshTokenPair",
+            summary: "token pair를 갱신함",
+            security: &[&["accessTokenCookie", "refreshTokenCookie"]],
+            responses: vec![(201, "갱신 성공", None)],
+            sets_cookies: true,
+            errors: vec![
+                (401, "invalid_token_pair"),
+                (401, "unauthorized_refresh_token"),
+            ],
+            ..Default::default()
+        },
+        Operation {
+            method: "delete",
+            path: "/auth/token",
+            id: "DeleteTokenPair",
+            summary: "로그아웃함",
+            security: &[&["accessTokenCookie", "refreshTokenCookie"]],
+            responses: vec![(204, "token pair cookie를 지움", None)],
+            errors: vec![(400, "invalid_token")],
+            ..Default::default()
+        },
+        Operation {
+            method: "post",
+            path: "/auth/token/check",
+            id: "CheckAndRefreshTokenPair",
+            summary: "access token을 확인하고 만료됐으면 갱신함",
+            security: &[&["accessTokenCookie", "refreshTokenCookie"]],
+            parameters: check_params(),
+            responses: vec![(
+                200,
+                "갱신했으면 Set-Cookie가 있음",
+                Some(schema_ref("CheckAccessToken")),
+            )],
+            sets_cookies: true,
+            errors: vec![
+                (400, "required_query"),
+                (401, "invalid_token_pair"),
+                (401, "unauthorized_refresh_token"),
+                (401, "stale_authentication"),
+                (403, "permission_denied"),
+                (403, "required_mfa"),
+                (403, "insufficient_scope"),
+            ],
+            ..Default::default()
+        },
+        Operation {
+            method: "post",
+            path: "/auth/token/introspect",
+            id: "IntrospectTokens",
+            summary: "access token이나 api key 여러개를 한번에 확인함 (RFC 7662)",
+            tag: "service",
+            security: &[&["bearer"]],
+            body: Some(object(
+                &["tokens"],
+                json!({
+                    "tokens": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "maxItems": introspect_tokens::MAX_TOKENS,
+                    },
+                }),
+            )),
+            responses: vec![(
+                200,
+                "요청한 순서대로",
+                Some(object(
+                    &["tokens"],
+                    json!({
+                        "tokens": { "type": "array", "items": schema_ref("Introspection") },
+                    }),
+                )),
+            )],
+            errors: vec![
+                (400, "too_many_tokens"),
+                (401, "unauthorized_access_token"),
+                (403, "insufficient_scope"),
+            ],
+            ..Default::default()
+        },
+        Operation {
+            method: "post",
+            path: "/auth/code",
+            id: "CreateAuthcode",
+            summary: "authcode를 메일로 보냄",
+            body: Some(schema_ref("CreateAuthcode")),
+            responses: vec![(201, "메일을 보냄", None)],
+            errors: vec![
+                (400, "invalid_email"),
+                (404, "not_found_user"),
+                (429, "too_many_created_authcode"),
+            ],
+            ..Default::default()
+        },
+        Operation {
+            method: "post",
+            path: "/auth/webauthn/registration",
+            id: "StartPasskeyRegistration",
+            summary: "passkey 등록을 시작함",
+            tag: "passkey",
+            security: &[&["accessTokenCookie"]],
+            responses: vec![(
+                200,
+                "WebAuthn CreationChallengeResponse",
+                Some(json!({ "type": "object" })),
+            )],
+            errors: ACCESS_TOKEN_ERRORS.to_vec(),
+            ..Default::default()
+        },
+        Operation {
+            method: "patch",
+            path: "/auth/webauthn/registration",
+            id: "FinishPasskeyRegistration",
+            summary: "passkey 등록을 끝냄",
+            tag: "passkey",
+            security: &[&["accessTokenCookie"]],
+            body: Some(json!({
+                "type": "object",
+                "description": "WebAuthn RegisterPublicKeyCredential",
+            })),
+            responses: vec![(201, "등록됨", None)],
+            errors: [
+                ACCESS_TOKEN_ERRORS.to_vec(),
+                vec![
+                    (404, "not_found_passkey_registration"),
+                    (400, "invalid_credential"),
+                ],
+            ]
+            .concat(),
+            ..Default::default()
+        },
+        Operation {
+            method: "post",
+            path: "/auth/webauthn/authentication",
+            id: "StartPasskeyAuthentication",
+            summary: "passkey 로그인을 시작함",
+            tag: "passkey",
+            body: Some(object(
+                &["email"],
+                json!({ "email": { "type": "string", "format": "email" } }),
+            )),
+            responses: vec![(
+                200,
+                "가입하지 않았거나 passkey가 없는 email에도 같은 모양으로 응답함",
+                Some(object(
+                    &["ceremony_id", "publicKey"],
+                    json!({
+                        "ceremony_id": { "type": "string", "format": "uuid" },
+                        "publicKey": {
+                            "type": "object",
+                            "description": "WebAuthn PublicKeyCredentialRequestOptions",
+                        },
+                    }),
+                )),
+            )],
+            ..Default::default()
+        },
+        Operation {
+            method: "patch",
+            path: "/auth/webauthn/authentication",
+            id: "CreateTokenPairByPasskey",
+            summary: "passkey로 로그인함",
+            tag: "passkey",
+            body: Some(object(
+                &["ceremony_id", "credential"],
+                json!({
+                    "ceremony_id": { "type": "string", "format": "uuid" },
+                    "credential": {
+                        "type": "object",
+                        "description": "WebAuthn PublicKeyCredential",
+                    },
+                }),
+            )),
+            responses: vec![(201, "로그인 성공", None)],
+            sets_cookies: true,
+            errors: vec![(401, "invalid_credential"), (404, "not_found_user")],
+            ..Default::default()
+        },
+        Operation {
+            method: "post",
+            path: "/auth/totp",
+            id: "CreateTotp",
+            summary: "TOTP secret과 recovery code를 만듦",
+            tag: "totp",
+            security: &[&["accessTokenCookie"]],
+            responses: vec![(201, "아직 활성화되지 않음", Some(schema_ref("CreateTotp")))],
+            errors: [
+                ACCESS_TOKEN_ERRORS.to_vec(),
+                vec![(409, "already_enabled_totp")],
+            ]
+            .concat(),
+            ..Default::default()
+        },
+        Operation {
+            method: "patch",
+            path: "/auth/totp",
+            id: "EnableTotp",
+            summary: "TOTP code를 확인하고 활성화함",
+            tag: "totp",
+            security: &[&["accessTokenCookie"]],
+            body: Some(object(&["code"], json!({ "code": { "type": "string" } }))),
+            responses: vec![(204, "활성화됨", None)],
+            errors: [
+                ACCESS_TOKEN_ERRORS.to_vec(),
+                vec![(404, "not_found_totp"), (400, "invalid_totp_code")],
+            ]
+            .concat(),
+            ..Default::default()
+        },
+        Operation {
+            method: "get",
+            path: "/auth/sessions/revoke",
+            id: "ConfirmRevokeSession",
+            summary: "새 기기 로그인 알림 메일의 링크, revoke할지 확인하는 페이지",
+            tag: "session",
+            parameters: vec![query(
+                "token",
+                json!({ "type": "string" }),
+                "메일에 들어있는 서명된 token",
+            )],
+            responses: vec![(200, "text/html", Some(json!({ "type": "string" })))],
+            ..Default::default()
+        },
+        Operation {
+            method: "post",
+            path: "/auth/sessions/revoke",
+            id: "RevokeSession",
+            summary: "확인 페이지에서 session을 revoke함",
+            tag: "session",
+            parameters: vec![query(
+                "token",
+                json!({ "type": "string" }),
+                "메일에 들어있는 서명된 token",
+            )],
+            responses: vec![(200, "text/plain", Some(json!({ "type": "string" })))],
+            errors: vec![(400, "invalid_revoke_link"), (404, "not_found_session")],
+            ..Default::default()
+        },
+        Operation {
+            method: "get",
+            path: "/auth/admin/sessions",
+            id: "ListSessions",
+            summary: "유저의 session 목록",
+            tag: "admin",
+            security: &[&["accessTokenCookie"]],
+            parameters: user_params(),
+            responses: vec![(200, "session 목록", Some(schema_ref("ListSessions")))],
+            errors: admin(),
+            ..Default::default()
+        },
+        Operation {
+            method: "delete",
+            path: "/auth/admin/sessions",
+            id: "RevokeSessions",
+            summary: "유저의 session을 끊음",
+            tag: "admin",
+            security: &[&["accessTokenCookie"]],
+            parameters: [
+                user_params(),
+                vec![query(
+                    "session_id",
+                    json!({ "type": "string", "format": "uuid" }),
+                    "없으면 모든 session",
+                )],
+            ]
+            .concat(),
+            responses: vec![(200, "끊은 session 수", Some(schema_ref("RevokeSessions")))],
+            errors: admin(),
+            ..Default::default()
+        },
+        Operation {
+            method: "delete",
+            path: "/auth/admin/sessions/{session_id}",
+            id: "RevokeSessions",
+            summary: "유저의 session 하나를 끊음",
+            tag: "admin",
+            security: &[&["accessTokenCookie"]],
+            parameters: [user_params(), vec![path_param("session_id", "session id")]].concat(),
+            responses: vec![(200, "끊은 session 수", Some(schema_ref("RevokeSessions")))],
+            errors: admin(),
+            ..Default::default()
+        },
+        Operation {
+            method: "delete",
+            path: "/auth/admin/authcodes",
+            id: "ClearAuthcodes",
+            summary: "유저의 authcode를 모두 지움",
+            tag: "admin",
+            security: &[&["accessTokenCookie"]],
+            parameters: user_params(),
+            responses: vec![(
+                200,
+                "지운 authcode 수",
+                Some(object(
+                    &["cleared"],
+                    json!({ "cleared": { "type": "integer" } }),
+                )),
+            )],
+            errors: admin(),
+            ..Default::default()
+        },
+        Operation {
+            method: "get",
+            path: "/auth/admin/audit",
+            id: "ListAuditEvents",
+            summary: "유저의 audit log",
+            tag: "admin",
+            security: &[&["accessTokenCookie"]],
+            parameters: [
+                user_params(),
+                vec![query(
+                    "count",
+                    json!({ "type": "integer", "minimum": 1 }),
+                    "최근 것부터",
+                )],
+            ]
+            .concat(),
+            responses: vec![(
+                200,
+                "audit event 목록",
+                Some(object(
+                    &["events"],
+                    json!({
+                        "events": { "type": "array", "items": schema_ref("AuditEvent") },
+                    }),
+                )),
+            )],
+            errors: [admin(), vec![(501, "unsupported_audit_sink")]].concat(),
+            ..Default::default()
+        },
+        Operation {
+            method: "post",
+            path: "/auth/api-keys",
+            id: "CreateApiKey",
+            summary: "api key를 만듦",
+            tag: "api-key",
+            security: &[&["accessTokenCookie"]],
+            body: Some(object(
+                &["name"],
+                json!({
+                    "name": { "type": "string" },
+                    "scopes": { "type": "array", "items": { "type": "string" } },
+                    "expires_in": {
+                        "type": "integer",
+                        "description": "초 단위, 없으면 만료되지 않음",
+                    },
+                }),
+            )),
+            responses: vec![(
+                201,
+                "key는 지금만 확인할 수 있음",
+                Some(schema_ref("CreateApiKey")),
+            )],
+            errors: [
+                ACCESS_TOKEN_ERRORS.to_vec(),
+                vec![
+                    (400, "invalid_api_key_name"),
+                    (400, "invalid_expires_in"),
+                    (403, "created_by_api_key"),
+                    (409, "too_many_api_keys"),
+                ],
+            ]
+            .concat(),
+            ..Default::default()
+        },
+        Operation {
+            method: "get",
+            path: "/auth/api-keys",
+            id: "ListApiKeys",
+            summary: "내 api key 목록",
+            tag: "api-key",
+            security: &[&["accessTokenCookie"], &["bearer"]],
+            responses: vec![(
+                200,
+                "api key 목록",
+                Some(object(
+                    &["api_keys"],
+                    json!({
+                        "api_keys": { "type": "array", "items": schema_ref("ApiKey") },
+                    }),
+                )),
+            )],
+            errors: ACCESS_TOKEN_ERRORS.to_vec(),
+            ..Default::default()
+        },
+        Operation {
+            method: "delete",
+            path: "/auth/api-keys",
+            id: "DeleteApiKey",
+            summary: "api key를 지움",
+            tag: "api-key",
+            security: &[&["accessTokenCookie"]],
+            parameters: vec![query(
+                "id",
+                json!({ "type": "string", "format": "uuid" }),
+                "`DELETE /auth/api-keys/{id}`를 사용하는게 좋음",
+            )],
+            responses: vec![(204, "지워짐", None)],
+            errors: [
+                ACCESS_TOKEN_ERRORS.to_vec(),
+                vec![(400, "required_query"), (404, "not_found_api_key")],
+            ]
+            .concat(),
+            ..Default::default()
+        },
+        Operation {
+            method: "delete",
+            path: "/auth/api-keys/{id}",
+            id: "DeleteApiKey",
+            summary: "api key를 지움",
+            tag: "api-key",
+            security: &[&["accessTokenCookie"]],
+            parameters: vec![path_param("id", "api key id")],
+            responses: vec![(204, "지워짐", None)],
+            errors: [
+                ACCESS_TOKEN_ERRORS.to_vec(),
+                vec![(404, "not_found_api_key")],
+            ]
+            .concat(),
+            ..Default::default()
+        },
+        Operation {
+            method: "post",
+            path: "/auth/service/token",
+            id: "CreateServiceToken",
+            summary: "client credentials grant (RFC 6749 4.4)",
+            tag: "service",
+            security: &[&["clientSecretBasic"], &[]],
+            body: Some(object(
+                &["grant_type"],
+                json!({
+                    "grant_type": { "type": "string", "enum": ["client_credentials"] },
+                    "client_id": {
+                        "type": "string",
+                        "description": "Authorization: Basic을 사용하지 않을 때 필요함",
+                    },
+                    "client_secret": {
+                        "type": "string",
+                        "description": "Authorization: Basic을 사용하지 않을 때 필요함",
+                    },
+                    "scope": {
+                        "type": "string",
+                        "description": "공백으로 구분함, 없으면 client의 모든 scope",
+                    },
+                }),
+            )),
+            responses: vec![(200, "service token", Some(schema_ref("CreateServiceToken")))],
+            errors: vec![
+                (400, "unsupported_grant_type"),
+                (401, "invalid_client"),
+                (400, "invalid_scope"),
+            ],
+            ..Default::default()
+        },
+        Operation {
+            method: "get",
+            path: "/auth/openapi.json",
+            id: "OpenApi",
+            summary: "이 문서",
+            tag: "meta",
+            responses: vec![(200, "OpenAPI 3 document", Some(json!({ "type": "object" })))],
+            ..Default::default()
+        },
+        Operation {
+            method: "get",
+            path: "/metrics",
+            id: "Metrics",
+            summary: "prometheus text format",
+            tag: "meta",
+            security: &[&["metricsToken"]],
+            responses: vec![(200, "text/plain", Some(json!({ "type": "string" })))],
+            errors: vec![(401, "unauthorized_metrics_token"), (404, "not_found")],
+            ..Default::default()
+        },
+        Operation {
+            method: "get",
+            path: "/healthz",
+            id: "Liveness",
+            summary: "liveness probe",
+            tag: "meta",
+            responses: vec![(
+                200,
+                "살아있음",
+                Some(object(
+                    &["status"],
+                    json!({ "status": { "type": "string" } }),
+                )),
+            )],
+            ..Default::default()
+        },
+        Operation {
+            method: "get",
+            path: "/readyz",
+            id: "Readiness",
+            summary: "readiness probe",
+            tag: "meta",
+            responses: vec![
+                (200, "준비됨", Some(schema_ref("Readiness"))),
+                (503, "준비되지 않음", Some(schema_ref("Readiness"))),
+            ],
+            ..Default::default()
+        },
+    ]
+}
+
+fn error_response(codes: &[&str]) -> Value {
+    json!({
+        "description": codes.join(", "),
+        "content": {
+            "application/json": {
+                "schema": {
+                    "allOf": [
+                        schema_ref("Error"),
+                        {
+                            "type": "object",
+                            "properties": { "code": { "type": "string", "enum": codes } },
+                        },
+                    ],
+                },
+            },
+        },
+    })
+}
+
+impl Operation {
+    /// body를 읽고 CSRF를 확인하는지는 route에서 알 수 있음
+    fn common_errors(&self) -> Vec<(u16, &'static str)> {
+        let mut errors = Vec::new();
+
+        if self.body.is_some() {
+            errors.push((400, "invalid_payload"));
+            errors.push((413, "payload_too_large"));
+        }
+
+        let protected = csrf::DEFAULT_PROTECTED_ROUTES
+            .iter()
+            .any(|x| x.eq_ignore_ascii_case(&format!("{} {}", self.method, self.path)));
+
+        if protected {
+            errors.push((403, "cross_site_request"));
+        }
+
+        // 모든 route에서 나올 수 있음
+        errors.push((503, "overloaded"));
+        errors.push((503, "timeout"));
+
+        errors
+    }
+
+    fn to_json(&self) -> Value {
+        let mut responses = Map::new();
+
+        for (status, description, schema) in &self.responses {
+            let mut response = json!({ "description": description });
+
+            if let Some(schema) = schema {
+                let content_type = match self.id {
+                    "Metrics" | "RevokeSession" => "text/plain",
+                    "ConfirmRevokeSession" => "text/html",
+                    _ => "application/json",
+                };
+
+                response["content"] = json!({ content_type: { "schema": schema } });
+            }
+
+            if self.sets_cookies {
+                response["headers"] = json!({
+                    "Set-Cookie": {
+                        "description": format!(
+                            "`{}`, `{}` (HttpOnly, Secure, Domain=COOKIE_DOMAIN, Max-Age=COOKIE_MAX_AGE)",
+                            MADOME_ACCESS_TOKEN, MADOME_REFRESH_TOKEN
+                        ),
+                        "schema": { "type": "string" },
+                    },
+                });
+            }
+
+            responses.insert(status.to_string(), response);
+        }
+
+        let mut errors = self.errors.clone();
+        errors.extend(self.common_errors());
+
+        let mut statuses = errors.iter().map(|(status, _)| *status).collect::<Vec<_>>();
+        statuses.sort_unstable();
+        statuses.dedup();
+
+        for status in statuses {
+            let codes = errors
+                .iter()
+                .filter(|(x, _)| *x == status)
+                .map(|(_, code)| *code)
+                .collect::<Vec<_>>();
+
+            // readyz의 503처럼 이미 있으면 그대로 둠
+            responses
+                .entry(status.to_string())
+                .or_insert_with(|| error_response(&codes));
+        }
+
+        let mut operation = json!({
+            "operationId": self.id,
+            "summary": self.summary,
+            "tags": [self.tag],
+            "parameters": self.parameters,
+            "responses": responses,
+        });
+
+        if !self.security.is_empty() {
+            operation["security"] = self
+                .security
+                .iter()
+                .map(|schemes| {
+                    schemes
+                        .iter()
+                        .map(|x| (x.to_string(), json!([])))
+                        .collect::<Map<_, _>>()
+                })
+                .collect();
+        }
+
+        if let Some(body) = &self.body {
+            let content = match self.id {
+                "CreateServiceToken" => json!({
+                    "application/x-www-form-urlencoded": { "schema": body },
+                    "application/json": { "schema": body },
+                }),
+                _ => json!({ "application/json": { "schema": body } }),
+            };
+
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": content,
+            });
+        }
+
+        operation
+    }
+}
+
+fn schemas() -> Value {
+    let uuid = || json!({ "type": "string", "format": "uuid" });
+    let nullable_int = || json!({ "type": "integer", "nullable": true });
+
+    json!({
+        "Error": object(
+            &["code", "message", "request_id"],
+            json!({
+                "code": { "type": "string", "enum": ERROR_CODES },
+                "message": { "type": "string", "description": "바뀔 수 있으니 code를 사용해야 함" },
+                "request_id": { "type": "string", "description": msg::REQUEST_ID },
+            }),
+        ),
+        "CreateAuthcode": object(
+            &["email"],
+            json!({
+                "email": { "type": "string", "format": "email" },
+                "ses_flag": { "type": "boolean", "default": false, "description": "debug build에서만 사용함" },
+            }),
+        ),
+        "CheckAuthcode": object(
+            &["code", "email"],
+            json!({
+                "code": { "type": "string" },
+                "email": { "type": "string", "format": "email" },
+                "totp": { "type": "string", "description": "TOTP를 등록한 유저만 필요함, recovery code도 가능" },
+            }),
+        ),
+        "CheckAccessToken": object(&["user_id"], json!({ "user_id": uuid() })),
+        "CheckServiceToken": object(
+            &["client_id", "scopes"],
+            json!({
+                "client_id": { "type": "string" },
+                "scopes": { "type": "array", "items": { "type": "string" } },
+            }),
+        ),
+        "CreateTotp": object(
+            &["secret", "uri", "recovery_codes"],
+            json!({
+                "secret": { "type": "string" },
+                "uri": { "type": "string", "description": "otpauth://" },
+                "recovery_codes": { "type": "array", "items": { "type": "string" } },
+            }),
+        ),
+        "Session": object(
+            &["id", "token_id", "user_id", "created_at"],
+            json!({
+                "id": uuid(),
+                "token_id": uuid(),
+                "user_id": uuid(),
+                "user_agent": { "type": "string", "nullable": true },
+                "ip": { "type": "string", "nullable": true },
+                "device_name": { "type": "string", "nullable": true },
+                "created_at": { "type": "integer" },
+                "last_refreshed_at": nullable_int(),
+            }),
+        ),
+        "ListSessions": object(
+            &["user_id", "sessions"],
+            json!({
+                "user_id": uuid(),
+                "sessions": { "type": "array", "items": schema_ref("Session") },
+            }),
+        ),
+        "RevokeSessions": object(&["revoked"], json!({ "revoked": { "type": "integer" } })),
+        "AuditEvent": object(
+            &["time", "kind", "outcome"],
+            json!({
+                "time": { "type": "integer", "description": "unix timestamp (milliseconds)" },
+                "kind": { "type": "string" },
+                "outcome": { "type": "string", "enum": ["success", "failure"] },
+                "user_id": uuid(),
+                "user_email": { "type": "string" },
+                "token_id": uuid(),
+                "ip": { "type": "string" },
+                "user_agent": { "type": "string" },
+                "request_id": { "type": "string" },
+                "detail": { "type": "string" },
+            }),
+        ),
+        "ApiKey": object(
+            &["id", "user_id", "name", "scopes", "created_at"],
+            json!({
+                "id": uuid(),
+                "user_id": uuid(),
+                "name": { "type": "string" },
+                "scopes": { "type": "array", "items": { "type": "string" } },
+                "created_at": { "type": "integer" },
+                "expires_at": nullable_int(),
+            }),
+        ),
+        "CreateApiKey": object(
+            &["id", "key", "name", "scopes"],
+            json!({
+                "id": uuid(),
+                "key": { "type": "string" },
+                "name": { "type": "string" },
+                "scopes": { "type": "array", "items": { "type": "string" } },
+                "expires_at": nullable_int(),
+            }),
+        ),
+        "Introspection": object(
+            &["active"],
+            json!({
+                "active": { "type": "boolean" },
+                "user_id": uuid(),
+                "token_id": uuid(),
+                "exp": { "type": "integer" },
+                "role": { "type": "integer" },
+                "scope": { "type": "string" },
+            }),
+        ),
+        "CreateServiceToken": object(
+            &["access_token", "token_type", "expires_in", "scope"],
+            json!({
+                "access_token": { "type": "string" },
+                "token_type": { "type": "string", "enum": ["Bearer"] },
+                "expires_in": { "type": "integer" },
+                "scope": { "type": "string" },
+            }),
+        ),
+        "Readiness": object(
+            &["ready", "checks"],
+            json!({
+                "ready": { "type": "boolean" },
+                "checks": {
+                    "type": "object",
+                    "additionalProperties": object(
+                        &["ok"],
+                        json!({
+                            "ok": { "type": "boolean" },
+                            "informational": {
+                                "type": "boolean",
+                                "description": "실패해도 ready에 영향을 주지 않음",
+                            },
+                            "error": { "type": "string" },
+                        }),
+                    ),
+                },
+            }),
+        ),
+    })
+}
+
+fn spec() -> Value {
+    let mut paths = Map::new();
+
+    for operation in operations() {
+        let path = paths
+            .entry(operation.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+
+        path[operation.method] = operation.to_json();
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "madome-auth",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "`/v1`과 BASE_PATH를 붙여서 요청해도 됨",
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas(),
+            "securitySchemes": {
+                "accessTokenCookie": { "type": "apiKey", "in": "cookie", "name": MADOME_ACCESS_TOKEN },
+                "refreshTokenCookie": { "type": "apiKey", "in": "cookie", "name": MADOME_REFRESH_TOKEN },
+                "bearer": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "service token 또는 api key",
+                },
+                "clientSecretBasic": {
+                    "type": "http",
+                    "scheme": "basic",
+                    "description": "client_id:client_secret (RFC 6749 2.3.1)",
+                },
+                "metricsToken": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "METRICS_TOKEN",
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use hyper::{body, Body, StatusCode};
+    use serde::Serialize;
+    use serde_json::Value;
+    use uuid::Uuid;
+
+    use crate::{
+        audit::AuditSinkKind,
+        command::{get_user_info, send_email},
+        entity::{
+            api_key::ApiKey,
+            audit::{AuditEvent, AuditKind},
+            session::{Client, Session},
+        },
+        error::AuditError,
+        health::{Check, Liveness, Readiness},
+        model::TokenPair,
+        msg::{self, ROUTER},
+        usecase::{
+            check_access_token, check_and_refresh_token_pair, check_authcode, check_refresh_token,
+            check_service_token, check_token_pair, check_totp, clear_authcodes, create_api_key,
+            create_authcode, create_service_token, create_token_pair, create_totp, delete_api_key,
+            delete_token_pair, enable_totp, finish_passkey_authentication,
+            finish_passkey_registration, introspect_tokens, list_api_keys, list_audit_events,
+            list_sessions, refresh_token_pair, revoke_session, revoke_sessions,
+            start_passkey_authentication, start_passkey_registration,
+        },
+    };
+
+    use super::{schema_ref, ERROR_CODES, SPEC};
+
+    /// 문서에서 사용하는 keyword만 확인함, 문서에 없는 field도 에러로 봄
+    fn validate(schema: &Value, value: &Value, at: &str) -> Result<(), String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/components/schemas/");
+
+            return validate(&SPEC["components"]["schemas"][name], value, at);
+        }
+
+        if let Some(schemas) = schema["allOf"].as_array() {
+            return schemas.iter().try_for_each(|x| validate(x, value, at));
+        }
+
+        if let Some(schemas) = schema["oneOf"].as_array() {
+            let matched = schemas
+                .iter()
+                .filter(|x| validate(x, value, at).is_ok())
+                .count();
+
+            return match matched {
+                1 => Ok(()),
+                n => Err(format!("{}: matched {} schemas of oneOf", at, n)),
+            };
+        }
+
+        if value.is_null() && schema["nullable"] == true {
+            return Ok(());
+        }
+
+        if let Some(variants) = schema["enum"].as_array() {
+            if !variants.contains(value) {
+                return Err(format!("{}: {} is not in enum", at, value));
+            }
+        }
+
+        let typed = match schema["type"].as_str() {
+            Some("object") => value.is_object(),
+            Some("array") => value.is_array(),
+            Some("string") => value.is_string(),
+            Some("integer") => value.is_i64() || value.is_u64(),
+            Some("boolean") => value.is_boolean(),
+            Some(x) => return Err(format!("{}: unsupported type {}", at, x)),
+            None => true,
+        };
+
+        if !typed {
+            return Err(format!("{}: {} is not {}", at, value, schema["type"]));
+        }
+
+        if schema["format"] == "uuid"
+            && value
+                .as_str()
+                .and_then(|x| x.parse::<Uuid>().ok())
+                .is_none()
+        {
+            return Err(format!("{}: {} is not uuid", at, value));
+        }
+
+        if let Some(object) = value.as_object() {
+            for key in schema["required"].as_array().into_iter().flatten() {
+                let key = key.as_str().unwrap();
+
+                if !object.contains_key(key) {
+                    return Err(format!("{}: missing {}", at, key));
+                }
+            }
+
+            let properties = schema["properties"].as_object();
+            let additional = &schema["additionalProperties"];
+
+            for (key, value) in object {
+                let at = format!("{}.{}", at, key);
+
+                match properties.and_then(|x| x.get(key)) {
+                    Some(schema) => validate(schema, value, &at)?,
+                    None if additional.is_object() => validate(additional, value, &at)?,
+                    // 모양을 정하지 않은 object
+                    None if properties.is_none() => {}
+                    None => return Err(format!("{}: undocumented", at)),
+                }
+            }
+        }
+
+        if let Some(items) = value.as_array() {
+            for (i, item) in items.iter().enumerate() {
+                validate(&schema["items"], item, &format!("{}[{}]", at, i))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// operationId와 status로 응답 body의 schema를 찾음
+    fn response_schema(id: &str, status: StatusCode) -> &'static Value {
+        SPEC["paths"]
+            .as_object()
+            .unwrap()
+            .values()
+            .flat_map(|x| x.as_object().unwrap().values())
+            .find(|x| x["operationId"] == id)
+            .map(|x| &x["responses"][status.as_str()]["content"]["application/json"]["schema"])
+            .unwrap_or_else(|| panic!("undocumented operation: {}", id))
+    }
+
+    fn sample(
+        id: &'static str,
+        status: StatusCode,
+        model: impl Serialize,
+    ) -> (&'static str, StatusCode, Value) {
+        (id, status, serde_json::to_value(model).unwrap())
+    }
+
+    /// Error::code()의 갈래마다 하나씩
+    ///
+    /// random_code, write_audit의 Error는 variant가 없어서 만들 수 없음
+    async fn every_error() -> Vec<crate::Error> {
+        let json = || serde_json::from_str::<()>("").unwrap_err();
+
+        let form = serde_urlencoded::from_str::<Vec<(String, u8)>>("a=b").unwrap_err();
+
+        let (sender, aborted) = Body::channel();
+        sender.abort();
+        let read_body = body::to_bytes(aborted).await.unwrap_err();
+
+        let not_found_user = || {
+            madome_sdk::api::user::Error::GetUser(
+                madome_sdk::api::user::get_user::Error::NotFoundUser,
+            )
+        };
+
+        let webauthn = || webauthn_rs::prelude::WebauthnError::Configuration;
+
+        vec![
+            msg::Error::NotFound.into(),
+            msg::Error::MethodNotAllowed(vec![]).into(),
+            msg::Error::JsonDeserializePayload(json()).into(),
+            msg::Error::FormDeserializePayload(form).into(),
+            msg::Error::RequiredQuery("id").into(),
+            msg::Error::PayloadTooLarge(0).into(),
+            msg::Error::CrossSiteRequest.into(),
+            msg::Error::UnauthorizedMetricsToken.into(),
+            check_access_token::Error::UnauthorizedAccessToken.into(),
+            check_access_token::Error::PermissionDenied.into(),
+            check_access_token::Error::RequiredMfa.into(),
+            check_access_token::Error::StaleAuthentication.into(),
+            check_access_token::Error::InsufficientScope("scope".to_string()).into(),
+            check_refresh_token::Error::UnauthorizedRefreshToken.into(),
+            check_authcode::Error::InvalidAuthcode.into(),
+            create_token_pair::Error::NotFoundUser.into(),
+            create_token_pair::Error::CannotAddedSecretKey.into(),
+            check_token_pair::Error::InvalidTokenPair.into(),
+            create_authcode::Error::InvalidEmail.into(),
+            create_authcode::Error::NotFoundUser.into(),
+            create_authcode::Error::TooManyCreatedAuthcode.into(),
+            check_and_refresh_token_pair::Error::Denied(
+                check_access_token::Error::RequiredMfa,
+                TokenPair::default(),
+            )
+            .into(),
+            refresh_token_pair::Error::CannotRemovedSecretKey.into(),
+            delete_token_pair::Error::InvalidToken.into(),
+            start_passkey_registration::Error::Webauthn(webauthn()).into(),
+            finish_passkey_registration::Error::NotFoundRegistration.into(),
+            finish_passkey_registration::Error::InvalidCredential.into(),
+            start_passkey_authentication::Error::Webauthn(webauthn()).into(),
+            finish_passkey_authentication::Error::InvalidCredential.into(),
+            check_totp::Error::RequiredTotpCode.into(),
+            check_totp::Error::InvalidTotpCode.into(),
+            check_totp::Error::TooManyTotpAttempts.into(),
+            create_totp::Error::AlreadyEnabledTotp.into(),
+            enable_totp::Error::NotFoundTotp.into(),
+            enable_totp::Error::InvalidTotpCode.into(),
+            revoke_session::Error::InvalidRevokeLink.into(),
+            revoke_session::Error::NotFoundSession.into(),
+            create_service_token::Error::UnsupportedGrantType.into(),
+            create_service_token::Error::InvalidClient.into(),
+            create_service_token::Error::InvalidScope.into(),
+            create_api_key::Error::InvalidName.into(),
+            create_api_key::Error::InvalidExpiresIn.into(),
+            create_api_key::Error::CreatedByApiKey.into(),
+            create_api_key::Error::TooManyApiKeys.into(),
+            delete_api_key::Error::NotFoundApiKey.into(),
+            list_audit_events::Error::UnsupportedAuditSink(AuditSinkKind::Stdout).into(),
+            introspect_tokens::Error::TooManyTokens(0).into(),
+            get_user_info::Error::Undefined(StatusCode::BAD_GATEWAY, String::new()).into(),
+            send_email::Error::AwsSes(Box::new(aws_sdk_sesv2::SdkError::ConstructionFailure(
+                "construction".into(),
+            )))
+            .into(),
+            not_found_user().into(),
+            redis::RedisError::from((redis::ErrorKind::IoError, "io")).into(),
+            crate::Error::Audit(AuditError::Io(std::io::ErrorKind::Other.into())),
+            read_body.into(),
+            crate::Error::Overloaded,
+            crate::Error::Timeout,
+        ]
+    }
+
+    #[test]
+    fn routes() {
+        let routes = ROUTER
+            .routes()
+            .map(|(method, path)| (method.as_str().to_lowercase(), path.to_string()))
+            .collect::<BTreeSet<_>>();
+
+        let documented = SPEC["paths"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .flat_map(|(path, operations)| {
+                operations
+                    .as_object()
+                    .unwrap()
+                    .keys()
+                    .map(move |method| (method.to_string(), path.to_string()))
+            })
+            .collect::<BTreeSet<_>>();
+
+        let undocumented = routes.difference(&documented).collect::<Vec<_>>();
+        let removed = documented.difference(&routes).collect::<Vec<_>>();
+
+        assert!(undocumented.is_empty(), "undocumented: {:?}", undocumented);
+        assert!(removed.is_empty(), "not routed: {:?}", removed);
+    }
+
+    #[test]
+    fn error_codes() {
+        for operations in SPEC["paths"].as_object().unwrap().values() {
+            for operation in operations.as_object().unwrap().values() {
+                for (status, response) in operation["responses"].as_object().unwrap() {
+                    if !status.starts_with('4') && !status.starts_with('5') {
+                        continue;
+                    }
+
+                    let codes = &response["content"]["application/json"]["schema"]["allOf"][1]
+                        ["properties"]["code"]["enum"];
+
+                    for code in codes.as_array().into_iter().flatten() {
+                        let code = code.as_str().unwrap();
+
+                        assert!(ERROR_CODES.contains(&code), "{}", code);
+                    }
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn error_codes_match_error() {
+        let errors = every_error().await;
+
+        let codes = errors
+            .iter()
+            .map(crate::Error::code)
+            .collect::<BTreeSet<_>>();
+        let documented = ERROR_CODES.iter().copied().collect::<BTreeSet<_>>();
+
+        assert_eq!(documented.len(), ERROR_CODES.len(), "duplicated code");
+        assert_eq!(codes, documented);
+
+        for err in errors {
+            let response = err.to_http("request-id");
+            let body = body::to_bytes(response.into_body()).await.unwrap();
+            let body = serde_json::from_slice::<Value>(&body).unwrap();
+
+            if let Err(err) = validate(&schema_ref("Error"), &body, "Error") {
+                panic!("{}", err);
+            }
+        }
+    }
+
+    #[test]
+    fn models() {
+        let id = Uuid::new_v4;
+
+        let client = Client {
+            user_agent: Some("Mozilla/5.0".to_string()),
+            ip: Some("1.2.3.4".to_string()),
+            device_name: Some("desktop".to_string()),
+        };
+
+        let session = Session {
+            id: id(),
+            token_id: id(),
+            user_id: id(),
+            client: client.clone(),
+            created_at: 0,
+            last_refreshed_at: None,
+        };
+
+        let audit_event = AuditEvent::new(AuditKind::TokenPairRevoked)
+            .user_id(id())
+            .user_email("user@madome.app")
+            .token_id(id())
+            .with_client(&client)
+            .with_request_id("request-id".to_string())
+            .detail("detail");
+
+        let (api_key, key) = ApiKey::new(id(), "name", vec!["a".to_string()], Some(3600));
+
+        let checks = [
+            (
+                "redis",
+                Check {
+                    ok: true,
+                    informational: false,
+                    error: None,
+                },
+            ),
+            (
+                "user_service",
+                Check {
+                    ok: false,
+                    informational: true,
+                    error: Some("timeout"),
+                },
+            ),
+        ];
+
+        let samples = vec![
+            sample(
+                "CheckAccessToken",
+                StatusCode::OK,
+                check_access_token::Model {
+                    token_id: id(),
+                    user_id: id(),
+                    amr: vec!["email".to_string()],
+                    auth_time: 0,
+                },
+            ),
+            sample(
+                "CheckAccessToken",
+                StatusCode::OK,
+                check_service_token::Model {
+                    token_id: id(),
+                    client_id: "client".to_string(),
+                    scopes: vec!["a".to_string()],
+                },
+            ),
+            sample(
+                "CheckAndRefreshTokenPair",
+                StatusCode::OK,
+                check_and_refresh_token_pair::Model {
+                    access_token: Some("access".to_string()),
+                    refresh_token: Some("refresh".to_string()),
+                    token_id: id(),
+                    user_id: id(),
+                },
+            ),
+            sample(
+                "IntrospectTokens",
+                StatusCode::OK,
+                introspect_tokens::Model {
+                    tokens: vec![
+                        introspect_tokens::Introspection {
+                            active: true,
+                            user_id: Some(id()),
+                            token_id: Some(id()),
+                            exp: Some(0),
+                            role: Some(0),
+                            scope: Some("library:read".to_string()),
+                        },
+                        introspect_tokens::Introspection::default(),
+                    ],
+                },
+            ),
+            sample(
+                "CreateTotp",
+                StatusCode::CREATED,
+                create_totp::Model {
+                    secret: "secret".to_string(),
+                    uri: "otpauth://totp/madome".to_string(),
+                    recovery_codes: vec!["code".to_string()],
+                },
+            ),
+            sample(
+                "ListSessions",
+                StatusCode::OK,
+                list_sessions::Model {
+                    user_id: session.user_id,
+                    sessions: vec![session.clone()],
+                },
+            ),
+            sample(
+                "RevokeSessions",
+                StatusCode::OK,
+                revoke_sessions::Model { revoked: 1 },
+            ),
+            sample(
+                "ClearAuthcodes",
+                StatusCode::OK,
+                clear_authcodes::Model { cleared: 1 },
+            ),
+            sample(
+                "ListAuditEvents",
+                StatusCode::OK,
+                list_audit_events::Model {
+                    events: vec![audit_event],
+                },
+            ),
+            sample(
+                "CreateApiKey",
+                StatusCode::CREATED,
+                create_api_key::Model {
+                    id: api_key.id,
+                    key,
+                    name: api_key.name.clone(),
+                    scopes: api_key.scopes.clone(),
+                    expires_at: api_key.expires_at,
+                },
+            ),
+            sample(
+                "ListApiKeys",
+                StatusCode::OK,
+                list_api_keys::Model {
+                    api_keys: vec![api_key.without_secret()],
+                },
+            ),
+            sample(
+                "CreateServiceToken",
+                StatusCode::OK,
+                create_service_token::Model {
+                    access_token: "access".to_string(),
+                    token_type: "Bearer",
+                    expires_in: 3600,
+                    scope: "a b".to_string(),
+                },
+            ),
+            sample("Liveness", StatusCode::OK, Liveness { status: "ok" }),
+            sample(
+                "Readiness",
+                StatusCode::SERVICE_UNAVAILABLE,
+                Readiness {
+                    ready: false,
+                    checks: checks.into_iter().collect::<BTreeMap<_, _>>(),
+                },
+            ),
+        ];
+
+        for (id, status, value) in samples {
+            let schema = response_schema(id, status);
+
+            assert!(!schema.is_null(), "{} {}: no schema", id, status);
+
+            if let Err(err) = validate(schema, &value, id) {
+                panic!("{}", err);
+            }
+        }
+    }
+}
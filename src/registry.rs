@@ -1,14 +1,24 @@
-pub use self::root_registry::RootRegistry;
+pub use self::root_registry::{CliRegistry, RootRegistry};
 
 mod root_registry {
     use sai::{combine_component_registry, component_registry, Component};
 
     use crate::{
         app::{HttpServer, Resolver},
-        command::{random_code::RandomCode, send_email::SendEmail, CommandSet, GetUser},
-        config::Config,
+        cli::Cli,
+        command::{
+            random_code::RandomCode, send_email::SendEmail, write_audit::WriteAudit, CommandSet,
+            GetUser,
+        },
+        config::{Config, ConfigReloader},
         database::DatabaseSet,
-        repository::{RedisAuthcodeRepository, RedisSecretKeyRepository, RepositorySet},
+        health::Health,
+        repository::{
+            RedisApiKeyRepository, RedisAuditRepository, RedisAuthcodeRepository,
+            RedisKnownDeviceRepository, RedisPasskeyRepository, RedisSecretKeyRepository,
+            RedisServiceClientRepository, RedisSessionRepository, RedisTotpRepository,
+            RepositorySet,
+        },
     };
 
     combine_component_registry!(
@@ -22,7 +32,20 @@ mod root_registry {
         ]
     );
 
-    component_registry!(ServerRegistry, [HttpServer]);
+    /// madome-auth-admin에서 사용함, server는 띄우지 않음
+    combine_component_registry!(
+        CliRegistry,
+        [
+            CliCommandRegistry,
+            RepositoryRegistry,
+            CommandRegistry,
+            ConfigRegistry
+        ]
+    );
+
+    component_registry!(CliCommandRegistry, [Cli]);
+
+    component_registry!(ServerRegistry, [HttpServer, Health, ConfigReloader]);
 
     component_registry!(ControllerRegistry, [Resolver]);
 
@@ -32,13 +55,20 @@ mod root_registry {
             DatabaseSet,
             RepositorySet,
             RedisAuthcodeRepository,
-            RedisSecretKeyRepository
+            RedisSecretKeyRepository,
+            RedisPasskeyRepository,
+            RedisTotpRepository,
+            RedisSessionRepository,
+            RedisKnownDeviceRepository,
+            RedisAuditRepository,
+            RedisServiceClientRepository,
+            RedisApiKeyRepository
         ]
     );
 
     component_registry!(
         CommandRegistry,
-        [CommandSet, GetUser, RandomCode, SendEmail]
+        [CommandSet, GetUser, RandomCode, SendEmail, WriteAudit]
     );
 
     component_registry!(ConfigRegistry, [Config]);
@@ -0,0 +1,47 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use sai::Component;
+use uuid::Uuid;
+
+use crate::{entity::api_key::ApiKey, repository::r#trait::ApiKeyRepository};
+
+#[cfg_attr(test, derive(Default))]
+#[derive(Component)]
+pub struct InMemoryApiKeyRepository {
+    inner: RwLock<HashMap<Uuid, ApiKey>>,
+}
+
+#[async_trait::async_trait]
+impl ApiKeyRepository for InMemoryApiKeyRepository {
+    async fn get(&self, id: Uuid) -> crate::Result<Option<ApiKey>> {
+        let inner = self.inner.read().unwrap();
+
+        Ok(inner.get(&id).filter(|x| !x.expired()).cloned())
+    }
+
+    async fn list(&self, user_id: Uuid) -> crate::Result<Vec<ApiKey>> {
+        let inner = self.inner.read().unwrap();
+
+        let api_keys = inner
+            .values()
+            .filter(|x| x.user_id == user_id && !x.expired())
+            .cloned()
+            .collect();
+
+        Ok(api_keys)
+    }
+
+    async fn add(&self, api_key: &ApiKey) -> crate::Result<bool> {
+        let mut inner = self.inner.write().unwrap();
+
+        inner.insert(api_key.id, api_key.clone());
+
+        Ok(true)
+    }
+
+    async fn remove(&self, id: Uuid) -> crate::Result<bool> {
+        let mut inner = self.inner.write().unwrap();
+
+        Ok(inner.remove(&id).is_some())
+    }
+}
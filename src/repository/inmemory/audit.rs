@@ -0,0 +1,36 @@
+use std::sync::RwLock;
+
+use sai::Component;
+use uuid::Uuid;
+
+use crate::{entity::audit::AuditEvent, repository::r#trait::AuditRepository};
+
+#[cfg_attr(test, derive(Default))]
+#[derive(Component)]
+pub struct InMemoryAuditRepository {
+    inner: RwLock<Vec<AuditEvent>>,
+}
+
+#[cfg(test)]
+impl InMemoryAuditRepository {
+    pub fn add(&self, event: AuditEvent) {
+        self.inner.write().unwrap().push(event);
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditRepository for InMemoryAuditRepository {
+    async fn list(&self, user_id: Uuid, count: usize) -> crate::Result<Vec<AuditEvent>> {
+        let inner = self.inner.read().unwrap();
+
+        let events = inner
+            .iter()
+            .rev()
+            .filter(|x| x.user_id == Some(user_id))
+            .take(count)
+            .cloned()
+            .collect();
+
+        Ok(events)
+    }
+}
@@ -1,9 +1,10 @@
 use std::{collections::HashMap, sync::Mutex, time::SystemTime};
 
-use sai::Component;
+use sai::{Component, Injected};
 use util::ori;
 
 use crate::{
+    config::Config,
     entity::authcode::{self, Authcode},
     repository::r#trait::AuthcodeRepository,
 };
@@ -15,10 +16,33 @@ pub enum Error {}
 #[derive(Component)]
 pub struct InMemoryAuthcodeRepository {
     inner: Mutex<HashMap<String, Vec<(Authcode, SystemTime)>>>,
+
+    #[injected]
+    config: Injected<Config>,
 }
 
 #[async_trait::async_trait]
 impl AuthcodeRepository for InMemoryAuthcodeRepository {
+    async fn get(&self, user_email: &str, code: &str) -> crate::Result<Option<Authcode>> {
+        let inner = self.inner.lock().unwrap();
+
+        let (authcode, timer) = ori! {
+            ori!(inner.get(user_email)).iter().find(|(x, _)| x.code == code)
+        };
+
+        let expired =
+            matches!(timer.elapsed(), Ok(elapsed) if elapsed.as_secs() > authcode::MAX_AGE);
+
+        if expired {
+            return Ok(None);
+        }
+
+        Ok(Some(Authcode::new(
+            authcode.user_email.clone(),
+            authcode.code.clone(),
+        )))
+    }
+
     async fn pop(&self, user_email: &str, code: &str) -> crate::Result<Option<Authcode>> {
         let mut inner = self.inner.lock().unwrap();
 
@@ -45,13 +69,15 @@ impl AuthcodeRepository for InMemoryAuthcodeRepository {
     }
 
     async fn add(&self, authcode: Authcode) -> crate::Result<bool> {
+        let limit = self.config.authcode_limit();
+
         let mut inner = self.inner.lock().unwrap();
 
         let authcodes = inner
             .entry(authcode.user_email.clone())
-            .or_insert_with(|| Vec::with_capacity(5));
+            .or_insert_with(|| Vec::with_capacity(limit));
 
-        if authcodes.len() >= 5 {
+        if authcodes.len() >= limit {
             let timer = authcodes.get(0).map(|(_, timer)| timer).unwrap();
 
             let expired =
@@ -68,4 +94,10 @@ impl AuthcodeRepository for InMemoryAuthcodeRepository {
 
         Ok(true)
     }
+
+    async fn clear(&self, user_email: &str) -> crate::Result<usize> {
+        let mut inner = self.inner.lock().unwrap();
+
+        Ok(inner.remove(user_email).map(|x| x.len()).unwrap_or(0))
+    }
 }
@@ -0,0 +1,30 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+};
+
+use sai::Component;
+use uuid::Uuid;
+
+use crate::repository::r#trait::KnownDeviceRepository;
+
+#[cfg_attr(test, derive(Default))]
+#[derive(Component)]
+pub struct InMemoryKnownDeviceRepository {
+    inner: RwLock<HashMap<Uuid, HashSet<String>>>,
+}
+
+#[async_trait::async_trait]
+impl KnownDeviceRepository for InMemoryKnownDeviceRepository {
+    async fn has_any(&self, user_id: Uuid) -> crate::Result<bool> {
+        let inner = self.inner.read().unwrap();
+
+        Ok(inner.get(&user_id).map(|x| !x.is_empty()).unwrap_or(false))
+    }
+
+    async fn add(&self, user_id: Uuid, device: &str) -> crate::Result<bool> {
+        let mut inner = self.inner.write().unwrap();
+
+        Ok(inner.entry(user_id).or_default().insert(device.to_string()))
+    }
+}
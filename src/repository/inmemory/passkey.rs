@@ -0,0 +1,94 @@
+use std::{collections::HashMap, sync::Mutex, time::SystemTime};
+
+use sai::Component;
+use uuid::Uuid;
+
+use crate::{
+    entity::passkey::{
+        self, Passkey, PasskeyAuthentication, PasskeyRegistration, CEREMONY_MAX_AGE,
+    },
+    repository::r#trait::PasskeyRepository,
+};
+
+fn expired(timer: &SystemTime) -> bool {
+    matches!(timer.elapsed(), Ok(elapsed) if elapsed.as_secs() > CEREMONY_MAX_AGE)
+}
+
+#[cfg_attr(test, derive(Default))]
+#[derive(Component)]
+pub struct InMemoryPasskeyRepository {
+    passkeys: Mutex<HashMap<Uuid, HashMap<String, Passkey>>>,
+    registrations: Mutex<HashMap<Uuid, (PasskeyRegistration, SystemTime)>>,
+    authentications: Mutex<HashMap<Uuid, (Uuid, PasskeyAuthentication, SystemTime)>>,
+}
+
+#[async_trait::async_trait]
+impl PasskeyRepository for InMemoryPasskeyRepository {
+    async fn list(&self, user_id: Uuid) -> crate::Result<Vec<Passkey>> {
+        let passkeys = self.passkeys.lock().unwrap();
+
+        let r = passkeys
+            .get(&user_id)
+            .map(|x| x.values().cloned().collect())
+            .unwrap_or_default();
+
+        Ok(r)
+    }
+
+    async fn add(&self, user_id: Uuid, passkey: &Passkey) -> crate::Result<bool> {
+        let mut passkeys = self.passkeys.lock().unwrap();
+
+        passkeys
+            .entry(user_id)
+            .or_default()
+            .insert(passkey::credential_key(passkey), passkey.clone());
+
+        Ok(true)
+    }
+
+    async fn add_registration(
+        &self,
+        user_id: Uuid,
+        state: &PasskeyRegistration,
+    ) -> crate::Result<bool> {
+        let mut registrations = self.registrations.lock().unwrap();
+
+        registrations.insert(user_id, (state.clone(), SystemTime::now()));
+
+        Ok(true)
+    }
+
+    async fn pop_registration(&self, user_id: Uuid) -> crate::Result<Option<PasskeyRegistration>> {
+        let mut registrations = self.registrations.lock().unwrap();
+
+        match registrations.remove(&user_id) {
+            Some((state, timer)) if !expired(&timer) => Ok(Some(state)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn add_authentication(
+        &self,
+        ceremony_id: Uuid,
+        user_id: Uuid,
+        state: &PasskeyAuthentication,
+    ) -> crate::Result<bool> {
+        let mut authentications = self.authentications.lock().unwrap();
+
+        authentications.insert(ceremony_id, (user_id, state.clone(), SystemTime::now()));
+
+        Ok(true)
+    }
+
+    async fn pop_authentication(
+        &self,
+        ceremony_id: Uuid,
+    ) -> crate::Result<Option<(Uuid, PasskeyAuthentication)>> {
+        let mut authentications = self.authentications.lock().unwrap();
+
+        match authentications.remove(&ceremony_id) {
+            Some((user_id, state, timer)) if !expired(&timer) => Ok(Some((user_id, state))),
+            _ => Ok(None),
+        }
+    }
+}
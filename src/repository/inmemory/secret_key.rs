@@ -30,6 +30,16 @@ impl SecretKeyRepository for InMemorySecretKeyRepository {
         }
     }
 
+    async fn get_many(&self, token_ids: &[Uuid]) -> crate::Result<Vec<Option<SecretKey>>> {
+        let mut secret_keys = Vec::with_capacity(token_ids.len());
+
+        for token_id in token_ids {
+            secret_keys.push(self.get(*token_id).await?);
+        }
+
+        Ok(secret_keys)
+    }
+
     async fn add(&self, token_id: Uuid, secret_key: &str) -> crate::Result<bool> {
         let mut inner = self.inner.write().unwrap();
 
@@ -50,4 +60,12 @@ impl SecretKeyRepository for InMemorySecretKeyRepository {
 
         Ok(true)
     }
+
+    async fn token_ids(&self) -> crate::Result<Vec<Uuid>> {
+        let inner = self.inner.read().unwrap();
+
+        let token_ids = inner.keys().filter_map(|x| x.parse().ok()).collect();
+
+        Ok(token_ids)
+    }
 }
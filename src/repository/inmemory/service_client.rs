@@ -0,0 +1,44 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use sai::Component;
+
+use crate::{entity::service_client::ServiceClient, repository::r#trait::ServiceClientRepository};
+
+#[cfg_attr(test, derive(Default))]
+#[derive(Component)]
+pub struct InMemoryServiceClientRepository {
+    inner: RwLock<HashMap<String, ServiceClient>>,
+}
+
+#[async_trait::async_trait]
+impl ServiceClientRepository for InMemoryServiceClientRepository {
+    async fn get(&self, client_id: &str) -> crate::Result<Option<ServiceClient>> {
+        let inner = self.inner.read().unwrap();
+
+        Ok(inner.get(client_id).cloned())
+    }
+
+    async fn list(&self) -> crate::Result<Vec<ServiceClient>> {
+        let inner = self.inner.read().unwrap();
+
+        Ok(inner.values().cloned().collect())
+    }
+
+    async fn add(&self, client: &ServiceClient) -> crate::Result<bool> {
+        let mut inner = self.inner.write().unwrap();
+
+        if inner.contains_key(&client.id) {
+            return Ok(false);
+        }
+
+        inner.insert(client.id.clone(), client.clone());
+
+        Ok(true)
+    }
+
+    async fn remove(&self, client_id: &str) -> crate::Result<bool> {
+        let mut inner = self.inner.write().unwrap();
+
+        Ok(inner.remove(client_id).is_some())
+    }
+}
@@ -0,0 +1,53 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use sai::Component;
+use uuid::Uuid;
+
+use crate::{entity::session::Session, repository::r#trait::SessionRepository};
+
+#[cfg_attr(test, derive(Default))]
+#[derive(Component)]
+pub struct InMemorySessionRepository {
+    inner: RwLock<HashMap<Uuid, Session>>,
+}
+
+#[async_trait::async_trait]
+impl SessionRepository for InMemorySessionRepository {
+    async fn get(&self, token_id: Uuid) -> crate::Result<Option<Session>> {
+        let inner = self.inner.read().unwrap();
+
+        Ok(inner.get(&token_id).cloned())
+    }
+
+    async fn list(&self, user_id: Uuid) -> crate::Result<Vec<Session>> {
+        let inner = self.inner.read().unwrap();
+
+        let sessions = inner
+            .values()
+            .filter(|x| x.user_id == user_id)
+            .cloned()
+            .collect();
+
+        Ok(sessions)
+    }
+
+    async fn add(&self, session: &Session) -> crate::Result<bool> {
+        let mut inner = self.inner.write().unwrap();
+
+        inner.insert(session.token_id, session.clone());
+
+        Ok(true)
+    }
+
+    async fn remove(&self, token_id: Uuid) -> crate::Result<bool> {
+        let mut inner = self.inner.write().unwrap();
+
+        Ok(inner.remove(&token_id).is_some())
+    }
+
+    async fn all(&self) -> crate::Result<Vec<Session>> {
+        let inner = self.inner.read().unwrap();
+
+        Ok(inner.values().cloned().collect())
+    }
+}
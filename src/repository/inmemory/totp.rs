@@ -0,0 +1,79 @@
+use std::{collections::HashMap, sync::RwLock, time::SystemTime};
+
+use sai::{Component, Injected};
+use uuid::Uuid;
+
+use crate::{config::Config, entity::totp::Totp, repository::r#trait::TotpRepository};
+
+#[cfg_attr(test, derive(Default))]
+#[derive(Component)]
+pub struct InMemoryTotpRepository {
+    inner: RwLock<HashMap<Uuid, Totp>>,
+    failures: RwLock<HashMap<Uuid, (u64, SystemTime)>>,
+
+    #[injected]
+    config: Injected<Config>,
+}
+
+impl InMemoryTotpRepository {
+    fn expired(&self, timer: &SystemTime) -> bool {
+        let window = self.config.totp_failure_window();
+
+        matches!(timer.elapsed(), Ok(elapsed) if elapsed.as_secs() > window)
+    }
+}
+
+#[async_trait::async_trait]
+impl TotpRepository for InMemoryTotpRepository {
+    async fn get(&self, user_id: Uuid) -> crate::Result<Option<Totp>> {
+        let inner = self.inner.read().unwrap();
+
+        Ok(inner.get(&user_id).cloned())
+    }
+
+    async fn add(&self, user_id: Uuid, totp: &Totp) -> crate::Result<bool> {
+        let mut inner = self.inner.write().unwrap();
+
+        inner.insert(user_id, totp.clone());
+
+        Ok(true)
+    }
+
+    async fn remove(&self, user_id: Uuid) -> crate::Result<bool> {
+        let mut inner = self.inner.write().unwrap();
+
+        Ok(inner.remove(&user_id).is_some())
+    }
+
+    async fn failures(&self, user_id: Uuid) -> crate::Result<u64> {
+        let failures = self.failures.read().unwrap();
+
+        match failures.get(&user_id) {
+            Some((count, timer)) if !self.expired(timer) => Ok(*count),
+            _ => Ok(0),
+        }
+    }
+
+    async fn add_failure(&self, user_id: Uuid) -> crate::Result<u64> {
+        let mut failures = self.failures.write().unwrap();
+
+        let (count, timer) = failures
+            .entry(user_id)
+            .or_insert_with(|| (0, SystemTime::now()));
+
+        if self.expired(timer) {
+            *count = 0;
+            *timer = SystemTime::now();
+        }
+
+        *count += 1;
+
+        Ok(*count)
+    }
+
+    async fn clear_failures(&self, user_id: Uuid) -> crate::Result<bool> {
+        let mut failures = self.failures.write().unwrap();
+
+        Ok(failures.remove(&user_id).is_some())
+    }
+}
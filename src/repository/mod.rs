@@ -27,6 +27,62 @@ pub struct RepositorySet {
     #[cfg(not(test))]
     #[injected]
     secret_key_repository: Injected<RedisSecretKeyRepository>,
+
+    #[cfg(test)]
+    #[injected]
+    passkey_repository: Injected<InMemoryPasskeyRepository>,
+
+    #[cfg(not(test))]
+    #[injected]
+    passkey_repository: Injected<RedisPasskeyRepository>,
+
+    #[cfg(test)]
+    #[injected]
+    totp_repository: Injected<InMemoryTotpRepository>,
+
+    #[cfg(not(test))]
+    #[injected]
+    totp_repository: Injected<RedisTotpRepository>,
+
+    #[cfg(test)]
+    #[injected]
+    service_client_repository: Injected<InMemoryServiceClientRepository>,
+
+    #[cfg(not(test))]
+    #[injected]
+    service_client_repository: Injected<RedisServiceClientRepository>,
+
+    #[cfg(test)]
+    #[injected]
+    session_repository: Injected<InMemorySessionRepository>,
+
+    #[cfg(not(test))]
+    #[injected]
+    session_repository: Injected<RedisSessionRepository>,
+
+    #[cfg(test)]
+    #[injected]
+    known_device_repository: Injected<InMemoryKnownDeviceRepository>,
+
+    #[cfg(not(test))]
+    #[injected]
+    known_device_repository: Injected<RedisKnownDeviceRepository>,
+
+    #[cfg(test)]
+    #[injected]
+    api_key_repository: Injected<InMemoryApiKeyRepository>,
+
+    #[cfg(not(test))]
+    #[injected]
+    api_key_repository: Injected<RedisApiKeyRepository>,
+
+    #[cfg(test)]
+    #[injected]
+    audit_repository: Injected<InMemoryAuditRepository>,
+
+    #[cfg(not(test))]
+    #[injected]
+    audit_repository: Injected<RedisAuditRepository>,
 }
 
 impl RepositorySet {
@@ -37,6 +93,40 @@ impl RepositorySet {
     pub fn secret_key(&self) -> Arc<impl r#trait::SecretKeyRepository> {
         Arc::clone(&self.secret_key_repository)
     }
+
+    pub fn passkey(&self) -> Arc<impl r#trait::PasskeyRepository> {
+        Arc::clone(&self.passkey_repository)
+    }
+
+    pub fn totp(&self) -> Arc<impl r#trait::TotpRepository> {
+        Arc::clone(&self.totp_repository)
+    }
+
+    pub fn session(&self) -> Arc<impl r#trait::SessionRepository> {
+        Arc::clone(&self.session_repository)
+    }
+
+    pub fn known_device(&self) -> Arc<impl r#trait::KnownDeviceRepository> {
+        Arc::clone(&self.known_device_repository)
+    }
+
+    pub fn service_client(&self) -> Arc<impl r#trait::ServiceClientRepository> {
+        Arc::clone(&self.service_client_repository)
+    }
+
+    pub fn api_key(&self) -> Arc<impl r#trait::ApiKeyRepository> {
+        Arc::clone(&self.api_key_repository)
+    }
+
+    pub fn audit(&self) -> Arc<impl r#trait::AuditRepository> {
+        Arc::clone(&self.audit_repository)
+    }
+
+    /// 실제로는 WriteAudit command가 sink에 씀
+    #[cfg(test)]
+    pub fn add_audit_event(&self, event: crate::entity::audit::AuditEvent) {
+        self.audit_repository.add(event);
+    }
 }
 
 #[cfg(test)]
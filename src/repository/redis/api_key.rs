@@ -0,0 +1,115 @@
+use chrono::Utc;
+use redis::AsyncCommands;
+use sai::{Component, Injected};
+use uuid::Uuid;
+
+use crate::{
+    database::DatabaseSet, entity::api_key::ApiKey, error::RepositoryError,
+    repository::r#trait::ApiKeyRepository,
+};
+
+#[derive(Component)]
+pub struct RedisApiKeyRepository {
+    #[injected]
+    database: Injected<DatabaseSet>,
+}
+
+#[async_trait::async_trait]
+impl ApiKeyRepository for RedisApiKeyRepository {
+    async fn get(&self, id: Uuid) -> crate::Result<Option<ApiKey>> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("api_key:{}", id);
+
+        let r: Option<String> = redis.get(key).await?;
+
+        match r {
+            Some(serialized) => {
+                let api_key = serde_json::from_str(&serialized).map_err(RepositoryError::from)?;
+
+                Ok(Some(api_key))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn list(&self, user_id: Uuid) -> crate::Result<Vec<ApiKey>> {
+        let mut redis = self.database.redis().await?;
+
+        let index_key = format!("api_keys:{}", user_id);
+
+        let ids: Vec<String> = redis.smembers(&index_key).await?;
+
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys = ids
+            .iter()
+            .map(|id| format!("api_key:{}", id))
+            .collect::<Vec<_>>();
+
+        let serialized: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut redis)
+            .await?;
+
+        let mut api_keys = Vec::with_capacity(serialized.len());
+
+        for (id, serialized) in ids.iter().zip(serialized) {
+            match serialized {
+                Some(serialized) => {
+                    let api_key =
+                        serde_json::from_str(&serialized).map_err(RepositoryError::from)?;
+
+                    api_keys.push(api_key);
+                }
+                // 만료된 api key는 index에서도 지움
+                None => {
+                    let _r: i64 = redis.srem(&index_key, id).await?;
+                }
+            }
+        }
+
+        Ok(api_keys)
+    }
+
+    async fn add(&self, api_key: &ApiKey) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("api_key:{}", api_key.id);
+        let index_key = format!("api_keys:{}", api_key.user_id);
+        let serialized = serde_json::to_string(api_key).map_err(RepositoryError::from)?;
+
+        let r: bool = match api_key.expires_at {
+            Some(expires_at) => {
+                let ttl = (expires_at - Utc::now().timestamp()).max(1);
+
+                redis.set_ex(key, serialized, ttl as usize).await?
+            }
+            None => redis.set(key, serialized).await?,
+        };
+
+        let _r: i64 = redis.sadd(index_key, api_key.id.to_string()).await?;
+
+        Ok(r)
+    }
+
+    async fn remove(&self, id: Uuid) -> crate::Result<bool> {
+        let api_key = match self.get(id).await? {
+            Some(api_key) => api_key,
+            None => return Ok(false),
+        };
+
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("api_key:{}", id);
+        let index_key = format!("api_keys:{}", api_key.user_id);
+
+        let _r: i64 = redis.srem(index_key, id.to_string()).await?;
+
+        let r: bool = redis.del(key).await?;
+
+        Ok(r)
+    }
+}
@@ -0,0 +1,50 @@
+use sai::{Component, Injected};
+use uuid::Uuid;
+
+use crate::{
+    database::DatabaseSet, entity::audit::AuditEvent, error::RepositoryError,
+    repository::r#trait::AuditRepository,
+};
+
+/// AUDIT_SINK=redis일 때만 읽을 수 있음
+#[derive(Component)]
+pub struct RedisAuditRepository {
+    #[injected]
+    database: Injected<DatabaseSet>,
+}
+
+#[async_trait::async_trait]
+impl AuditRepository for RedisAuditRepository {
+    async fn list(&self, user_id: Uuid, count: usize) -> crate::Result<Vec<AuditEvent>> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("audit:{}", user_id);
+
+        // [(id, [field, value, ...])]
+        let entries: Vec<(String, Vec<String>)> = redis::cmd("XREVRANGE")
+            .arg(&key)
+            .arg("+")
+            .arg("-")
+            .arg("COUNT")
+            .arg(count)
+            .query_async(&mut redis)
+            .await?;
+
+        let mut events = Vec::with_capacity(entries.len());
+
+        for (_, fields) in entries {
+            let serialized = fields
+                .chunks(2)
+                .find(|x| x[0] == "event")
+                .and_then(|x| x.get(1));
+
+            if let Some(serialized) = serialized {
+                let event = serde_json::from_str(serialized).map_err(RepositoryError::from)?;
+
+                events.push(event);
+            }
+        }
+
+        Ok(events)
+    }
+}
@@ -3,6 +3,7 @@ use redis::AsyncCommands;
 use sai::{Component, Injected};
 
 use crate::{
+    config::Config,
     database::DatabaseSet,
     entity::authcode::{self, Authcode},
     repository::r#trait::AuthcodeRepository,
@@ -13,10 +14,29 @@ use crate::{
 pub struct RedisAuthcodeRepository {
     #[injected]
     database: Injected<DatabaseSet>,
+
+    #[injected]
+    config: Injected<Config>,
 }
 
 #[async_trait::async_trait]
 impl AuthcodeRepository for RedisAuthcodeRepository {
+    async fn get(&self, user_email: &str, code: &str) -> crate::Result<Option<Authcode>> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("authcode:{}:{}", user_email, code);
+
+        let r: Option<String> = redis::cmd("GET")
+            .arg(&[&key])
+            .query_async(&mut redis)
+            .await?;
+
+        Ok(r.map(|code| Authcode {
+            code,
+            user_email: user_email.to_string(),
+        }))
+    }
+
     async fn pop(&self, user_email: &str, code: &str) -> crate::Result<Option<Authcode>> {
         let mut redis = self.database.redis().await?;
 
@@ -45,7 +65,7 @@ impl AuthcodeRepository for RedisAuthcodeRepository {
 
         log::debug!("scan_match({}) = {:?}", pattern, keys);
 
-        if keys.len() >= 5 {
+        if keys.len() >= self.config.authcode_limit() {
             return Ok(false);
         }
 
@@ -63,4 +83,23 @@ impl AuthcodeRepository for RedisAuthcodeRepository {
 
         Ok(r)
     }
+
+    async fn clear(&self, user_email: &str) -> crate::Result<usize> {
+        let mut redis = self.database.redis().await?;
+
+        let pattern = format!("authcode:{}:*", user_email);
+        let keys = redis
+            .scan_match(&pattern)
+            .await?
+            .collect::<Vec<String>>()
+            .await;
+
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        let r: usize = redis.del(keys).await?;
+
+        Ok(r)
+    }
 }
@@ -0,0 +1,34 @@
+use redis::AsyncCommands;
+use sai::{Component, Injected};
+use uuid::Uuid;
+
+use crate::{database::DatabaseSet, repository::r#trait::KnownDeviceRepository};
+
+#[derive(Component)]
+pub struct RedisKnownDeviceRepository {
+    #[injected]
+    database: Injected<DatabaseSet>,
+}
+
+#[async_trait::async_trait]
+impl KnownDeviceRepository for RedisKnownDeviceRepository {
+    async fn has_any(&self, user_id: Uuid) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("known_devices:{}", user_id);
+
+        let r: bool = redis.exists(key).await?;
+
+        Ok(r)
+    }
+
+    async fn add(&self, user_id: Uuid, device: &str) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("known_devices:{}", user_id);
+
+        let r: i64 = redis.sadd(key, device).await?;
+
+        Ok(r > 0)
+    }
+}
@@ -1,5 +1,19 @@
+mod api_key;
+mod audit;
 mod authcode;
+mod known_device;
+mod passkey;
 mod secret_key;
+mod service_client;
+mod session;
+mod totp;
 
+pub use api_key::*;
+pub use audit::*;
 pub use authcode::*;
+pub use known_device::*;
+pub use passkey::*;
 pub use secret_key::*;
+pub use service_client::*;
+pub use session::*;
+pub use totp::*;
@@ -0,0 +1,118 @@
+use redis::AsyncCommands;
+use sai::{Component, Injected};
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    database::DatabaseSet,
+    entity::passkey::{
+        self, Passkey, PasskeyAuthentication, PasskeyRegistration, CEREMONY_MAX_AGE,
+    },
+    error::RepositoryError,
+    repository::r#trait::PasskeyRepository,
+};
+
+#[derive(Component)]
+pub struct RedisPasskeyRepository {
+    #[injected]
+    database: Injected<DatabaseSet>,
+}
+
+impl RedisPasskeyRepository {
+    async fn set_state<T: Serialize + Sync>(&self, key: String, state: &T) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let serialized = serde_json::to_string(state).map_err(RepositoryError::from)?;
+
+        let r = redis
+            .set_ex(key, serialized, CEREMONY_MAX_AGE as usize)
+            .await?;
+
+        Ok(r)
+    }
+
+    async fn pop_state<T: DeserializeOwned>(&self, key: String) -> crate::Result<Option<T>> {
+        let mut redis = self.database.redis().await?;
+
+        let r: Option<String> = redis::cmd("GETDEL")
+            .arg(&[&key])
+            .query_async(&mut redis)
+            .await?;
+
+        match r {
+            Some(serialized) => {
+                let state = serde_json::from_str(&serialized).map_err(RepositoryError::from)?;
+
+                Ok(Some(state))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PasskeyRepository for RedisPasskeyRepository {
+    async fn list(&self, user_id: Uuid) -> crate::Result<Vec<Passkey>> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("passkey:{}", user_id);
+
+        let serialized: Vec<String> = redis.hvals(key).await?;
+
+        let passkeys = serialized
+            .iter()
+            .map(|x| serde_json::from_str(x))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::from)?;
+
+        Ok(passkeys)
+    }
+
+    async fn add(&self, user_id: Uuid, passkey: &Passkey) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("passkey:{}", user_id);
+        let field = passkey::credential_key(passkey);
+        let serialized = serde_json::to_string(passkey).map_err(RepositoryError::from)?;
+
+        // 덮어썼을 때는 0을 반환하기 때문에 결과는 무시함
+        let _r: i64 = redis.hset(key, field, serialized).await?;
+
+        Ok(true)
+    }
+
+    async fn add_registration(
+        &self,
+        user_id: Uuid,
+        state: &PasskeyRegistration,
+    ) -> crate::Result<bool> {
+        self.set_state(format!("webauthn:registration:{}", user_id), state)
+            .await
+    }
+
+    async fn pop_registration(&self, user_id: Uuid) -> crate::Result<Option<PasskeyRegistration>> {
+        self.pop_state(format!("webauthn:registration:{}", user_id))
+            .await
+    }
+
+    async fn add_authentication(
+        &self,
+        ceremony_id: Uuid,
+        user_id: Uuid,
+        state: &PasskeyAuthentication,
+    ) -> crate::Result<bool> {
+        self.set_state(
+            format!("webauthn:authentication:{}", ceremony_id),
+            &(user_id, state),
+        )
+        .await
+    }
+
+    async fn pop_authentication(
+        &self,
+        ceremony_id: Uuid,
+    ) -> crate::Result<Option<(Uuid, PasskeyAuthentication)>> {
+        self.pop_state(format!("webauthn:authentication:{}", ceremony_id))
+            .await
+    }
+}
@@ -1,3 +1,5 @@
+use futures_util::StreamExt;
+use redis::AsyncCommands;
 use sai::{Component, Injected};
 use uuid::Uuid;
 
@@ -24,6 +26,23 @@ impl SecretKeyRepository for RedisSecretKeyRepository {
         Ok(secret_key.map(SecretKey))
     }
 
+    async fn get_many(&self, token_ids: &[Uuid]) -> crate::Result<Vec<Option<SecretKey>>> {
+        if token_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut redis = self.database.redis().await?;
+
+        let keys = token_ids.iter().map(Uuid::to_string).collect::<Vec<_>>();
+
+        let secret_keys: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut redis)
+            .await?;
+
+        Ok(secret_keys.into_iter().map(|x| x.map(SecretKey)).collect())
+    }
+
     async fn add(&self, token_id: Uuid, secret_key: &str) -> crate::Result<bool> {
         let token_id = token_id.to_string();
         let mut redis = self.database.redis().await?;
@@ -50,4 +69,19 @@ impl SecretKeyRepository for RedisSecretKeyRepository {
 
         Ok(r)
     }
+
+    async fn token_ids(&self) -> crate::Result<Vec<Uuid>> {
+        let mut redis = self.database.redis().await?;
+
+        // secret key는 token id를 그대로 key로 사용함
+        let keys = redis
+            .scan_match("????????-????-????-????-????????????")
+            .await?
+            .collect::<Vec<String>>()
+            .await;
+
+        let token_ids = keys.iter().filter_map(|x| x.parse().ok()).collect();
+
+        Ok(token_ids)
+    }
 }
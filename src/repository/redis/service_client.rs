@@ -0,0 +1,84 @@
+use futures_util::StreamExt;
+use redis::AsyncCommands;
+use sai::{Component, Injected};
+
+use crate::{
+    database::DatabaseSet, entity::service_client::ServiceClient, error::RepositoryError,
+    repository::r#trait::ServiceClientRepository,
+};
+
+#[derive(Component)]
+pub struct RedisServiceClientRepository {
+    #[injected]
+    database: Injected<DatabaseSet>,
+}
+
+#[async_trait::async_trait]
+impl ServiceClientRepository for RedisServiceClientRepository {
+    async fn get(&self, client_id: &str) -> crate::Result<Option<ServiceClient>> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("service_client:{}", client_id);
+
+        let r: Option<String> = redis.get(key).await?;
+
+        match r {
+            Some(serialized) => {
+                let client = serde_json::from_str(&serialized).map_err(RepositoryError::from)?;
+
+                Ok(Some(client))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn list(&self) -> crate::Result<Vec<ServiceClient>> {
+        let mut redis = self.database.redis().await?;
+
+        let keys = redis
+            .scan_match("service_client:*")
+            .await?
+            .collect::<Vec<String>>()
+            .await;
+
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let serialized: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut redis)
+            .await?;
+
+        let mut clients = Vec::with_capacity(serialized.len());
+
+        for serialized in serialized.into_iter().flatten() {
+            let client = serde_json::from_str(&serialized).map_err(RepositoryError::from)?;
+
+            clients.push(client);
+        }
+
+        Ok(clients)
+    }
+
+    async fn add(&self, client: &ServiceClient) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("service_client:{}", client.id);
+        let serialized = serde_json::to_string(client).map_err(RepositoryError::from)?;
+
+        let r: bool = redis.set_nx(key, serialized).await?;
+
+        Ok(r)
+    }
+
+    async fn remove(&self, client_id: &str) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("service_client:{}", client_id);
+
+        let r: bool = redis.del(key).await?;
+
+        Ok(r)
+    }
+}
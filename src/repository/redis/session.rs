@@ -0,0 +1,142 @@
+use futures_util::StreamExt;
+use redis::AsyncCommands;
+use sai::{Component, Injected};
+use uuid::Uuid;
+
+use crate::{
+    database::DatabaseSet,
+    entity::{secret_key::SECRET_KEY_EXP, session::Session},
+    error::RepositoryError,
+    repository::r#trait::SessionRepository,
+};
+
+#[derive(Component)]
+pub struct RedisSessionRepository {
+    #[injected]
+    database: Injected<DatabaseSet>,
+}
+
+#[async_trait::async_trait]
+impl SessionRepository for RedisSessionRepository {
+    async fn get(&self, token_id: Uuid) -> crate::Result<Option<Session>> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("session:{}", token_id);
+
+        let r: Option<String> = redis.get(key).await?;
+
+        match r {
+            Some(serialized) => {
+                let session = serde_json::from_str(&serialized).map_err(RepositoryError::from)?;
+
+                Ok(Some(session))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn list(&self, user_id: Uuid) -> crate::Result<Vec<Session>> {
+        let mut redis = self.database.redis().await?;
+
+        let index_key = format!("sessions:{}", user_id);
+
+        let token_ids: Vec<String> = redis.smembers(&index_key).await?;
+
+        if token_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys = token_ids
+            .iter()
+            .map(|token_id| format!("session:{}", token_id))
+            .collect::<Vec<_>>();
+
+        let serialized: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut redis)
+            .await?;
+
+        let mut sessions = Vec::with_capacity(serialized.len());
+
+        for (token_id, serialized) in token_ids.iter().zip(serialized) {
+            match serialized {
+                Some(serialized) => {
+                    let session =
+                        serde_json::from_str(&serialized).map_err(RepositoryError::from)?;
+
+                    sessions.push(session);
+                }
+                // 만료된 session은 index에서도 지움
+                None => {
+                    let _r: i64 = redis.srem(&index_key, token_id).await?;
+                }
+            }
+        }
+
+        Ok(sessions)
+    }
+
+    async fn add(&self, session: &Session) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("session:{}", session.token_id);
+        let index_key = format!("sessions:{}", session.user_id);
+        let serialized = serde_json::to_string(session).map_err(RepositoryError::from)?;
+
+        let r = redis
+            .set_ex(key, serialized, SECRET_KEY_EXP as usize)
+            .await?;
+
+        let _r: i64 = redis.sadd(index_key, session.token_id.to_string()).await?;
+
+        Ok(r)
+    }
+
+    async fn remove(&self, token_id: Uuid) -> crate::Result<bool> {
+        let session = match self.get(token_id).await? {
+            Some(session) => session,
+            None => return Ok(false),
+        };
+
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("session:{}", token_id);
+        let index_key = format!("sessions:{}", session.user_id);
+
+        let _r: i64 = redis.srem(index_key, token_id.to_string()).await?;
+
+        let r: bool = redis.del(key).await?;
+
+        Ok(r)
+    }
+
+    async fn all(&self) -> crate::Result<Vec<Session>> {
+        let mut redis = self.database.redis().await?;
+
+        let keys = redis
+            .scan_match("session:*")
+            .await?
+            .collect::<Vec<String>>()
+            .await;
+
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let serialized: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut redis)
+            .await?;
+
+        let mut sessions = Vec::with_capacity(serialized.len());
+
+        // scan한 뒤에 만료된 건 None
+        for serialized in serialized.into_iter().flatten() {
+            let session = serde_json::from_str(&serialized).map_err(RepositoryError::from)?;
+
+            sessions.push(session);
+        }
+
+        Ok(sessions)
+    }
+}
@@ -0,0 +1,117 @@
+use redis::AsyncCommands;
+use sai::{Component, Injected};
+use uuid::Uuid;
+
+use crate::{
+    config::Config, database::DatabaseSet, entity::totp::Totp, error::RepositoryError,
+    repository::r#trait::TotpRepository,
+};
+
+#[derive(Component)]
+pub struct RedisTotpRepository {
+    #[injected]
+    database: Injected<DatabaseSet>,
+
+    #[injected]
+    config: Injected<Config>,
+}
+
+#[async_trait::async_trait]
+impl TotpRepository for RedisTotpRepository {
+    async fn get(&self, user_id: Uuid) -> crate::Result<Option<Totp>> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("totp:{}", user_id);
+
+        let r: Option<String> = redis::cmd("GET")
+            .arg(&[&key])
+            .query_async(&mut redis)
+            .await?;
+
+        let serialized = match r {
+            // 암호화하기 전에 저장된 값, 다음에 저장할 때 암호화됨
+            Some(serialized) if serialized.starts_with('{') => serialized.into_bytes(),
+            Some(sealed) => self
+                .config
+                .totp_encryption_key()
+                .open(&key, &sealed)
+                .ok_or_else(|| RepositoryError::Decrypt(key.clone()))?,
+            None => return Ok(None),
+        };
+
+        let totp = serde_json::from_slice(&serialized).map_err(RepositoryError::from)?;
+
+        Ok(Some(totp))
+    }
+
+    async fn add(&self, user_id: Uuid, totp: &Totp) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("totp:{}", user_id);
+        let serialized = serde_json::to_vec(totp).map_err(RepositoryError::from)?;
+        let sealed = self.config.totp_encryption_key().seal(&key, &serialized);
+
+        let r: bool = redis::cmd("SET")
+            .arg(&[&key, &sealed])
+            .query_async(&mut redis)
+            .await?;
+
+        Ok(r)
+    }
+
+    async fn remove(&self, user_id: Uuid) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("totp:{}", user_id);
+
+        let r: bool = redis::cmd("DEL")
+            .arg(&[&key])
+            .query_async(&mut redis)
+            .await?;
+
+        Ok(r)
+    }
+
+    async fn failures(&self, user_id: Uuid) -> crate::Result<u64> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("totp:failures:{}", user_id);
+
+        let r: Option<u64> = redis::cmd("GET")
+            .arg(&[&key])
+            .query_async(&mut redis)
+            .await?;
+
+        Ok(r.unwrap_or(0))
+    }
+
+    async fn add_failure(&self, user_id: Uuid) -> crate::Result<u64> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("totp:failures:{}", user_id);
+
+        let r: u64 = redis.incr(&key, 1).await?;
+
+        // 첫 실패부터 TOTP_FAILURE_WINDOW를 셈
+        if r == 1 {
+            let window = self.config.totp_failure_window();
+
+            let _r: bool = redis.expire(&key, window as usize).await?;
+        }
+
+        Ok(r)
+    }
+
+    async fn clear_failures(&self, user_id: Uuid) -> crate::Result<bool> {
+        let mut redis = self.database.redis().await?;
+
+        let key = format!("totp:failures:{}", user_id);
+
+        let r: bool = redis::cmd("DEL")
+            .arg(&[&key])
+            .query_async(&mut redis)
+            .await?;
+
+        Ok(r)
+    }
+}
@@ -0,0 +1,14 @@
+use uuid::Uuid;
+
+use crate::entity::api_key::ApiKey;
+
+#[async_trait::async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn get(&self, id: Uuid) -> crate::Result<Option<ApiKey>>;
+
+    async fn list(&self, user_id: Uuid) -> crate::Result<Vec<ApiKey>>;
+
+    async fn add(&self, api_key: &ApiKey) -> crate::Result<bool>;
+
+    async fn remove(&self, id: Uuid) -> crate::Result<bool>;
+}
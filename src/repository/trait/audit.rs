@@ -0,0 +1,10 @@
+use uuid::Uuid;
+
+use crate::entity::audit::AuditEvent;
+
+/// AuditSink로 남긴 event를 읽음
+#[async_trait::async_trait]
+pub trait AuditRepository: Send + Sync {
+    /// 최근 것부터 count개
+    async fn list(&self, user_id: Uuid, count: usize) -> crate::Result<Vec<AuditEvent>>;
+}
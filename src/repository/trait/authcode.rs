@@ -2,7 +2,13 @@ use crate::entity::authcode::Authcode;
 
 #[async_trait::async_trait]
 pub trait AuthcodeRepository: Send + Sync {
+    /// 지우지 않고 확인만 함
+    async fn get(&self, user_email: &str, code: &str) -> crate::Result<Option<Authcode>>;
+
     async fn pop(&self, user_email: &str, code: &str) -> crate::Result<Option<Authcode>>;
 
     async fn add(&self, authcode: Authcode) -> crate::Result<bool>;
+
+    /// 발급된 authcode를 모두 지움 (너무 많이 발급해서 막힌 걸 풀 때 사용함)
+    async fn clear(&self, user_email: &str) -> crate::Result<usize>;
 }
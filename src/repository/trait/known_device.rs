@@ -0,0 +1,10 @@
+use uuid::Uuid;
+
+#[async_trait::async_trait]
+pub trait KnownDeviceRepository: Send + Sync {
+    /// 한번이라도 기록된 기기가 있는지
+    async fn has_any(&self, user_id: Uuid) -> crate::Result<bool>;
+
+    /// 처음 보는 기기라면 true를 반환함
+    async fn add(&self, user_id: Uuid, device: &str) -> crate::Result<bool>;
+}
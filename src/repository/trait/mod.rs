@@ -1,5 +1,19 @@
+mod api_key;
+mod audit;
 mod authcode;
+mod known_device;
+mod passkey;
 mod secret_key;
+mod service_client;
+mod session;
+mod totp;
 
+pub use api_key::ApiKeyRepository;
+pub use audit::AuditRepository;
 pub use authcode::AuthcodeRepository;
+pub use known_device::KnownDeviceRepository;
+pub use passkey::PasskeyRepository;
 pub use secret_key::SecretKeyRepository;
+pub use service_client::ServiceClientRepository;
+pub use session::SessionRepository;
+pub use totp::TotpRepository;
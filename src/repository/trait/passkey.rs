@@ -0,0 +1,32 @@
+use uuid::Uuid;
+
+use crate::entity::passkey::{Passkey, PasskeyAuthentication, PasskeyRegistration};
+
+#[async_trait::async_trait]
+pub trait PasskeyRepository: Send + Sync {
+    async fn list(&self, user_id: Uuid) -> crate::Result<Vec<Passkey>>;
+
+    /// 같은 credential id가 있으면 덮어씀
+    async fn add(&self, user_id: Uuid, passkey: &Passkey) -> crate::Result<bool>;
+
+    async fn add_registration(
+        &self,
+        user_id: Uuid,
+        state: &PasskeyRegistration,
+    ) -> crate::Result<bool>;
+
+    async fn pop_registration(&self, user_id: Uuid) -> crate::Result<Option<PasskeyRegistration>>;
+
+    /// 로그인 전이라 user id 대신 client에게 돌려준 ceremony id로 보관함
+    async fn add_authentication(
+        &self,
+        ceremony_id: Uuid,
+        user_id: Uuid,
+        state: &PasskeyAuthentication,
+    ) -> crate::Result<bool>;
+
+    async fn pop_authentication(
+        &self,
+        ceremony_id: Uuid,
+    ) -> crate::Result<Option<(Uuid, PasskeyAuthentication)>>;
+}
@@ -6,7 +6,13 @@ use crate::entity::secret_key::SecretKey;
 pub trait SecretKeyRepository: Send + Sync {
     async fn get(&self, token_id: Uuid) -> crate::Result<Option<SecretKey>>;
 
+    /// token_ids와 같은 순서로 돌려줌
+    async fn get_many(&self, token_ids: &[Uuid]) -> crate::Result<Vec<Option<SecretKey>>>;
+
     async fn add(&self, token_id: Uuid, secret_key: &str) -> crate::Result<bool>;
 
     async fn remove(&self, token_id: Uuid) -> crate::Result<bool>;
+
+    /// 저장된 모든 secret key의 token id (admin cli에서 사용함)
+    async fn token_ids(&self) -> crate::Result<Vec<Uuid>>;
 }
@@ -0,0 +1,12 @@
+use crate::entity::service_client::ServiceClient;
+
+#[async_trait::async_trait]
+pub trait ServiceClientRepository: Send + Sync {
+    async fn get(&self, client_id: &str) -> crate::Result<Option<ServiceClient>>;
+
+    async fn list(&self) -> crate::Result<Vec<ServiceClient>>;
+
+    async fn add(&self, client: &ServiceClient) -> crate::Result<bool>;
+
+    async fn remove(&self, client_id: &str) -> crate::Result<bool>;
+}
@@ -0,0 +1,17 @@
+use uuid::Uuid;
+
+use crate::entity::session::Session;
+
+#[async_trait::async_trait]
+pub trait SessionRepository: Send + Sync {
+    async fn get(&self, token_id: Uuid) -> crate::Result<Option<Session>>;
+
+    async fn list(&self, user_id: Uuid) -> crate::Result<Vec<Session>>;
+
+    async fn add(&self, session: &Session) -> crate::Result<bool>;
+
+    async fn remove(&self, token_id: Uuid) -> crate::Result<bool>;
+
+    /// 모든 유저의 session (admin cli에서 사용함)
+    async fn all(&self) -> crate::Result<Vec<Session>>;
+}
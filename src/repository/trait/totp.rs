@@ -0,0 +1,21 @@
+use uuid::Uuid;
+
+use crate::entity::totp::Totp;
+
+#[async_trait::async_trait]
+pub trait TotpRepository: Send + Sync {
+    async fn get(&self, user_id: Uuid) -> crate::Result<Option<Totp>>;
+
+    /// 이미 있으면 덮어씀, TOTP_ENCRYPTION_KEY로 암호화해서 저장함
+    async fn add(&self, user_id: Uuid, totp: &Totp) -> crate::Result<bool>;
+
+    async fn remove(&self, user_id: Uuid) -> crate::Result<bool>;
+
+    /// TOTP_FAILURE_WINDOW 안에 틀린 횟수
+    async fn failures(&self, user_id: Uuid) -> crate::Result<u64>;
+
+    /// 틀린 횟수를 늘리고 늘어난 값을 돌려줌, 첫 실패부터 TOTP_FAILURE_WINDOW가 지나면 초기화됨
+    async fn add_failure(&self, user_id: Uuid) -> crate::Result<u64>;
+
+    async fn clear_failures(&self, user_id: Uuid) -> crate::Result<bool>;
+}
@@ -0,0 +1,213 @@
+use hyper::Method;
+
+/// `/v1/auth/token`과 `/auth/token`을 같은 route로 봄
+pub const VERSIONS: [&str; 1] = ["v1"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Static(&'static str),
+    /// `{id}`
+    Param(&'static str),
+}
+
+struct Route<T> {
+    method: Method,
+    pattern: &'static str,
+    segments: Vec<Segment>,
+    handler: T,
+}
+
+/// path parameter, request extension으로 넘겨줌
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params(Vec<(&'static str, String)>);
+
+impl Params {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+pub enum Match<'a, T> {
+    Found(&'a T, Params),
+    /// path는 있지만 method가 다름, Allow header에 사용함
+    MethodNotAllowed(Vec<Method>),
+    NotFound,
+}
+
+pub struct Router<T> {
+    routes: Vec<Route<T>>,
+}
+
+impl<T> Default for Router<T> {
+    fn default() -> Self {
+        Self { routes: Vec::new() }
+    }
+}
+
+impl<T> Router<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn route(mut self, method: Method, pattern: &'static str, handler: T) -> Self {
+        let segments = pattern
+            .split('/')
+            .filter(|x| !x.is_empty())
+            .map(
+                |x| match x.strip_prefix('{').and_then(|x| x.strip_suffix('}')) {
+                    Some(name) => Segment::Param(name),
+                    None => Segment::Static(x),
+                },
+            )
+            .collect();
+
+        self.routes.push(Route {
+            method,
+            pattern,
+            segments,
+            handler,
+        });
+
+        self
+    }
+
+    /// 등록된 순서대로 (method, pattern)
+    pub fn routes(&self) -> impl Iterator<Item = (&Method, &'static str)> {
+        self.routes.iter().map(|x| (&x.method, x.pattern))
+    }
+
+    pub fn find(&self, method: &Method, path: &str) -> Match<'_, T> {
+        let segments = path
+            .split('/')
+            .filter(|x| !x.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut allow = Vec::new();
+
+        for route in &self.routes {
+            let params = match matches(&route.segments, &segments) {
+                Some(params) => params,
+                None => continue,
+            };
+
+            if route.method == method {
+                return Match::Found(&route.handler, params);
+            }
+
+            if !allow.contains(&route.method) {
+                allow.push(route.method.clone());
+            }
+        }
+
+        if allow.is_empty() {
+            Match::NotFound
+        } else {
+            Match::MethodNotAllowed(allow)
+        }
+    }
+}
+
+fn matches(route: &[Segment], path: &[&str]) -> Option<Params> {
+    if route.len() != path.len() {
+        return None;
+    }
+
+    let mut params = Vec::new();
+
+    for (segment, x) in route.iter().zip(path) {
+        match segment {
+            Segment::Static(s) if s == x => {}
+            Segment::Param(name) => params.push((*name, x.to_string())),
+            _ => return None,
+        }
+    }
+
+    Some(Params(params))
+}
+
+/// base_path와 version을 뗌, base_path가 없는 요청도 그대로 받음
+///
+/// `/api/v1/auth/token` -> `/auth/token`
+pub fn strip_prefix<'a>(path: &'a str, base_path: &str) -> &'a str {
+    let base_path = base_path.trim_end_matches('/');
+
+    let path = match path.strip_prefix(base_path) {
+        Some(x) if !base_path.is_empty() && (x.is_empty() || x.starts_with('/')) => x,
+        _ => path,
+    };
+
+    for version in VERSIONS {
+        if let Some(x) = path
+            .strip_prefix('/')
+            .and_then(|x| x.strip_prefix(version))
+            .filter(|x| x.is_empty() || x.starts_with('/'))
+        {
+            return x;
+        }
+    }
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::Method;
+
+    use super::{strip_prefix, Match, Router};
+
+    fn router() -> Router<&'static str> {
+        Router::new()
+            .route(Method::GET, "/auth/token", "check")
+            .route(Method::PATCH, "/auth/token", "refresh")
+            .route(Method::DELETE, "/auth/api-keys/{id}", "delete api key")
+    }
+
+    #[test]
+    fn find() {
+        let router = router();
+
+        assert!(matches!(
+            router.find(&Method::PATCH, "/auth/token"),
+            Match::Found(&"refresh", _)
+        ));
+
+        match router.find(&Method::DELETE, "/auth/api-keys/abc") {
+            Match::Found(handler, params) => {
+                assert_eq!(*handler, "delete api key");
+                assert_eq!(params.get("id"), Some("abc"));
+            }
+            _ => panic!("not found"),
+        }
+
+        match router.find(&Method::POST, "/auth/token") {
+            Match::MethodNotAllowed(allow) => assert_eq!(allow, [Method::GET, Method::PATCH]),
+            _ => panic!("expected method not allowed"),
+        }
+
+        assert!(matches!(
+            router.find(&Method::GET, "/auth/api-keys"),
+            Match::NotFound
+        ));
+        assert!(matches!(
+            router.find(&Method::DELETE, "/auth/api-keys/abc/def"),
+            Match::NotFound
+        ));
+    }
+
+    #[test]
+    fn strip() {
+        assert_eq!(strip_prefix("/auth/token", ""), "/auth/token");
+        assert_eq!(strip_prefix("/v1/auth/token", ""), "/auth/token");
+        assert_eq!(strip_prefix("/api/v1/auth/token", "/api/"), "/auth/token");
+        assert_eq!(strip_prefix("/api/auth/token", "/api"), "/auth/token");
+        assert_eq!(
+            strip_prefix("/apiv1/auth/token", "/api"),
+            "/apiv1/auth/token"
+        );
+        assert_eq!(strip_prefix("/v10/auth/token", ""), "/v10/auth/token");
+        assert_eq!(strip_prefix("/healthz", "/api"), "/healthz");
+    }
+}
@@ -0,0 +1,49 @@
+use std::env;
+
+use opentelemetry::{
+    global,
+    sdk::{propagation::TraceContextPropagator, trace, Resource},
+    KeyValue,
+};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+/// log crate로 찍는 로그도 tracing으로 넘어가서 span의 request_id가 같이 찍힘
+///
+/// OTEL_EXPORTER_OTLP_ENDPOINT가 있으면 span을 OTLP collector로 보냄
+pub fn init(default_filter: &str) {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter));
+
+    let otel = env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .ok()
+        .map(|endpoint| {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", env!("CARGO_PKG_NAME")),
+                    KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+                ])))
+                .install_batch(opentelemetry::runtime::Tokio)
+                .expect("install otlp pipeline");
+
+            tracing_opentelemetry::layer().with_tracer(tracer)
+        });
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel)
+        .init();
+}
+
+/// 남은 span을 모두 보내고 종료함
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
@@ -0,0 +1,251 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader},
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use openssl::{pkey::PKey, x509::X509};
+use rustls_pemfile::Item;
+use tokio::task::JoinHandle;
+use tokio_rustls::{
+    rustls::{
+        server::{ClientHello, ResolvesServerCert},
+        sign::{self, CertifiedKey},
+        Certificate, PrivateKey, ServerConfig,
+    },
+    TlsAcceptor,
+};
+
+/// cert와 key 파일이 바뀌었는지 확인하는 주기
+const RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}: {1}")]
+    Io(String, io::Error),
+    #[error("{0}: certificate not found")]
+    NotFoundCertificate(String),
+    #[error("{0}: private key not found")]
+    NotFoundPrivateKey(String),
+    #[error("{0}: unsupported private key")]
+    UnsupportedPrivateKey(String),
+    #[error("{0}: private key does not match the certificate {1}")]
+    KeyMismatch(String, String),
+}
+
+fn read_pem(path: &str) -> Result<Vec<Item>, Error> {
+    let file = File::open(path).map_err(|err| Error::Io(path.to_string(), err))?;
+
+    rustls_pemfile::read_all(&mut BufReader::new(file))
+        .map_err(|err| Error::Io(path.to_string(), err))
+}
+
+fn load(cert_path: &str, key_path: &str) -> Result<CertifiedKey, Error> {
+    let certs = read_pem(cert_path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::X509Certificate(x) => Some(Certificate(x)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if certs.is_empty() {
+        return Err(Error::NotFoundCertificate(cert_path.to_string()));
+    }
+
+    let key = read_pem(key_path)?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::RSAKey(x) | Item::PKCS8Key(x) | Item::ECKey(x) => Some(PrivateKey(x)),
+            _ => None,
+        })
+        .ok_or_else(|| Error::NotFoundPrivateKey(key_path.to_string()))?;
+
+    // 잘못 짝지은 파일로 바꾸면 모든 handshake가 실패하므로 미리 확인함
+    if !key_matches(&certs[0], &key) {
+        return Err(Error::KeyMismatch(
+            key_path.to_string(),
+            cert_path.to_string(),
+        ));
+    }
+
+    let key = sign::any_supported_type(&key)
+        .map_err(|_| Error::UnsupportedPrivateKey(key_path.to_string()))?;
+
+    Ok(CertifiedKey::new(certs, key))
+}
+
+/// leaf certificate의 public key가 private key의 것과 같은지
+fn key_matches(cert: &Certificate, key: &PrivateKey) -> bool {
+    let public_key = X509::from_der(&cert.0).and_then(|x| x.public_key());
+    let private_key = PKey::private_key_from_der(&key.0);
+
+    match (public_key, private_key) {
+        (Ok(public_key), Ok(private_key)) => public_key.public_eq(&private_key),
+        _ => false,
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+/// 파일이 바뀌면 다음 handshake부터 새 인증서를 씀
+pub struct CertResolver {
+    cert_path: String,
+    key_path: String,
+    certified_key: RwLock<Arc<CertifiedKey>>,
+}
+
+impl CertResolver {
+    pub fn new(cert_path: &str, key_path: &str) -> Result<Self, Error> {
+        let certified_key = load(cert_path, key_path)?;
+
+        Ok(Self {
+            cert_path: cert_path.to_string(),
+            key_path: key_path.to_string(),
+            certified_key: RwLock::new(Arc::new(certified_key)),
+        })
+    }
+
+    fn modified(&self) -> (Option<SystemTime>, Option<SystemTime>) {
+        (modified(&self.cert_path), modified(&self.key_path))
+    }
+
+    /// 교체하다가 실패하면 기존 인증서를 계속 씀
+    fn reload(&self) {
+        match load(&self.cert_path, &self.key_path) {
+            Ok(certified_key) => {
+                *self.certified_key.write().unwrap() = Arc::new(certified_key);
+
+                log::info!("reloaded tls certificate: {}", self.cert_path);
+            }
+            Err(err) => log::error!("can't reload tls certificate: {}", err),
+        }
+    }
+
+    pub fn watch(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut prev = self.modified();
+
+            loop {
+                tokio::time::sleep(RELOAD_INTERVAL).await;
+
+                let modified = self.modified();
+
+                if modified != prev {
+                    self.reload();
+
+                    prev = modified;
+                }
+            }
+        })
+    }
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(Arc::clone(&self.certified_key.read().unwrap()))
+    }
+}
+
+pub fn acceptor(resolver: Arc<CertResolver>, http2: bool) -> TlsAcceptor {
+    let mut config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_cert_resolver(resolver);
+
+    config.alpn_protocols = if http2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+
+    TlsAcceptor::from(Arc::new(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use openssl::{
+        asn1::Asn1Time,
+        ec::{EcGroup, EcKey},
+        hash::MessageDigest,
+        nid::Nid,
+        pkey::{PKey, Private},
+        x509::{X509NameBuilder, X509},
+    };
+    use uuid::Uuid;
+
+    use super::{CertResolver, Error};
+
+    fn private_key() -> PKey<Private> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    fn self_signed(key: &PKey<Private>) -> X509 {
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, "localhost")
+            .unwrap();
+        let name = name.build();
+
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_subject_name(&name).unwrap();
+        cert.set_issuer_name(&name).unwrap();
+        cert.set_pubkey(key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        cert.sign(key, MessageDigest::sha256()).unwrap();
+
+        cert.build()
+    }
+
+    /// (cert_path, key_path)
+    fn write(cert: &X509, key: &PKey<Private>) -> (String, String) {
+        let dir = env::temp_dir().join(format!("madome-auth-tls-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let cert_path = dir.join("tls.crt");
+        let key_path = dir.join("tls.key");
+
+        fs::write(&cert_path, cert.to_pem().unwrap()).unwrap();
+        fs::write(&key_path, key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+
+        (
+            cert_path.to_str().unwrap().to_string(),
+            key_path.to_str().unwrap().to_string(),
+        )
+    }
+
+    #[test]
+    fn load_matching_key() {
+        let key = private_key();
+        let (cert_path, key_path) = write(&self_signed(&key), &key);
+
+        assert!(CertResolver::new(&cert_path, &key_path).is_ok());
+    }
+
+    #[test]
+    fn error_key_mismatch() {
+        let cert = self_signed(&private_key());
+        let (cert_path, key_path) = write(&cert, &private_key());
+
+        let r = CertResolver::new(&cert_path, &key_path);
+
+        assert!(matches!(r, Err(Error::KeyMismatch(path, _)) if path == key_path));
+    }
+
+    #[test]
+    fn error_not_found_file() {
+        let r = CertResolver::new("./.temp/nope.crt", "./.temp/nope.key");
+
+        assert!(matches!(r, Err(Error::Io(path, _)) if path == "./.temp/nope.crt"));
+    }
+}
@@ -1,5 +1,6 @@
 use std::{collections::HashMap, convert::TryFrom, sync::Arc};
 
+use chrono::Utc;
 use either::Either;
 use hyper::{Body, Request};
 use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
@@ -9,15 +10,74 @@ use uuid::Uuid;
 
 use crate::{
     command::CommandSet,
-    entity::{secret_key::SecretKey, token::AccessToken},
+    config::Config,
+    entity::{
+        api_key::ApiKey,
+        audit::{AuditEvent, AuditKind},
+        scope::{self, RoleScopes},
+        secret_key::SecretKey,
+        service_client::ServiceToken,
+        token::{amr, AccessToken, Token},
+    },
     error::UseCaseError,
-    repository::{r#trait::SecretKeyRepository, RepositorySet},
+    msg,
+    repository::{
+        r#trait::{ApiKeyRepository, SecretKeyRepository},
+        RepositorySet,
+    },
 };
 
 pub struct Payload {
     pub access_token: String,
     pub minimum_role: Option<u8>,
     pub validate_exp: bool,
+    /// true이면 mfa를 거친 token이어야 함
+    pub require_mfa: bool,
+    /// 마지막으로 인증한 지 max_age초가 지났으면 다시 인증해야 함
+    pub max_age: Option<i64>,
+    /// 모두 가지고 있어야 함
+    pub scopes: Vec<String>,
+    /// 유저의 role이 이 이상이면 요청과 상관없이 mfa를 요구함
+    pub mfa_required_role: Option<u8>,
+    /// None이면 RoleScopes::default()
+    pub role_scopes: Option<RoleScopes>,
+}
+
+impl Payload {
+    /// admin api에서 사용함
+    pub fn admin(access_token: String, config: &Config) -> Self {
+        Self {
+            access_token,
+            minimum_role: Some(config.admin_role()),
+            validate_exp: true,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![scope::ADMIN_USERS.to_string()],
+            mfa_required_role: None,
+            role_scopes: None,
+        }
+        .with_mfa_required_role(config.mfa_required_role())
+        .with_role_scopes(config.role_scopes())
+    }
+
+    pub fn is_service_token(&self) -> bool {
+        ServiceToken::deserialize_payload(&self.access_token).is_some()
+    }
+
+    /// 요청한 minimum_role이 아니라 token 주인의 role로 판단함
+    pub fn with_mfa_required_role(self, mfa_required_role: Option<u8>) -> Self {
+        Self {
+            mfa_required_role,
+            ..self
+        }
+    }
+
+    pub fn with_role_scopes(self, role_scopes: RoleScopes) -> Self {
+        Self {
+            role_scopes: Some(role_scopes),
+            ..self
+        }
+    }
 }
 
 impl TryFrom<Request<Body>> for Payload {
@@ -25,17 +85,42 @@ impl TryFrom<Request<Body>> for Payload {
 
     fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
         let mut cookie = Cookie::from(&request);
-        let qs = querystring::querify(request.uri().query().unwrap_or(""))
-            .into_iter()
-            .collect::<HashMap<_, _>>();
-
-        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+        let query = querystring::querify(request.uri().query().unwrap_or(""));
+
+        // ?scope=a&scope=b 처럼 여러번 올 수 있음
+        let scopes = query
+            .iter()
+            .filter(|(k, v)| *k == "scope" && !v.is_empty())
+            .map(|(_, v)| v.to_string())
+            .collect();
+
+        let qs = query.into_iter().collect::<HashMap<_, _>>();
+
+        // service token과 api key는 cookie 대신 Authorization header로 받음
+        let access_token = cookie
+            .take(MADOME_ACCESS_TOKEN)
+            .or_else(|| msg::bearer_token(&request))
+            .unwrap_or_default();
         let minimum_role = qs.get("role").and_then(|v| v.parse().ok());
+        let require_mfa = qs.get("mfa").map(|v| *v == "true").unwrap_or(false);
+        // 음수나 숫자가 아니면 검사를 건너뛰지 않고 요청을 거절함
+        let max_age = match qs.get("max_age") {
+            Some(v) => match v.parse::<i64>() {
+                Ok(max_age) if max_age >= 0 => Some(max_age),
+                _ => return Err(msg::Error::RequiredQuery("max_age").into()),
+            },
+            None => None,
+        };
 
         Ok(Self {
             access_token,
             minimum_role,
             validate_exp: true,
+            require_mfa,
+            max_age,
+            scopes,
+            mfa_required_role: None,
+            role_scopes: None,
         })
     }
 }
@@ -45,6 +130,10 @@ pub struct Model {
     #[serde(skip_serializing)]
     pub token_id: Uuid,
     pub user_id: Uuid,
+    #[serde(skip_serializing)]
+    pub amr: Vec<String>,
+    #[serde(skip_serializing)]
+    pub auth_time: i64,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -53,6 +142,13 @@ pub enum Error {
     UnauthorizedAccessToken,
     #[error("Permission denied")]
     PermissionDenied,
+    #[error("Required mfa")]
+    RequiredMfa,
+    #[error("Stale authentication")]
+    StaleAuthentication,
+    /// 빠진 scope를 공백으로 구분함
+    #[error("Insufficient scope: {0}")]
+    InsufficientScope(String),
 }
 
 impl From<Error> for crate::Error {
@@ -80,34 +176,157 @@ async fn deserialize(
     Ok(Some(token_data))
 }
 
+/// api key는 access token처럼 다룸, 인증한 시간은 api key를 만든 시간으로 봄
+async fn deserialize_api_key(
+    api_key: &str,
+    api_key_repository: Arc<impl ApiKeyRepository>,
+) -> crate::Result<Option<AccessToken>> {
+    let (id, secret) = ori!(ApiKey::split(api_key));
+
+    let api_key = ori!(api_key_repository.get(id).await?);
+
+    if api_key.expired() || !api_key.verify_secret(secret) {
+        return Ok(None);
+    }
+
+    let token = Token::new(api_key.user_id)
+        .with_amr(vec![amr::API_KEY.to_string()])
+        .with_auth_time(api_key.created_at)
+        .with_scopes(api_key.scopes);
+
+    Ok(Some(AccessToken::from(Token {
+        id: api_key.id,
+        ..token
+    })))
+}
+
+/// PermissionDenied audit를 남기고 돌려줄 에러로 바꿈
+async fn deny(
+    command: &CommandSet,
+    token_data: &AccessToken,
+    reason: impl Into<String>,
+    err: Error,
+) -> crate::Error {
+    command
+        .audit(
+            AuditEvent::new(AuditKind::PermissionDenied)
+                .user_id(token_data.user_id)
+                .token_id(token_data.id)
+                .detail(reason),
+        )
+        .await;
+
+    err.into()
+}
+
 pub async fn execute(
     Payload {
         access_token,
         minimum_role,
         validate_exp,
+        require_mfa,
+        max_age,
+        scopes,
+        mfa_required_role,
+        role_scopes,
     }: Payload,
     repository: Arc<RepositorySet>,
     command: Arc<CommandSet>,
 ) -> crate::Result<Model> {
-    let token_data = match deserialize(&access_token, validate_exp, repository.secret_key()).await?
-    {
+    let token_data = if ApiKey::is_api_key(&access_token) {
+        deserialize_api_key(&access_token, repository.api_key()).await?
+    } else {
+        deserialize(&access_token, validate_exp, repository.secret_key()).await?
+    };
+
+    let token_data = match token_data {
         Some(r) => r,
         None => return Err(Error::UnauthorizedAccessToken.into()),
     };
 
-    if let Some(minimum_role) = minimum_role {
-        let user = command
-            .get_user_info(Either::Left(token_data.user_id))
-            .await?;
+    let role_scopes = role_scopes.unwrap_or_default();
+
+    // scope가 지정된 api key, token은 role만 확인할 때도 계정 전체의 권한을 갖지 않음
+    // minimum_role이 가지는 scope를 모두 가지고 있어야 함
+    let scopes = match minimum_role {
+        Some(minimum_role) if scopes.is_empty() && !token_data.scopes.is_empty() => {
+            let scopes = role_scopes.get(minimum_role);
+
+            if scopes.is_empty() {
+                let reason = format!(
+                    "scoped credential on role check: minimum_role = {}",
+                    minimum_role
+                );
+
+                return Err(deny(&command, &token_data, reason, Error::PermissionDenied).await);
+            }
+
+            scopes
+        }
+        _ => scopes,
+    };
+
+    let user = if minimum_role.is_some() || !scopes.is_empty() || mfa_required_role.is_some() {
+        Some(
+            command
+                .get_user_info(Either::Left(token_data.user_id))
+                .await?,
+        )
+    } else {
+        None
+    };
 
+    if let (Some(minimum_role), Some(user)) = (minimum_role, &user) {
         if user.role < minimum_role {
-            return Err(Error::PermissionDenied.into());
+            let reason = format!("role = {} minimum_role = {}", user.role, minimum_role);
+
+            return Err(deny(&command, &token_data, reason, Error::PermissionDenied).await);
+        }
+    }
+
+    if let (false, Some(user)) = (scopes.is_empty(), &user) {
+        let granted = role_scopes.get(user.role);
+        let missing = scope::missing(&scopes, &granted, &token_data.scopes);
+
+        if !missing.is_empty() {
+            let missing = missing.join(" ");
+            let reason = format!("missing scopes = {}", missing);
+
+            return Err(deny(
+                &command,
+                &token_data,
+                reason,
+                Error::InsufficientScope(missing),
+            )
+            .await);
+        }
+    }
+
+    let require_mfa = require_mfa
+        || matches!(
+            (mfa_required_role, &user),
+            (Some(mfa_required_role), Some(user)) if user.role >= mfa_required_role
+        );
+
+    if require_mfa && !token_data.mfa() {
+        return Err(deny(&command, &token_data, "required mfa", Error::RequiredMfa).await);
+    }
+
+    if let Some(max_age) = max_age {
+        let age = Utc::now().timestamp() - token_data.auth_time;
+
+        if age > max_age {
+            let reason = format!("stale authentication: age = {} max_age = {}", age, max_age);
+
+            return Err(deny(&command, &token_data, reason, Error::StaleAuthentication).await);
         }
     }
 
     Ok(Model {
         token_id: token_data.id,
         user_id: token_data.user_id,
+        amr: token_data.amr,
+        auth_time: token_data.auth_time,
     })
 }
 
@@ -120,8 +339,17 @@ mod tests {
     use uuid::Uuid;
 
     use crate::command::{self, CommandSet};
-    use crate::entity::token::Token;
-    use crate::repository::{r#trait::SecretKeyRepository, RepositorySet};
+    use crate::config::DEFAULT_ADMIN_ROLE;
+    use crate::entity::{
+        api_key::ApiKey,
+        audit::AuditKind,
+        scope,
+        token::{amr, Token},
+    };
+    use crate::repository::{
+        r#trait::{ApiKeyRepository, SecretKeyRepository},
+        RepositorySet,
+    };
     use crate::usecase::check_access_token::{self, Payload};
 
     #[tokio::test]
@@ -151,6 +379,11 @@ mod tests {
                 access_token: serialized,
                 minimum_role: None,
                 validate_exp: true,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
             };
             let r = check_access_token::execute(payload, repository, command)
                 .await
@@ -199,6 +432,11 @@ mod tests {
                 access_token: serialized,
                 minimum_role: Some(0),
                 validate_exp: true,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
             };
             let r = check_access_token::execute(payload, repository, command)
                 .await
@@ -209,6 +447,139 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn error_insufficient_scope() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [secret_key: String, user_id: Uuid, token: Token] ->
+        {
+            secret_key = "secret1234".to_string();
+            user_id = Uuid::new_v4();
+            token = Token::new(user_id).with_scopes(vec![scope::LIBRARY_READ.to_string()]);
+
+            repository
+                .secret_key()
+                .add(token.id, &secret_key)
+                .await
+                .unwrap();
+
+            let get_user_info = command::tests::GetUser::from(User {
+                id: user_id,
+                email: "".to_string(),
+                role: 0,
+                name: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now()
+            });
+
+            command.set_get_user_info(get_user_info);
+        },
+        {
+            let (serialized, _) = token.serialize(&secret_key).expect("token serialize");
+
+            let payload = |scopes: &[&str]| Payload {
+                access_token: serialized.clone(),
+                minimum_role: None,
+                validate_exp: true,
+                require_mfa: false,
+                max_age: None,
+                scopes: scopes.iter().map(|x| x.to_string()).collect(),
+                mfa_required_role: None,
+                role_scopes: None,
+            };
+
+            check_access_token::execute(payload(&[scope::LIBRARY_READ]), repository.clone(), command.clone())
+                .await
+                .expect("expected ok, but returns error");
+
+            // role에는 있지만 token에는 없음
+            let r = check_access_token::execute(payload(&[scope::LIBRARY_WRITE]), repository.clone(), command.clone())
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::InsufficientScope(scope::LIBRARY_WRITE.to_string())));
+
+            // role에 없음
+            let r = check_access_token::execute(payload(&[scope::LIBRARY_READ, scope::ADMIN_USERS]), repository, command)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::InsufficientScope(scope::ADMIN_USERS.to_string())));
+        });
+    }
+
+    #[tokio::test]
+    async fn error_scoped_api_key_on_role_check() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [read_only: String, full: String] ->
+        {
+            let user_id = Uuid::new_v4();
+
+            let (api_key, key) = ApiKey::new(user_id, "reader", vec![scope::LIBRARY_READ.to_string()], None);
+            repository.api_key().add(&api_key).await.unwrap();
+            read_only = key;
+
+            let (api_key, key) = ApiKey::new(
+                user_id,
+                "writer",
+                vec![scope::LIBRARY_READ.to_string(), scope::LIBRARY_WRITE.to_string()],
+                None,
+            );
+            repository.api_key().add(&api_key).await.unwrap();
+            full = key;
+
+            let get_user_info = command::tests::GetUser::from(User {
+                id: user_id,
+                email: "".to_string(),
+                role: DEFAULT_ADMIN_ROLE,
+                name: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now()
+            });
+
+            command.set_get_user_info(get_user_info);
+        },
+        {
+            let payload = |access_token: &str, minimum_role: u8| Payload {
+                access_token: access_token.to_string(),
+                minimum_role: Some(minimum_role),
+                validate_exp: true,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+            };
+
+            // role 0은 library:read, library:write를 가짐
+            let r = check_access_token::execute(payload(&read_only, 0), repository.clone(), command.clone())
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::InsufficientScope(scope::LIBRARY_WRITE.to_string())));
+
+            check_access_token::execute(payload(&full, 0), repository.clone(), command.clone())
+                .await
+                .expect("expected ok, but returns error");
+
+            // 유저는 admin이지만 api key에 admin:users가 없음
+            let r = check_access_token::execute(payload(&full, DEFAULT_ADMIN_ROLE), repository, command)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::InsufficientScope(scope::ADMIN_USERS.to_string())));
+        });
+    }
+
     #[tokio::test]
     async fn error_permission_denied() {
         let mut test = System::<TestRegistry>::new();
@@ -247,6 +618,11 @@ mod tests {
                 access_token: serialized,
                 minimum_role: Some(1),
                 validate_exp: true,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
             };
             let r = check_access_token::execute(payload, repository, command)
                 .await
@@ -257,6 +633,210 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn error_required_mfa() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [secret_key: String, user_id: Uuid, token: Token] ->
+        {
+            secret_key = "secret1234".to_string();
+            user_id = Uuid::new_v4();
+            token = Token::new(user_id).with_amr(vec![amr::EMAIL.to_string()]);
+
+            repository
+                .secret_key()
+                .add(token.id, &secret_key)
+                .await
+                .unwrap();
+        },
+        {
+            let (serialized, _) = token.serialize(&secret_key).expect("token serialize");
+
+            let payload = Payload {
+                access_token: serialized,
+                minimum_role: None,
+                validate_exp: true,
+                require_mfa: true,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+            };
+            let r = check_access_token::execute(payload, repository, command)
+                .await
+                .expect_err("expected error, but returns ok");
+
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::RequiredMfa));
+        });
+    }
+
+    #[tokio::test]
+    async fn error_required_mfa_by_user_role() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [secret_key: String, user_id: Uuid, token: Token] ->
+        {
+            secret_key = "secret1234".to_string();
+            user_id = Uuid::new_v4();
+            token = Token::new(user_id).with_amr(vec![amr::EMAIL.to_string()]);
+
+            repository
+                .secret_key()
+                .add(token.id, &secret_key)
+                .await
+                .unwrap();
+
+            let get_user_info = command::tests::GetUser::from(User {
+                id: user_id,
+                email: "".to_string(),
+                role: 2,
+                name: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now()
+            });
+
+            command.set_get_user_info(get_user_info);
+        },
+        {
+            let (serialized, _) = token.serialize(&secret_key).expect("token serialize");
+
+            // 낮은 role만 요구해도 token 주인의 role이 높으면 mfa가 필요함
+            let payload = Payload {
+                access_token: serialized,
+                minimum_role: Some(0),
+                validate_exp: true,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+            }
+            .with_mfa_required_role(Some(2));
+            let r = check_access_token::execute(payload, repository, command.clone())
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::RequiredMfa));
+
+            let events = command.audit_events();
+
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].kind, AuditKind::PermissionDenied);
+            assert_eq!(events[0].detail.as_deref(), Some("required mfa"));
+        });
+    }
+
+    #[tokio::test]
+    async fn success_with_mfa() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [secret_key: String, user_id: Uuid, token: Token] ->
+        {
+            secret_key = "secret1234".to_string();
+            user_id = Uuid::new_v4();
+            token = Token::new(user_id).with_amr(vec![
+                amr::EMAIL.to_string(),
+                amr::OTP.to_string(),
+                amr::MFA.to_string(),
+            ]);
+
+            repository
+                .secret_key()
+                .add(token.id, &secret_key)
+                .await
+                .unwrap();
+        },
+        {
+            let (serialized, _) = token.serialize(&secret_key).expect("token serialize");
+
+            let payload = Payload {
+                access_token: serialized,
+                minimum_role: None,
+                validate_exp: true,
+                require_mfa: true,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+            };
+            let r = check_access_token::execute(payload, repository, command)
+                .await
+                .unwrap();
+
+            assert_eq!(r.token_id, token.id);
+            assert_eq!(r.user_id, user_id);
+        });
+    }
+
+    #[tokio::test]
+    async fn error_stale_authentication() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [secret_key: String, user_id: Uuid, token: Token] ->
+        {
+            secret_key = "secret1234".to_string();
+            user_id = Uuid::new_v4();
+            token = Token::new(user_id).with_auth_time(Utc::now().timestamp() - 600);
+
+            repository
+                .secret_key()
+                .add(token.id, &secret_key)
+                .await
+                .unwrap();
+        },
+        {
+            let (serialized, _) = token.serialize(&secret_key).expect("token serialize");
+
+            let payload = Payload {
+                access_token: serialized.clone(),
+                minimum_role: None,
+                validate_exp: true,
+                require_mfa: false,
+                max_age: Some(900),
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+            };
+            check_access_token::execute(payload, repository.clone(), command.clone())
+                .await
+                .expect("expected ok, but returns error");
+
+            let payload = Payload {
+                access_token: serialized,
+                minimum_role: None,
+                validate_exp: true,
+                require_mfa: false,
+                max_age: Some(300),
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+            };
+            let r = check_access_token::execute(payload, repository, command)
+                .await
+                .expect_err("expected error, but returns ok");
+
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::StaleAuthentication));
+        });
+    }
+
     #[tokio::test]
     async fn error_unauthorized_by_use_refresh_token_instead_of_access_token() {
         let mut test = System::<TestRegistry>::new();
@@ -297,6 +877,11 @@ mod tests {
                 access_token: refresh_token,
                 minimum_role: None,
                 validate_exp: true,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
             };
             let r = check_access_token::execute(payload, repository, command)
                 .await
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, convert::TryFrom, sync::Arc};
+use std::{convert::TryFrom, sync::Arc};
 
 use hyper::{Body, Request};
 use madome_sdk::api::cookie::{MADOME_ACCESS_TOKEN, MADOME_REFRESH_TOKEN};
@@ -7,15 +7,59 @@ use util::http::Cookie;
 use uuid::Uuid;
 
 use crate::{
-    command::CommandSet, error::UseCaseError, model::TokenPair, repository::RepositorySet,
+    command::CommandSet,
+    entity::{scope::RoleScopes, session::Client},
+    error::UseCaseError,
+    model::TokenPair,
+    msg,
+    repository::RepositorySet,
 };
 
 use super::{check_access_token, refresh_token_pair};
 
+/// POST /auth/token/check?role=&mfa=&max_age=&scope=
+///
+/// GET /auth/token과 같은 검사를 하고, 만료된 access token이면 갱신한 뒤에 검사함
 pub struct Payload {
     pub access_token: String,
     pub refresh_token: String,
     pub minimum_role: Option<u8>,
+    /// check_access_token::Payload::require_mfa
+    pub require_mfa: bool,
+    /// check_access_token::Payload::max_age
+    pub max_age: Option<i64>,
+    /// check_access_token::Payload::scopes
+    pub scopes: Vec<String>,
+    /// check_access_token::Payload::mfa_required_role
+    pub mfa_required_role: Option<u8>,
+    /// check_access_token::Payload::role_scopes
+    pub role_scopes: Option<RoleScopes>,
+    pub client: Client,
+    /// refresh_token_pair::Payload::warn_suspicious
+    pub warn_suspicious: bool,
+}
+
+impl Payload {
+    pub fn with_warn_suspicious(self, warn_suspicious: bool) -> Self {
+        Self {
+            warn_suspicious,
+            ..self
+        }
+    }
+
+    pub fn with_mfa_required_role(self, mfa_required_role: Option<u8>) -> Self {
+        Self {
+            mfa_required_role,
+            ..self
+        }
+    }
+
+    pub fn with_role_scopes(self, role_scopes: RoleScopes) -> Self {
+        Self {
+            role_scopes: Some(role_scopes),
+            ..self
+        }
+    }
 }
 
 impl TryFrom<Request<Body>> for Payload {
@@ -23,18 +67,32 @@ impl TryFrom<Request<Body>> for Payload {
 
     fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
         let mut cookie = Cookie::from(&request);
-        let qs = querystring::querify(request.uri().query().unwrap_or(""))
-            .into_iter()
-            .collect::<HashMap<_, _>>();
 
+        // 갱신하려면 cookie가 필요하므로 Authorization header는 받지 않음
         let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
         let refresh_token = cookie.take(MADOME_REFRESH_TOKEN).unwrap_or_default();
-        let minimum_role = qs.get("role").and_then(|v| v.parse().ok());
+        let client = msg::client(&request);
+
+        // query는 GET /auth/token과 같음
+        let check_access_token::Payload {
+            minimum_role,
+            require_mfa,
+            max_age,
+            scopes,
+            ..
+        } = check_access_token::Payload::try_from(request)?;
 
         Ok(Self {
             access_token,
             refresh_token,
             minimum_role,
+            require_mfa,
+            max_age,
+            scopes,
+            mfa_required_role: None,
+            role_scopes: None,
+            client,
+            warn_suspicious: false,
         })
     }
 }
@@ -52,8 +110,9 @@ pub struct Model {
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("Permission denied")]
-    PermissionDenied(TokenPair),
+    /// 갱신한 뒤에 거절되면 이전 token pair는 이미 쓸 수 없으므로 갱신한 token pair를 함께 돌려줌
+    #[error("{0}")]
+    Denied(check_access_token::Error, TokenPair),
 }
 
 impl From<Error> for crate::Error {
@@ -77,16 +136,18 @@ fn is_not_unauthorized(err: crate::Error) -> Option<crate::Error> {
 }
 
 /// # Return
-/// - PermissionDenied -> None
-/// - Other -> Some(err)
-fn is_not_permission_denied(err: crate::Error) -> Option<crate::Error> {
+/// - PermissionDenied, RequiredMfa, StaleAuthentication, InsufficientScope -> Ok(err)
+/// - Other -> Err(err)
+fn into_denied(err: crate::Error) -> Result<check_access_token::Error, crate::Error> {
     use crate::error::UseCaseError::*;
     use crate::usecase::check_access_token::Error::*;
     use crate::Error::*;
 
     match err {
-        UseCase(CheckAccessToken(PermissionDenied)) => None,
-        err => Some(err),
+        UseCase(CheckAccessToken(
+            err @ (PermissionDenied | RequiredMfa | StaleAuthentication | InsufficientScope(_)),
+        )) => Ok(err),
+        err => Err(err),
     }
 }
 
@@ -95,23 +156,36 @@ pub async fn execute(
         access_token,
         refresh_token,
         minimum_role,
+        require_mfa,
+        max_age,
+        scopes,
+        mfa_required_role,
+        role_scopes,
+        client,
+        warn_suspicious,
     }: Payload,
     repository: Arc<RepositorySet>,
     command: Arc<CommandSet>,
 ) -> crate::Result<Model> {
+    let check = |access_token: String| check_access_token::Payload {
+        access_token,
+        minimum_role,
+        validate_exp: true,
+        require_mfa,
+        max_age,
+        scopes: scopes.clone(),
+        mfa_required_role,
+        role_scopes: role_scopes.clone(),
+    };
+
     let r_check_access_token = check_access_token::execute(
-        check_access_token::Payload {
-            access_token: access_token.clone(),
-            minimum_role,
-            validate_exp: true,
-        },
+        check(access_token.clone()),
         repository.clone(),
         command.clone(),
     )
     .await
     .map_err(is_not_unauthorized);
 
-    // match Err::<check_access_token::Model, _>(None) {
     match r_check_access_token {
         // PermissionDenied 또는 기타 에러
         Err(Some(err)) => Err(err),
@@ -131,43 +205,34 @@ pub async fn execute(
                 refresh_token_pair::Payload {
                     access_token,
                     refresh_token,
+                    client,
+                    warn_suspicious,
                 },
                 repository.clone(),
                 command.clone(),
             )
             .await?;
 
-            let r = if let Some(minimum_role) = minimum_role {
-                let r = check_access_token::execute(
-                    check_access_token::Payload {
-                        access_token: t.access_token.clone(),
-                        minimum_role: Some(minimum_role),
-                        validate_exp: true,
-                    },
-                    repository,
-                    command,
-                )
+            let r = check_access_token::execute(check(t.access_token.clone()), repository, command)
                 .await
-                .map_err(is_not_permission_denied);
-
-                Some(r)
-            } else {
-                None
-            };
+                .map_err(into_denied);
 
             match r {
-                None | Some(Ok(_)) => Ok(Model {
+                Ok(_) => Ok(Model {
                     access_token: Some(t.access_token),
                     refresh_token: Some(t.refresh_token),
                     token_id: t.token_id,
                     user_id: t.user_id,
                 }),
-                Some(Err(None)) => Err(Error::PermissionDenied(TokenPair {
-                    access_token: t.access_token,
-                    refresh_token: t.refresh_token,
-                })
+                Err(Ok(err)) => Err(Error::Denied(
+                    err,
+                    TokenPair {
+                        access_token: t.access_token,
+                        refresh_token: t.refresh_token,
+                    },
+                )
                 .into()),
-                Some(Err(Some(err))) => Err(err),
+                Err(Err(err)) => Err(err),
             }
         }
     }
@@ -182,6 +247,7 @@ mod tests {
     use uuid::Uuid;
 
     use crate::command::{self, CommandSet};
+    use crate::entity::session::Client;
     use crate::entity::token::{
         self, AccessToken, RefreshToken, Token, ACCESS_TOKEN_EXP, REFRESH_TOKEN_EXP,
     };
@@ -217,6 +283,13 @@ mod tests {
                 access_token,
                 refresh_token,
                 minimum_role: None,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+                client: Client::default(),
+                warn_suspicious: false,
             };
             let r = check_and_refresh_token_pair::execute(payload, repository, command)
                 .await
@@ -253,6 +326,9 @@ mod tests {
                 id: token.id,
                 user_id,
                 _a: true,
+                amr: vec![],
+                auth_time: now,
+                scopes: vec![],
             };
             refresh_token = RefreshToken {
                 sub: "madome refresh token".to_string(),
@@ -281,6 +357,13 @@ mod tests {
                 access_token,
                 refresh_token,
                 minimum_role: None,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+                client: Client::default(),
+                warn_suspicious: false,
             };
             let r = check_and_refresh_token_pair::execute(payload, repository.clone(), command)
                 .await
@@ -341,6 +424,13 @@ mod tests {
                 access_token,
                 refresh_token,
                 minimum_role: Some(1),
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+                client: Client::default(),
+                warn_suspicious: false,
             };
             let r = check_and_refresh_token_pair::execute(payload, repository, command)
                 .await
@@ -376,6 +466,9 @@ mod tests {
                 id: token.id,
                 user_id,
                 _a: true,
+                amr: vec![],
+                auth_time: now,
+                scopes: vec![],
             };
             refresh_token = RefreshToken {
                 sub: "madome refresh token".to_string(),
@@ -415,6 +508,13 @@ mod tests {
                 access_token,
                 refresh_token,
                 minimum_role: Some(1),
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+                client: Client::default(),
+                warn_suspicious: false,
             };
             let r = check_and_refresh_token_pair::execute(payload, repository.clone(), command)
                 .await
@@ -426,7 +526,7 @@ mod tests {
                 use check_and_refresh_token_pair::Error::*;
 
                 match r {
-                    UseCase(CheckAndRefreshTokenPair(PermissionDenied(TokenPair { access_token, refresh_token }))) => {
+                    UseCase(CheckAndRefreshTokenPair(Denied(check_access_token::Error::PermissionDenied, TokenPair { access_token, refresh_token }))) => {
                         let p = AccessToken::deserialize_payload(&access_token).expect("deserialize payload from access token");
 
                         let secret_key = repository.secret_key().get(p.id).await.unwrap().unwrap();
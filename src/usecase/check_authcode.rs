@@ -1,21 +1,60 @@
 use std::sync::Arc;
 
+use either::Either;
+use hyper::{Body, Request};
 use serde::Deserialize;
+use util::{r#async::AsyncTryFrom, FromOwnedRequest};
+use uuid::Uuid;
 
 use crate::{
+    command::CommandSet,
+    config::Config,
+    entity::{
+        audit::{AuditEvent, AuditKind},
+        session::Client,
+    },
     error::UseCaseError,
+    metrics,
+    msg::{self, Wrap},
     repository::{r#trait::AuthcodeRepository, RepositorySet},
 };
 
+use super::check_totp;
+
 #[derive(Deserialize, Clone)]
 pub struct Payload {
     pub code: String,
     #[serde(rename = "email")]
     pub user_email: String,
+    /// TOTP를 등록한 유저만 필요함, recovery code도 가능
+    #[serde(default, rename = "totp")]
+    pub totp_code: Option<String>,
+    #[serde(skip)]
+    pub client: Client,
+}
+
+#[async_trait::async_trait]
+impl FromOwnedRequest for Payload {
+    type Error = crate::Error;
+    type Parameter = ();
+
+    async fn from_owned_request(
+        _parameter: Self::Parameter,
+        request: Request<Body>,
+    ) -> Result<Self, Self::Error> {
+        let client = msg::client(&request);
+
+        let payload: Self = Wrap::async_try_from(request).await?.inner();
+
+        Ok(Self { client, ..payload })
+    }
 }
 
+/// authcode와 TOTP를 모두 통과함
+#[derive(Debug)]
 pub struct Model {
-    pub user_email: String,
+    pub user_id: Uuid,
+    pub amr: Vec<String>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -31,20 +70,308 @@ impl From<Error> for crate::Error {
 }
 
 pub async fn execute(
-    Payload { code, user_email }: Payload,
+    Payload {
+        code,
+        user_email,
+        totp_code,
+        ..
+    }: Payload,
     repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
 ) -> crate::Result<Model> {
-    let maybe_authcode = repository.authcode().pop(&user_email, &code).await?;
+    let authcode_repository = repository.authcode();
 
-    match maybe_authcode {
-        Some(authcode) => Ok(Model {
-            user_email: authcode.user_email,
-        }),
-        _ => Err(Error::InvalidAuthcode.into()),
+    if authcode_repository.get(&user_email, &code).await?.is_none() {
+        return Err(failed(&user_email, None, &command).await);
     }
+
+    // TOTP가 틀렸을 때 authcode를 다시 받지 않아도 되게 TOTP까지 통과한 뒤에 authcode를 사용함
+    let check_totp::Model { user_id, amr } = check_totp::execute(
+        check_totp::Payload {
+            user_email: user_email.clone(),
+            code: totp_code,
+        },
+        repository.clone(),
+        command.clone(),
+        config,
+    )
+    .await?;
+
+    // 동시에 같은 authcode로 요청했으면 하나만 통과함
+    if authcode_repository.pop(&user_email, &code).await?.is_none() {
+        return Err(failed(&user_email, Some(user_id), &command).await);
+    }
+
+    command
+        .audit(
+            AuditEvent::new(AuditKind::AuthcodeVerified)
+                .user_id(user_id)
+                .user_email(&user_email),
+        )
+        .await;
+
+    Ok(Model { user_id, amr })
+}
+
+/// user_id를 모르면 email로 찾아봄, 없는 유저여도 event는 남김
+async fn failed(user_email: &str, user_id: Option<Uuid>, command: &CommandSet) -> crate::Error {
+    metrics::AUTHCODES_FAILED.inc();
+
+    let user_id = match user_id {
+        Some(user_id) => Some(user_id),
+        None => command
+            .get_user_info(Either::Right(user_email.to_string()))
+            .await
+            .ok()
+            .map(|user| user.id),
+    };
+
+    let event = AuditEvent::new(AuditKind::AuthcodeFailed).user_email(user_email);
+    let event = match user_id {
+        Some(user_id) => event.user_id(user_id),
+        None => event,
+    };
+
+    command.audit(event).await;
+
+    Error::InvalidAuthcode.into()
 }
 
 #[cfg(test)]
 mod tests {
-    // TODO: success, error_invalid_auth_code(시간 초과된 걸로 테스트 해야함, 시간을 설정할 수 있게 가능한가?)
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use madome_sdk::api::user::model::User;
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::command::{self, CommandSet};
+    use crate::config::Config;
+    use crate::entity::{
+        audit::AuditKind,
+        authcode::Authcode,
+        session::Client,
+        token::amr,
+        totp::{Totp, MAX_FAILURES},
+    };
+    use crate::repository::{
+        r#trait::{AuthcodeRepository, TotpRepository},
+        RepositorySet,
+    };
+    use crate::usecase::{
+        check_authcode::{self, Payload},
+        check_totp,
+    };
+
+    const EMAIL: &str = "authcode@madome.app";
+
+    fn payload(code: &str, totp_code: Option<&str>) -> Payload {
+        Payload {
+            code: code.to_string(),
+            user_email: EMAIL.to_string(),
+            totp_code: totp_code.map(|x| x.to_string()),
+            client: Client::default(),
+        }
+    }
+
+    fn user(user_id: Uuid) -> command::tests::GetUser {
+        command::tests::GetUser::from(User {
+            id: user_id,
+            email: EMAIL.to_string(),
+            role: 0,
+            name: "".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        })
+    }
+
+    #[tokio::test]
+    async fn success() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(user(user_id));
+
+            repository
+                .authcode()
+                .add(Authcode::new(EMAIL.to_string(), "123456".to_string()))
+                .await
+                .unwrap();
+        },
+        {
+            let r = check_authcode::execute(payload("123456", None), repository.clone(), command.clone(), Arc::new(Config::default()))
+                .await
+                .unwrap();
+
+            assert_eq!(r.user_id, user_id);
+            assert_eq!(r.amr, vec![amr::EMAIL.to_string()]);
+
+            // 한번만 사용할 수 있음
+            let r = check_authcode::execute(payload("123456", None), repository, command.clone(), Arc::new(Config::default()))
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_authcode::Error::InvalidAuthcode));
+
+            let events = command.audit_events();
+
+            assert_eq!(events.len(), 2);
+            assert_eq!(events[0].kind, AuditKind::AuthcodeVerified);
+            assert_eq!(events[0].user_id, Some(user_id));
+            assert_eq!(events[1].kind, AuditKind::AuthcodeFailed);
+            assert_eq!(events[1].user_id, Some(user_id));
+        });
+    }
+
+    #[tokio::test]
+    async fn success_with_totp() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid, recovery_codes: Vec<String>] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(user(user_id));
+
+            let (mut totp, codes) = Totp::new();
+            totp.enabled = true;
+            recovery_codes = codes;
+
+            repository.totp().add(user_id, &totp).await.unwrap();
+
+            repository
+                .authcode()
+                .add(Authcode::new(EMAIL.to_string(), "123456".to_string()))
+                .await
+                .unwrap();
+        },
+        {
+            let r = check_authcode::execute(payload("123456", None), repository.clone(), command.clone(), Arc::new(Config::default()))
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_totp::Error::RequiredTotpCode));
+
+            let r = check_authcode::execute(payload("123456", Some("000000")), repository.clone(), command.clone(), Arc::new(Config::default()))
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_totp::Error::InvalidTotpCode));
+
+            // TOTP가 틀렸을 때는 authcode를 사용하지 않음
+            let r = check_authcode::execute(
+                payload("123456", Some(&recovery_codes[0])),
+                repository.clone(),
+                command,
+                Arc::new(Config::default()),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(r.user_id, user_id);
+            assert!(r.amr.contains(&amr::MFA.to_string()));
+
+            assert_eq!(repository.totp().failures(user_id).await.unwrap(), 0);
+        });
+    }
+
+    #[tokio::test]
+    async fn error_invalid_authcode_before_totp() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(user(user_id));
+
+            let (mut totp, _) = Totp::new();
+            totp.enabled = true;
+
+            repository.totp().add(user_id, &totp).await.unwrap();
+        },
+        {
+            let r = check_authcode::execute(payload("123456", Some("000000")), repository.clone(), command.clone(), Arc::new(Config::default()))
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_authcode::Error::InvalidAuthcode));
+
+            // authcode 없이는 TOTP를 맞혀볼 수 없음
+            assert_eq!(repository.totp().failures(user_id).await.unwrap(), 0);
+
+            let events = command.audit_events();
+
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].kind, AuditKind::AuthcodeFailed);
+            assert_eq!(events[0].user_id, Some(user_id));
+        });
+    }
+
+    #[tokio::test]
+    async fn error_too_many_totp_attempts() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid, recovery_codes: Vec<String>] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(user(user_id));
+
+            let (mut totp, codes) = Totp::new();
+            totp.enabled = true;
+            recovery_codes = codes;
+
+            repository.totp().add(user_id, &totp).await.unwrap();
+
+            repository
+                .authcode()
+                .add(Authcode::new(EMAIL.to_string(), "123456".to_string()))
+                .await
+                .unwrap();
+        },
+        {
+            for _ in 0..MAX_FAILURES {
+                let r = check_authcode::execute(payload("123456", Some("000000")), repository.clone(), command.clone(), Arc::new(Config::default()))
+                    .await
+                    .expect_err("expected error, but returns ok");
+
+                assert_debug!(r, crate::Error::from(check_totp::Error::InvalidTotpCode));
+            }
+
+            // 막힌 동안에는 맞는 code도 받지 않음
+            let r = check_authcode::execute(
+                payload("123456", Some(&recovery_codes[0])),
+                repository,
+                command,
+                Arc::new(Config::default()),
+            )
+            .await
+            .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_totp::Error::TooManyTotpAttempts));
+        });
+    }
 }
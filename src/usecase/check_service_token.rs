@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    entity::{scope, service_client::ServiceToken},
+    repository::{r#trait::ServiceClientRepository, RepositorySet},
+};
+
+use super::check_access_token::{self, Error};
+
+/// GET /auth/token에 service token이 오면 check_access_token 대신 실행함
+pub struct Payload {
+    pub service_token: String,
+    pub minimum_role: Option<u8>,
+    pub require_mfa: bool,
+    pub max_age: Option<i64>,
+    pub scopes: Vec<String>,
+}
+
+impl From<check_access_token::Payload> for Payload {
+    fn from(payload: check_access_token::Payload) -> Self {
+        Self {
+            service_token: payload.access_token,
+            minimum_role: payload.minimum_role,
+            require_mfa: payload.require_mfa,
+            max_age: payload.max_age,
+            scopes: payload.scopes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model {
+    #[serde(skip_serializing)]
+    pub token_id: Uuid,
+    pub client_id: String,
+    pub scopes: Vec<String>,
+}
+
+pub async fn execute(
+    Payload {
+        service_token,
+        minimum_role,
+        require_mfa,
+        max_age,
+        scopes: required_scopes,
+    }: Payload,
+    repository: Arc<RepositorySet>,
+) -> crate::Result<Model> {
+    let client_id = match ServiceToken::deserialize_payload(&service_token) {
+        Some(payload) => payload.client_id,
+        None => return Err(Error::UnauthorizedAccessToken.into()),
+    };
+
+    // client를 지우면 이미 발급된 token도 바로 무효가 됨
+    let client = match repository.service_client().get(&client_id).await? {
+        Some(client) => client,
+        None => return Err(Error::UnauthorizedAccessToken.into()),
+    };
+
+    let token = match ServiceToken::deserialize(&service_token, &client.signing_key) {
+        Some(token_data) => token_data.claims,
+        None => return Err(Error::UnauthorizedAccessToken.into()),
+    };
+
+    // service에는 role이 없고 사람이 아니니 mfa도 할 수 없음
+    if minimum_role.is_some() || require_mfa {
+        return Err(Error::PermissionDenied.into());
+    }
+
+    if let Some(max_age) = max_age {
+        if Utc::now().timestamp() - token.iat > max_age {
+            return Err(Error::StaleAuthentication.into());
+        }
+    }
+
+    // 발급한 뒤에 client의 scope가 줄었을 수 있음
+    let scopes = token
+        .scopes
+        .into_iter()
+        .filter(|x| client.scopes.contains(x))
+        .collect::<Vec<_>>();
+
+    // scope가 비어 있는 token은 아무 scope도 갖지 않으므로 token_scopes로 넘기지 않음
+    let missing = scope::missing(&required_scopes, &scopes, &[]);
+
+    if !missing.is_empty() {
+        return Err(Error::InsufficientScope(missing.join(" ")).into());
+    }
+
+    Ok(Model {
+        token_id: token.id,
+        client_id: client.id,
+        scopes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+
+    use crate::command::CommandSet;
+    use crate::entity::service_client::{ServiceClient, ServiceToken};
+    use crate::repository::{r#trait::ServiceClientRepository, RepositorySet};
+    use crate::usecase::{check_access_token, check_service_token::Payload, create_service_token};
+
+    fn payload(service_token: &str) -> Payload {
+        Payload {
+            service_token: service_token.to_string(),
+            minimum_role: None,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![],
+        }
+    }
+
+    async fn service_token(
+        client: &ServiceClient,
+        secret: String,
+        repository: std::sync::Arc<RepositorySet>,
+        command: std::sync::Arc<CommandSet>,
+    ) -> String {
+        let payload = create_service_token::Payload {
+            grant_type: create_service_token::CLIENT_CREDENTIALS.to_string(),
+            client_id: client.id.clone(),
+            client_secret: secret,
+            scope: None,
+        };
+
+        create_service_token::execute(payload, repository, command)
+            .await
+            .unwrap()
+            .access_token
+    }
+
+    #[tokio::test]
+    async fn success() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [client: ServiceClient, token: String] ->
+        {
+            let (c, secret) = ServiceClient::new("madome-library", vec!["library:read".to_string(), "users:read".to_string()]);
+            client = c;
+
+            repository.service_client().add(&client).await.unwrap();
+
+            token = service_token(&client, secret, repository.clone(), command.clone()).await;
+        },
+        {
+            let r = super::execute(
+                Payload {
+                    scopes: vec!["library:read".to_string()],
+                    ..payload(&token)
+                },
+                repository.clone(),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(r.client_id, client.id);
+            assert_eq!(r.scopes, vec!["library:read".to_string(), "users:read".to_string()]);
+
+            // 발급한 뒤에 client의 scope가 줄어듦
+            let reduced = ServiceClient {
+                scopes: vec!["library:read".to_string()],
+                ..client.clone()
+            };
+            repository.service_client().remove(&client.id).await.unwrap();
+            repository.service_client().add(&reduced).await.unwrap();
+
+            let r = super::execute(payload(&token), repository.clone()).await.unwrap();
+
+            assert_eq!(r.scopes, vec!["library:read".to_string()]);
+
+            // scope 없이 발급된 token은 client의 scope를 이어받지 않음
+            let unscoped = ServiceToken::new(&client.id, vec![]).serialize(&client.signing_key);
+
+            let r = super::execute(
+                Payload {
+                    scopes: vec!["library:read".to_string()],
+                    ..payload(&unscoped)
+                },
+                repository.clone(),
+            )
+            .await
+            .expect_err("expected error, but returns ok");
+
+            let expected: crate::Error =
+                check_access_token::Error::InsufficientScope("library:read".to_string()).into();
+
+            assert_debug!(r, expected);
+
+            let r = super::execute(
+                Payload {
+                    scopes: vec!["users:read".to_string()],
+                    ..payload(&token)
+                },
+                repository,
+            )
+            .await
+            .expect_err("expected error, but returns ok");
+
+            let expected: crate::Error =
+                check_access_token::Error::InsufficientScope("users:read".to_string()).into();
+
+            assert_debug!(r, expected);
+        });
+    }
+
+    #[tokio::test]
+    async fn error_permission_denied() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [token: String] ->
+        {
+            let (client, secret) = ServiceClient::new("madome-library", vec![]);
+
+            repository.service_client().add(&client).await.unwrap();
+
+            token = service_token(&client, secret, repository.clone(), command.clone()).await;
+        },
+        {
+            // service에는 role이 없음
+            let r = super::execute(
+                Payload {
+                    minimum_role: Some(0),
+                    ..payload(&token)
+                },
+                repository.clone(),
+            )
+            .await
+            .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::PermissionDenied));
+
+            let r = super::execute(
+                Payload {
+                    require_mfa: true,
+                    ..payload(&token)
+                },
+                repository,
+            )
+            .await
+            .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::PermissionDenied));
+        });
+    }
+
+    #[tokio::test]
+    async fn error_unauthorized() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [client: ServiceClient, token: String] ->
+        {
+            let (c, secret) = ServiceClient::new("madome-library", vec![]);
+            client = c;
+
+            repository.service_client().add(&client).await.unwrap();
+
+            token = service_token(&client, secret, repository.clone(), command.clone()).await;
+        },
+        {
+            // 다른 key로 서명함
+            let forged = ServiceToken::new(&client.id, vec![]).serialize("forged signing key");
+
+            let r = super::execute(payload(&forged), repository.clone())
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::UnauthorizedAccessToken));
+
+            // client를 지우면 이미 발급된 token도 무효
+            repository.service_client().remove(&client.id).await.unwrap();
+
+            let r = super::execute(payload(&token), repository)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::UnauthorizedAccessToken));
+        });
+    }
+}
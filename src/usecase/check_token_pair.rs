@@ -45,6 +45,8 @@ impl From<(String, String)> for Payload {
 pub struct Model {
     pub user_id: Uuid,
     pub token_id: Uuid,
+    pub amr: Vec<String>,
+    pub auth_time: i64,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -72,6 +74,11 @@ pub async fn execute(
             access_token,
             minimum_role: None,
             validate_exp: false,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![],
+            mfa_required_role: None,
+            role_scopes: None,
         },
         repository.clone(),
         command,
@@ -91,6 +98,8 @@ pub async fn execute(
     Ok(Model {
         user_id: access_token.user_id,
         token_id: access_token.token_id,
+        amr: access_token.amr,
+        auth_time: access_token.auth_time,
     })
 }
 
@@ -0,0 +1,312 @@
+use std::sync::Arc;
+
+use either::Either;
+use uuid::Uuid;
+
+use crate::{
+    command::CommandSet,
+    config::Config,
+    entity::token::amr,
+    error::UseCaseError,
+    repository::{r#trait::TotpRepository, RepositorySet},
+};
+
+pub struct Payload {
+    pub user_email: String,
+    pub code: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Model {
+    pub user_id: Uuid,
+    pub amr: Vec<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Required totp code")]
+    RequiredTotpCode,
+
+    #[error("Invalid totp code")]
+    InvalidTotpCode,
+
+    #[error("Too many totp attempts")]
+    TooManyTotpAttempts,
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload { user_email, code }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    let user = command.get_user_info(Either::Right(user_email)).await?;
+
+    let totp_repository = repository.totp();
+
+    // TOTP를 등록하지 않은 유저는 authcode만으로 통과
+    let mut totp = match totp_repository.get(user.id).await? {
+        Some(totp) if totp.enabled => totp,
+        _ => {
+            return Ok(Model {
+                user_id: user.id,
+                amr: vec![amr::EMAIL.to_string()],
+            })
+        }
+    };
+
+    let code = match code {
+        Some(code) => code,
+        None => return Err(Error::RequiredTotpCode.into()),
+    };
+
+    // 6자리 code를 맞힐 때까지 시도하지 못하게 막음, 막혔을 때는 맞는 code도 받지 않음
+    if totp_repository.failures(user.id).await? >= config.totp_max_failures() {
+        return Err(Error::TooManyTotpAttempts.into());
+    }
+
+    if !totp.verify(&code) && !totp.take_recovery_code(&code) {
+        totp_repository.add_failure(user.id).await?;
+
+        return Err(Error::InvalidTotpCode.into());
+    }
+
+    totp_repository.clear_failures(user.id).await?;
+
+    // last_counter, recovery codes가 바뀌었기 때문에 다시 저장함
+    totp_repository.add(user.id, &totp).await?;
+
+    Ok(Model {
+        user_id: user.id,
+        amr: vec![
+            amr::EMAIL.to_string(),
+            amr::OTP.to_string(),
+            amr::MFA.to_string(),
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use madome_sdk::api::user::model::User;
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::command::{self, CommandSet};
+    use crate::config::Config;
+    use crate::entity::{token::amr, totp::Totp};
+    use crate::repository::{r#trait::TotpRepository, RepositorySet};
+    use crate::usecase::check_totp::{self, Payload};
+
+    #[tokio::test]
+    async fn success_without_totp() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            let get_user_info = command::tests::GetUser::from(User {
+                id: user_id,
+                email: "totp@madome.app".to_string(),
+                role: 0,
+                name: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now()
+            });
+
+            command.set_get_user_info(get_user_info);
+        },
+        {
+            let payload = Payload {
+                user_email: "totp@madome.app".to_string(),
+                code: None,
+            };
+            let r = check_totp::execute(payload, repository, command, Arc::new(Config::default()))
+                .await
+                .unwrap();
+
+            assert_eq!(r.user_id, user_id);
+            assert_eq!(r.amr, vec![amr::EMAIL.to_string()]);
+        });
+    }
+
+    #[tokio::test]
+    async fn success_with_recovery_code() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid, recovery_codes: Vec<String>] ->
+        {
+            user_id = Uuid::new_v4();
+
+            let (mut totp, codes) = Totp::new();
+            totp.enabled = true;
+            recovery_codes = codes;
+
+            repository
+                .totp()
+                .add(user_id, &totp)
+                .await
+                .unwrap();
+
+            let get_user_info = command::tests::GetUser::from(User {
+                id: user_id,
+                email: "totp@madome.app".to_string(),
+                role: 0,
+                name: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now()
+            });
+
+            command.set_get_user_info(get_user_info);
+        },
+        {
+            let payload = Payload {
+                user_email: "totp@madome.app".to_string(),
+                code: Some(recovery_codes[0].clone()),
+            };
+            let r = check_totp::execute(payload, repository.clone(), command.clone(), Arc::new(Config::default()))
+                .await
+                .unwrap();
+
+            assert_eq!(r.user_id, user_id);
+            assert!(r.amr.contains(&amr::MFA.to_string()));
+
+            // 한번 사용한 recovery code는 다시 사용할 수 없음
+            let payload = Payload {
+                user_email: "totp@madome.app".to_string(),
+                code: Some(recovery_codes[0].clone()),
+            };
+            let r = check_totp::execute(payload, repository, command, Arc::new(Config::default()))
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_totp::Error::InvalidTotpCode));
+        });
+    }
+
+    #[tokio::test]
+    async fn error_required_totp_code() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            let (mut totp, _) = Totp::new();
+            totp.enabled = true;
+
+            repository
+                .totp()
+                .add(user_id, &totp)
+                .await
+                .unwrap();
+
+            let get_user_info = command::tests::GetUser::from(User {
+                id: user_id,
+                email: "totp@madome.app".to_string(),
+                role: 0,
+                name: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now()
+            });
+
+            command.set_get_user_info(get_user_info);
+        },
+        {
+            let payload = Payload {
+                user_email: "totp@madome.app".to_string(),
+                code: None,
+            };
+            let r = check_totp::execute(payload, repository, command, Arc::new(Config::default()))
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_totp::Error::RequiredTotpCode));
+        });
+    }
+
+    #[tokio::test]
+    async fn error_too_many_totp_attempts() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid, recovery_codes: Vec<String>] ->
+        {
+            user_id = Uuid::new_v4();
+
+            let (mut totp, codes) = Totp::new();
+            totp.enabled = true;
+            recovery_codes = codes;
+
+            repository
+                .totp()
+                .add(user_id, &totp)
+                .await
+                .unwrap();
+
+            let get_user_info = command::tests::GetUser::from(User {
+                id: user_id,
+                email: "totp@madome.app".to_string(),
+                role: 0,
+                name: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now()
+            });
+
+            command.set_get_user_info(get_user_info);
+        },
+        {
+            // TOTP_MAX_FAILURES=1
+            let mut config = Config::default();
+            config.set_totp_max_failures(1);
+            let config = Arc::new(config);
+
+            let payload = Payload {
+                user_email: "totp@madome.app".to_string(),
+                code: Some("wrong".to_string()),
+            };
+            let r = check_totp::execute(payload, repository.clone(), command.clone(), config.clone())
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_totp::Error::InvalidTotpCode));
+
+            // 맞는 code여도 받지 않음
+            let payload = Payload {
+                user_email: "totp@madome.app".to_string(),
+                code: Some(recovery_codes[0].clone()),
+            };
+            let r = check_totp::execute(payload, repository, command, config)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_totp::Error::TooManyTotpAttempts));
+        });
+    }
+}
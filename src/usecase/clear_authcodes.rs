@@ -0,0 +1,197 @@
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
+
+use either::Either;
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use serde::Serialize;
+use util::http::Cookie;
+use uuid::Uuid;
+
+use crate::{
+    command::CommandSet,
+    config::Config,
+    entity::audit::{AuditEvent, AuditKind},
+    msg,
+    repository::{r#trait::AuthcodeRepository, RepositorySet},
+};
+
+use super::check_access_token;
+
+/// DELETE /auth/admin/authcodes?user_id= | ?email=
+pub struct Payload {
+    pub access_token: String,
+    pub user: Either<Uuid, String>,
+}
+
+impl TryFrom<Request<Body>> for Payload {
+    type Error = crate::Error;
+
+    fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+        let qs = querystring::querify(request.uri().query().unwrap_or(""))
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+        let user = msg::user_id_or_email(&qs)?;
+
+        Ok(Self { access_token, user })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model {
+    pub cleared: usize,
+}
+
+pub async fn execute(
+    Payload { access_token, user }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    let admin = check_access_token::execute(
+        check_access_token::Payload::admin(access_token, &config),
+        repository.clone(),
+        command.clone(),
+    )
+    .await?;
+
+    let user = command.get_user_info(user).await?;
+
+    let cleared = repository.authcode().clear(&user.email).await?;
+
+    command
+        .audit(
+            AuditEvent::new(AuditKind::AuthcodesCleared)
+                .user_id(user.id)
+                .user_email(&user.email)
+                .token_id(admin.token_id)
+                .detail(format!("admin = {} cleared = {}", admin.user_id, cleared)),
+        )
+        .await;
+
+    Ok(Model { cleared })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use either::Either;
+    use madome_sdk::api::user::model::User;
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::command::{self, CommandSet};
+    use crate::config::{Config, DEFAULT_ADMIN_ROLE};
+    use crate::entity::{audit::AuditKind, authcode::Authcode, session::Client};
+    use crate::repository::{r#trait::AuthcodeRepository, RepositorySet};
+    use crate::usecase::{check_access_token, clear_authcodes, create_token_pair};
+
+    const EMAIL: &str = "clear@madome.app";
+
+    fn user(id: Uuid, role: u8) -> User {
+        User {
+            id,
+            email: EMAIL.to_string(),
+            role,
+            name: "".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn success() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(command::tests::GetUser::from(user(user_id, DEFAULT_ADMIN_ROLE)));
+
+            for code in ["111111", "222222"] {
+                repository
+                    .authcode()
+                    .add(Authcode::new(EMAIL.to_string(), code.to_string()))
+                    .await
+                    .unwrap();
+            }
+        },
+        {
+            let payload = create_token_pair::Payload {
+                subject: create_token_pair::Subject::UserId(user_id),
+                client: Client::default(),
+            };
+            let r = create_token_pair::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+            let payload = clear_authcodes::Payload {
+                access_token: r.access_token,
+                user: Either::Right(EMAIL.to_string()),
+            };
+            let r = clear_authcodes::execute(payload, repository.clone(), command.clone(), Arc::new(Config::default()))
+                .await
+                .unwrap();
+
+            assert_eq!(r.cleared, 2);
+            assert!(repository.authcode().get(EMAIL, "111111").await.unwrap().is_none());
+
+            let events = command.audit_events();
+            let event = events.iter().find(|x| x.kind == AuditKind::AuthcodesCleared).unwrap();
+
+            assert_eq!(event.user_id, Some(user_id));
+            assert_eq!(
+                event.detail.as_deref(),
+                Some(format!("admin = {} cleared = 2", user_id).as_str())
+            );
+        });
+    }
+
+    #[tokio::test]
+    async fn error_permission_denied() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(command::tests::GetUser::from(user(user_id, 0)));
+
+            repository
+                .authcode()
+                .add(Authcode::new(EMAIL.to_string(), "111111".to_string()))
+                .await
+                .unwrap();
+        },
+        {
+            let payload = create_token_pair::Payload {
+                subject: create_token_pair::Subject::UserId(user_id),
+                client: Client::default(),
+            };
+            let r = create_token_pair::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+            let payload = clear_authcodes::Payload {
+                access_token: r.access_token,
+                user: Either::Right(EMAIL.to_string()),
+            };
+            let r = clear_authcodes::execute(payload, repository.clone(), command, Arc::new(Config::default()))
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::PermissionDenied));
+            assert!(repository.authcode().get(EMAIL, "111111").await.unwrap().is_some());
+        });
+    }
+}
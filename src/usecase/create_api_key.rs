@@ -0,0 +1,254 @@
+use std::sync::Arc;
+
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use serde::{Deserialize, Serialize};
+use util::{http::Cookie, r#async::AsyncTryFrom, FromOwnedRequest};
+use uuid::Uuid;
+
+use crate::{
+    command::CommandSet,
+    entity::{api_key::ApiKey, token::amr},
+    error::UseCaseError,
+    msg::Wrap,
+    repository::{r#trait::ApiKeyRepository, RepositorySet},
+};
+
+use super::check_access_token;
+
+/// 유저 한명이 만들 수 있는 api key 수
+pub const MAX_API_KEYS: usize = 20;
+
+#[derive(Deserialize)]
+struct RequestBody {
+    name: String,
+    #[serde(default)]
+    scopes: Vec<String>,
+    /// 초 단위, 없으면 만료되지 않음
+    #[serde(default)]
+    expires_in: Option<i64>,
+}
+
+/// POST /auth/api-keys
+pub struct Payload {
+    pub access_token: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_in: Option<i64>,
+}
+
+#[async_trait::async_trait]
+impl FromOwnedRequest for Payload {
+    type Error = crate::Error;
+    type Parameter = ();
+
+    async fn from_owned_request(
+        _parameter: Self::Parameter,
+        request: Request<Body>,
+    ) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+
+        let RequestBody {
+            name,
+            scopes,
+            expires_in,
+        } = Wrap::async_try_from(request).await?.inner();
+
+        Ok(Self {
+            access_token,
+            name,
+            scopes,
+            expires_in,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model {
+    pub id: Uuid,
+    /// 지금만 확인할 수 있음
+    pub key: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid api key name")]
+    InvalidName,
+    #[error("Invalid expires_in")]
+    InvalidExpiresIn,
+    #[error("Can't create api key with api key")]
+    CreatedByApiKey,
+    #[error("Too many api keys")]
+    TooManyApiKeys,
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload {
+        access_token,
+        name,
+        scopes,
+        expires_in,
+    }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+) -> crate::Result<Model> {
+    let token_data = check_access_token::execute(
+        check_access_token::Payload {
+            access_token,
+            minimum_role: None,
+            validate_exp: true,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![],
+            mfa_required_role: None,
+            role_scopes: None,
+        },
+        repository.clone(),
+        command.clone(),
+    )
+    .await?;
+
+    // api key가 유출됐을 때 만료되지 않는 key를 새로 만들 수 없게 함
+    if token_data.amr.iter().any(|x| x == amr::API_KEY) {
+        return Err(Error::CreatedByApiKey.into());
+    }
+
+    let name = name.trim();
+
+    if name.is_empty() || name.len() > 64 {
+        return Err(Error::InvalidName.into());
+    }
+
+    if matches!(expires_in, Some(x) if x <= 0) {
+        return Err(Error::InvalidExpiresIn.into());
+    }
+
+    let api_key_repository = repository.api_key();
+
+    if api_key_repository.list(token_data.user_id).await?.len() >= MAX_API_KEYS {
+        return Err(Error::TooManyApiKeys.into());
+    }
+
+    let (api_key, key) = ApiKey::new(token_data.user_id, name, scopes, expires_in);
+
+    api_key_repository.add(&api_key).await?;
+
+    Ok(Model {
+        id: api_key.id,
+        key,
+        name: api_key.name,
+        scopes: api_key.scopes,
+        expires_at: api_key.expires_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::command::CommandSet;
+    use crate::entity::token::{amr, Token};
+    use crate::repository::{r#trait::SecretKeyRepository, RepositorySet};
+    use crate::usecase::{check_access_token, create_api_key, delete_api_key, list_api_keys};
+
+    #[tokio::test]
+    async fn success() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [access_token: String, user_id: Uuid] ->
+        {
+            let secret_key = "secret1234".to_string();
+            user_id = Uuid::new_v4();
+            let token = Token::new(user_id);
+
+            repository
+                .secret_key()
+                .add(token.id, &secret_key)
+                .await
+                .unwrap();
+
+            access_token = token.serialize(&secret_key).expect("token serialize").0;
+        },
+        {
+            let payload = create_api_key::Payload {
+                access_token: access_token.clone(),
+                name: "cron".to_string(),
+                scopes: vec!["library:read".to_string()],
+                expires_in: Some(3600),
+            };
+            let created = create_api_key::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+            // api key로 access token처럼 인증할 수 있음
+            let payload = check_access_token::Payload {
+                access_token: created.key.clone(),
+                minimum_role: None,
+                validate_exp: true,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+            };
+            let r = check_access_token::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+            assert_eq!(r.user_id, user_id);
+            assert_eq!(r.token_id, created.id);
+            assert_eq!(r.amr, vec![amr::API_KEY.to_string()]);
+
+            // api key로 api key를 만들 수 없음
+            let payload = create_api_key::Payload {
+                access_token: created.key.clone(),
+                name: "cron2".to_string(),
+                scopes: vec![],
+                expires_in: None,
+            };
+            let r = create_api_key::execute(payload, repository.clone(), command.clone())
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(create_api_key::Error::CreatedByApiKey));
+
+            let payload = list_api_keys::Payload { access_token: access_token.clone() };
+            let r = list_api_keys::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+            assert_eq!(r.api_keys.len(), 1);
+            assert!(r.api_keys[0].secret.is_empty());
+
+            let payload = delete_api_key::Payload { access_token, id: created.id };
+            delete_api_key::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+            let payload = check_access_token::Payload {
+                access_token: created.key,
+                minimum_role: None,
+                validate_exp: true,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+            };
+            let r = check_access_token::execute(payload, repository, command)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::UnauthorizedAccessToken));
+        });
+    }
+}
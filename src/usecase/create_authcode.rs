@@ -7,8 +7,12 @@ use util::{r#async::AsyncTryFrom, validate::ValidatorStringExt, FromOwnedRequest
 
 use crate::{
     command::CommandSet,
-    entity::authcode::Authcode,
+    entity::{
+        audit::{AuditEvent, AuditKind},
+        authcode::Authcode,
+    },
     error::UseCaseError,
+    metrics,
     msg::Wrap,
     repository::{r#trait::AuthcodeRepository, RepositorySet},
 };
@@ -110,16 +114,33 @@ pub async fn execute(
         return Err(Error::TooManyCreatedAuthcode.into());
     }
 
+    metrics::AUTHCODES_CREATED.inc();
+
+    command
+        .audit(
+            AuditEvent::new(AuditKind::AuthcodeRequested)
+                .user_id(user.id)
+                .user_email(&user.email),
+        )
+        .await;
+
     #[cfg(feature = "aws-ses")]
     {
         #[cfg(not(debug_assertions))]
         {
-            command.send_email(user.email, code).await?;
+            command
+                .send_email(user.email, crate::command::Mail::Authcode(code))
+                .await?;
         }
         #[cfg(debug_assertions)]
         {
             if ses_flag {
-                command.send_email(user.email.clone(), code.clone()).await?;
+                command
+                    .send_email(
+                        user.email.clone(),
+                        crate::command::Mail::Authcode(code.clone()),
+                    )
+                    .await?;
             }
         }
     }
@@ -0,0 +1,278 @@
+use std::sync::Arc;
+
+use hyper::{Body, Request};
+use serde::{Deserialize, Serialize};
+use util::{r#async::AsyncTryFrom, FromOwnedRequest};
+
+use crate::{
+    command::CommandSet,
+    entity::{
+        audit::{AuditEvent, AuditKind},
+        service_client::{ServiceToken, SERVICE_TOKEN_EXP},
+    },
+    error::UseCaseError,
+    metrics,
+    msg::{self, Form, Wrap},
+    repository::{r#trait::ServiceClientRepository, RepositorySet},
+};
+
+pub const CLIENT_CREDENTIALS: &str = "client_credentials";
+
+/// POST /auth/service/token
+///
+/// RFC 6749 4.4 client credentials grant
+///
+/// body는 application/x-www-form-urlencoded이지만 json도 받음
+///
+/// client 인증은 `Authorization: Basic` (client_secret_basic) 또는 body (client_secret_post)
+#[derive(Deserialize)]
+pub struct Payload {
+    pub grant_type: String,
+    #[serde(default)]
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: String,
+    /// 공백으로 구분함, 없으면 client의 모든 scope
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl FromOwnedRequest for Payload {
+    type Error = crate::Error;
+    type Parameter = ();
+
+    async fn from_owned_request(
+        _parameter: Self::Parameter,
+        request: Request<Body>,
+    ) -> Result<Self, Self::Error> {
+        let basic = msg::basic_credentials(&request);
+
+        let payload: Self = if msg::is_form(&request) {
+            Form::async_try_from(request).await?.inner()
+        } else {
+            Wrap::async_try_from(request).await?.inner()
+        };
+
+        match basic {
+            // 두 가지 방법을 같이 사용하면 안됨 (RFC 6749 2.3)
+            Some(_) if !payload.client_id.is_empty() || !payload.client_secret.is_empty() => {
+                Err(Error::InvalidClient.into())
+            }
+            Some((client_id, client_secret)) => Ok(Self {
+                client_id,
+                client_secret,
+                ..payload
+            }),
+            None => Ok(payload),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Unsupported grant type")]
+    UnsupportedGrantType,
+    #[error("Invalid client")]
+    InvalidClient,
+    #[error("Invalid scope")]
+    InvalidScope,
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload {
+        grant_type,
+        client_id,
+        client_secret,
+        scope,
+    }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+) -> crate::Result<Model> {
+    if grant_type != CLIENT_CREDENTIALS {
+        return Err(Error::UnsupportedGrantType.into());
+    }
+
+    let client = match repository.service_client().get(&client_id).await? {
+        Some(client) if client.verify_secret(&client_secret) => client,
+        _ => {
+            command
+                .audit(
+                    AuditEvent::new(AuditKind::ServiceTokenDenied)
+                        .detail(format!("client_id = {}", client_id)),
+                )
+                .await;
+
+            return Err(Error::InvalidClient.into());
+        }
+    };
+
+    let scopes = match scope {
+        Some(scope) => {
+            let scopes = scope
+                .split_whitespace()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>();
+
+            if scopes.iter().any(|x| !client.scopes.contains(x)) {
+                return Err(Error::InvalidScope.into());
+            }
+
+            scopes
+        }
+        None => client.scopes.clone(),
+    };
+
+    let token = ServiceToken::new(&client.id, scopes);
+    let access_token = token.serialize(&client.signing_key);
+
+    metrics::TOKENS_ISSUED.with_label_values(&["service"]).inc();
+
+    command
+        .audit(
+            AuditEvent::new(AuditKind::ServiceTokenIssued)
+                .token_id(token.id)
+                .detail(format!("client_id = {}", client.id)),
+        )
+        .await;
+
+    Ok(Model {
+        access_token,
+        token_type: "Bearer",
+        expires_in: SERVICE_TOKEN_EXP,
+        scope: token.scopes.join(" "),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+
+    use crate::command::CommandSet;
+    use crate::entity::service_client::ServiceClient;
+    use crate::repository::{r#trait::ServiceClientRepository, RepositorySet};
+    use crate::usecase::{check_access_token, check_service_token, create_service_token};
+
+    #[tokio::test]
+    async fn success() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [client: ServiceClient, secret: String] ->
+        {
+            let (c, s) = ServiceClient::new("madome-library", vec!["library:read".to_string(), "users:read".to_string()]);
+            client = c;
+            secret = s;
+
+            repository.service_client().add(&client).await.unwrap();
+        },
+        {
+            let payload = create_service_token::Payload {
+                grant_type: create_service_token::CLIENT_CREDENTIALS.to_string(),
+                client_id: client.id.clone(),
+                client_secret: secret,
+                scope: Some("library:read".to_string()),
+            };
+            let r = create_service_token::execute(payload, repository.clone(), command).await.unwrap();
+
+            assert_eq!(r.scope, "library:read");
+
+            let payload = check_access_token::Payload {
+                access_token: r.access_token,
+                minimum_role: None,
+                validate_exp: true,
+                require_mfa: false,
+                max_age: None,
+                scopes: vec![],
+                mfa_required_role: None,
+                role_scopes: None,
+            };
+
+            assert!(payload.is_service_token());
+
+            let r = check_service_token::execute(payload.into(), repository).await.unwrap();
+
+            assert_eq!(r.client_id, client.id);
+            assert_eq!(r.scopes, vec!["library:read".to_string()]);
+        });
+    }
+
+    #[tokio::test]
+    async fn error_invalid_client() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [client: ServiceClient] ->
+        {
+            let (c, _) = ServiceClient::new("madome-library", vec![]);
+            client = c;
+
+            repository.service_client().add(&client).await.unwrap();
+        },
+        {
+            let payload = create_service_token::Payload {
+                grant_type: create_service_token::CLIENT_CREDENTIALS.to_string(),
+                client_id: client.id.clone(),
+                client_secret: "wrong secret".to_string(),
+                scope: None,
+            };
+            let r = create_service_token::execute(payload, repository, command)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(create_service_token::Error::InvalidClient));
+        });
+    }
+
+    #[tokio::test]
+    async fn error_invalid_scope() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [client: ServiceClient, secret: String] ->
+        {
+            let (c, s) = ServiceClient::new("madome-library", vec!["library:read".to_string()]);
+            client = c;
+            secret = s;
+
+            repository.service_client().add(&client).await.unwrap();
+        },
+        {
+            let payload = create_service_token::Payload {
+                grant_type: create_service_token::CLIENT_CREDENTIALS.to_string(),
+                client_id: client.id.clone(),
+                client_secret: secret,
+                scope: Some("admin:users".to_string()),
+            };
+            let r = create_service_token::execute(payload, repository, command)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(create_service_token::Error::InvalidScope));
+        });
+    }
+}
@@ -5,21 +5,96 @@ use uuid::Uuid;
 
 use crate::{
     command::CommandSet,
-    entity::{secret_key::SecretKey, token::Token},
+    entity::{
+        audit::{AuditEvent, AuditKind},
+        secret_key::SecretKey,
+        session::{Client, Session},
+        token::{amr, Token},
+    },
     error::UseCaseError,
-    repository::{r#trait::SecretKeyRepository, RepositorySet},
+    metrics,
+    repository::{
+        r#trait::{SecretKeyRepository, SessionRepository},
+        RepositorySet,
+    },
 };
 
-use super::check_authcode;
+use super::{check_authcode, check_totp, finish_passkey_authentication};
 
-pub enum Payload {
+pub struct Payload {
+    pub subject: Subject,
+    pub client: Client,
+}
+
+pub enum Subject {
     UserEmail(String),
     UserId(Uuid),
+    /// 인증에 사용된 수단(amr)을 token에 기록함
+    Authenticated {
+        user_id: Uuid,
+        amr: Vec<String>,
+    },
+    /// 이전 token의 인증 정보(amr, auth_time)와 session을 이어받음
+    Refresh {
+        user_id: Uuid,
+        amr: Vec<String>,
+        auth_time: i64,
+        session: Option<Session>,
+    },
+}
+
+impl Subject {
+    /// metrics의 label로 사용함
+    fn label(&self) -> &'static str {
+        match self {
+            Self::UserEmail(_) => "user_email",
+            Self::UserId(_) => "user_id",
+            Self::Authenticated { .. } => "authenticated",
+            Self::Refresh { .. } => "refresh",
+        }
+    }
+}
+
+impl From<(check_authcode::Model, Client)> for Payload {
+    fn from((model, client): (check_authcode::Model, Client)) -> Self {
+        Self {
+            subject: Subject::Authenticated {
+                user_id: model.user_id,
+                amr: model.amr,
+            },
+            client,
+        }
+    }
 }
 
-impl From<check_authcode::Model> for Payload {
-    fn from(model: check_authcode::Model) -> Self {
-        Self::UserEmail(model.user_email)
+/// user verification을 거친 passkey는 가진 것(authenticator)과 아는 것/생체 정보를 함께 확인하므로 mfa로 봄
+impl From<finish_passkey_authentication::Model> for Payload {
+    fn from(model: finish_passkey_authentication::Model) -> Self {
+        let amr = if model.user_verified {
+            vec![amr::HWK.to_string(), amr::MFA.to_string()]
+        } else {
+            vec![amr::HWK.to_string()]
+        };
+
+        Self {
+            subject: Subject::Authenticated {
+                user_id: model.user_id,
+                amr,
+            },
+            client: model.client,
+        }
+    }
+}
+
+impl From<(check_totp::Model, Client)> for Payload {
+    fn from((model, client): (check_totp::Model, Client)) -> Self {
+        Self {
+            subject: Subject::Authenticated {
+                user_id: model.user_id,
+                amr: model.amr,
+            },
+            client,
+        }
     }
 }
 
@@ -37,6 +112,7 @@ pub struct Model {
     pub refresh_token: String,
     pub token_id: Uuid,
     pub user_id: Uuid,
+    pub session_id: Uuid,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -55,18 +131,32 @@ impl From<Error> for crate::Error {
 }
 
 pub async fn execute(
-    payload: Payload,
+    Payload { subject, client }: Payload,
     repository: Arc<RepositorySet>,
     command: Arc<CommandSet>,
 ) -> crate::Result<Model> {
-    let user_id = match payload {
-        Payload::UserId(user_id) => user_id,
-        Payload::UserEmail(user_email) => {
-            command.get_user_info(Either::Right(user_email)).await?.id
+    let subject_label = subject.label();
+    let is_refresh = matches!(subject, Subject::Refresh { .. });
+
+    let (token, prev_session) = match subject {
+        Subject::UserId(user_id) => (Token::new(user_id), None),
+        Subject::UserEmail(user_email) => {
+            let user_id = command.get_user_info(Either::Right(user_email)).await?.id;
+
+            (Token::new(user_id), None)
         }
+        Subject::Authenticated { user_id, amr } => (Token::new(user_id).with_amr(amr), None),
+        Subject::Refresh {
+            user_id,
+            amr,
+            auth_time,
+            session,
+        } => (
+            Token::new(user_id).with_amr(amr).with_auth_time(auth_time),
+            session,
+        ),
     };
 
-    let token = Token::new(user_id);
     let secret_key = SecretKey::new();
 
     let secret_key_added = repository.secret_key().add(token.id, &secret_key).await?;
@@ -75,13 +165,40 @@ pub async fn execute(
         return Err(Error::CannotAddedSecretKey.into());
     }
 
+    let session = match prev_session {
+        Some(prev_session) => prev_session.refreshed(token.id, client),
+        None => Session::new(token.id, token.user_id, client),
+    };
+
+    repository.session().add(&session).await?;
+
     let (access_token, refresh_token) = token.serialize(&secret_key)?;
 
+    metrics::TOKENS_ISSUED
+        .with_label_values(&[subject_label])
+        .inc();
+
+    let audit_kind = if is_refresh {
+        AuditKind::TokenPairRefreshed
+    } else {
+        AuditKind::TokenPairIssued
+    };
+
+    command
+        .audit(
+            AuditEvent::new(audit_kind)
+                .user_id(token.user_id)
+                .token_id(token.id)
+                .detail(format!("amr = {}", token.amr.join(","))),
+        )
+        .await;
+
     Ok(Model {
         access_token,
         refresh_token,
         token_id: token.id,
         user_id: token.user_id,
+        session_id: session.id,
     })
 }
 
@@ -93,10 +210,30 @@ mod tests {
 
     use crate::{
         command::CommandSet,
-        repository::RepositorySet,
-        usecase::{check_token_pair, create_token_pair},
+        entity::{audit::AuditKind, session::Client},
+        repository::{r#trait::SessionRepository, RepositorySet},
+        usecase::{check_token_pair, create_token_pair, finish_passkey_authentication},
     };
 
+    #[test]
+    fn passkey_amr() {
+        let amr = |user_verified: bool| {
+            let model = finish_passkey_authentication::Model {
+                user_id: Uuid::new_v4(),
+                user_verified,
+                client: Client::default(),
+            };
+
+            match create_token_pair::Payload::from(model).subject {
+                create_token_pair::Subject::Authenticated { amr, .. } => amr,
+                _ => unreachable!(),
+            }
+        };
+
+        assert_eq!(amr(true), ["hwk", "mfa"]);
+        assert_eq!(amr(false), ["hwk"]);
+    }
+
     #[tokio::test]
     async fn success() {
         let mut test = System::<TestRegistry>::new();
@@ -110,9 +247,26 @@ mod tests {
             user_id = Uuid::new_v4();
         },
         {
-            let payload = create_token_pair::Payload::UserId(user_id);
+            let payload = create_token_pair::Payload {
+                subject: create_token_pair::Subject::UserId(user_id),
+                client: Client {
+                    user_agent: Some("curl/7.79.1".to_string()),
+                    ..Default::default()
+                },
+            };
             let r = create_token_pair::execute(payload, repository.clone(), command.clone()).await.unwrap();
 
+            let session = repository.session().get(r.token_id).await.unwrap().expect("session");
+
+            assert_eq!(session.user_id, user_id);
+            assert_eq!(session.client.user_agent_family(), "curl");
+
+            let events = command.audit_events();
+
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].kind, AuditKind::TokenPairIssued);
+            assert_eq!(events[0].token_id, Some(r.token_id));
+
             let payload = check_token_pair::Payload {
                 access_token: r.access_token,
                 refresh_token: r.refresh_token
@@ -0,0 +1,97 @@
+use std::{convert::TryFrom, sync::Arc};
+
+use either::Either;
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use serde::Serialize;
+use util::http::Cookie;
+
+use crate::{
+    command::CommandSet,
+    entity::totp::Totp,
+    error::UseCaseError,
+    repository::{r#trait::TotpRepository, RepositorySet},
+};
+
+use super::check_access_token;
+
+pub struct Payload {
+    pub access_token: String,
+}
+
+impl TryFrom<Request<Body>> for Payload {
+    type Error = crate::Error;
+
+    fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+
+        Ok(Self { access_token })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model {
+    pub secret: String,
+    pub uri: String,
+    /// 지금만 확인할 수 있음
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Already enabled totp")]
+    AlreadyEnabledTotp,
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload { access_token }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+) -> crate::Result<Model> {
+    let token_data = check_access_token::execute(
+        check_access_token::Payload {
+            access_token,
+            minimum_role: None,
+            validate_exp: true,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![],
+            mfa_required_role: None,
+            role_scopes: None,
+        },
+        repository.clone(),
+        command.clone(),
+    )
+    .await?;
+
+    let totp_repository = repository.totp();
+
+    // 활성화되지 않은 건 덮어씀
+    if let Some(totp) = totp_repository.get(token_data.user_id).await? {
+        if totp.enabled {
+            return Err(Error::AlreadyEnabledTotp.into());
+        }
+    }
+
+    let user = command
+        .get_user_info(Either::Left(token_data.user_id))
+        .await?;
+
+    let (totp, recovery_codes) = Totp::new();
+
+    totp_repository.add(user.id, &totp).await?;
+
+    Ok(Model {
+        uri: totp.uri(&user.email),
+        secret: totp.secret,
+        recovery_codes,
+    })
+}
@@ -0,0 +1,89 @@
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
+
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use util::http::Cookie;
+use uuid::Uuid;
+
+use crate::{
+    command::CommandSet,
+    error::UseCaseError,
+    msg,
+    repository::{r#trait::ApiKeyRepository, RepositorySet},
+};
+
+use super::check_access_token;
+
+/// DELETE /auth/api-keys?id=
+pub struct Payload {
+    pub access_token: String,
+    pub id: Uuid,
+}
+
+impl TryFrom<Request<Body>> for Payload {
+    type Error = crate::Error;
+
+    fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+        let qs = querystring::querify(request.uri().query().unwrap_or(""))
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+        // DELETE /auth/api-keys/{id} 또는 ?id=
+        let id = msg::param(&request, "id")
+            .or_else(|| qs.get("id").copied())
+            .and_then(|v| v.parse().ok())
+            .ok_or(msg::Error::RequiredQuery("id"))?;
+
+        Ok(Self { access_token, id })
+    }
+}
+
+pub struct Model;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Not found api key")]
+    NotFoundApiKey,
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload { access_token, id }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+) -> crate::Result<Model> {
+    let token_data = check_access_token::execute(
+        check_access_token::Payload {
+            access_token,
+            minimum_role: None,
+            validate_exp: true,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![],
+            mfa_required_role: None,
+            role_scopes: None,
+        },
+        repository.clone(),
+        command,
+    )
+    .await?;
+
+    let api_key_repository = repository.api_key();
+
+    // 다른 유저의 api key는 없는 것처럼 다룸
+    match api_key_repository.get(id).await? {
+        Some(api_key) if api_key.user_id == token_data.user_id => {
+            api_key_repository.remove(id).await?;
+        }
+        _ => return Err(Error::NotFoundApiKey.into()),
+    }
+
+    Ok(Model)
+}
@@ -5,9 +5,17 @@ use madome_sdk::api::cookie::{MADOME_ACCESS_TOKEN, MADOME_REFRESH_TOKEN};
 use util::http::Cookie;
 
 use crate::{
-    entity::token::{AccessToken, RefreshToken},
+    command::CommandSet,
+    entity::{
+        audit::{AuditEvent, AuditKind},
+        token::{AccessToken, RefreshToken},
+    },
     error::UseCaseError,
-    repository::{r#trait::SecretKeyRepository, RepositorySet},
+    metrics,
+    repository::{
+        r#trait::{SecretKeyRepository, SessionRepository},
+        RepositorySet,
+    },
 };
 
 #[derive(Debug)]
@@ -52,6 +60,7 @@ pub async fn execute(
         refresh_token,
     }: Payload,
     repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
 ) -> crate::Result<Model> {
     // TODO:
     // 지우기만 하면 되기 때문에 유효한지 검증할 필요가 없음
@@ -59,15 +68,29 @@ pub async fn execute(
     let a = AccessToken::deserialize_payload(&access_token);
     let r = RefreshToken::deserialize_payload(&refresh_token);
 
-    let token_id = match (a, r) {
-        (Some(a), Some(r)) if a.id == r.id && a.user_id == r.user_id => a.id,
-        (Some(a), None) => a.id,
-        (_, Some(r)) => r.id,
+    let (token_id, user_id) = match (a, r) {
+        (Some(a), Some(r)) if a.id == r.id && a.user_id == r.user_id => (a.id, a.user_id),
+        (Some(a), None) => (a.id, a.user_id),
+        (_, Some(r)) => (r.id, r.user_id),
         _ => return Err(Error::InvalidToken.into()),
     };
 
     // 에러만 안나면 됨
     let _r = repository.secret_key().remove(token_id).await?;
+    let _r = repository.session().remove(token_id).await?;
+
+    metrics::TOKEN_REVOCATIONS
+        .with_label_values(&["logout"])
+        .inc();
+
+    command
+        .audit(
+            AuditEvent::new(AuditKind::TokenPairRevoked)
+                .user_id(user_id)
+                .token_id(token_id)
+                .detail("logout"),
+        )
+        .await;
 
     Ok(Model)
 }
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use serde::Deserialize;
+use util::{http::Cookie, r#async::AsyncTryFrom, FromOwnedRequest};
+
+use crate::{
+    command::CommandSet,
+    error::UseCaseError,
+    msg::Wrap,
+    repository::{r#trait::TotpRepository, RepositorySet},
+};
+
+use super::check_access_token;
+
+pub struct Payload {
+    pub access_token: String,
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+struct RequestBody {
+    code: String,
+}
+
+#[async_trait::async_trait]
+impl FromOwnedRequest for Payload {
+    type Error = crate::Error;
+    type Parameter = ();
+
+    async fn from_owned_request(
+        _parameter: Self::Parameter,
+        request: Request<Body>,
+    ) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+
+        let RequestBody { code } = Wrap::async_try_from(request).await?.inner();
+
+        Ok(Self { access_token, code })
+    }
+}
+
+pub struct Model;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Not found totp")]
+    NotFoundTotp,
+
+    #[error("Invalid totp code")]
+    InvalidTotpCode,
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload { access_token, code }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+) -> crate::Result<Model> {
+    let token_data = check_access_token::execute(
+        check_access_token::Payload {
+            access_token,
+            minimum_role: None,
+            validate_exp: true,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![],
+            mfa_required_role: None,
+            role_scopes: None,
+        },
+        repository.clone(),
+        command,
+    )
+    .await?;
+
+    let totp_repository = repository.totp();
+
+    let mut totp = match totp_repository.get(token_data.user_id).await? {
+        Some(totp) => totp,
+        None => return Err(Error::NotFoundTotp.into()),
+    };
+
+    if !totp.verify(&code) {
+        return Err(Error::InvalidTotpCode.into());
+    }
+
+    totp.enabled = true;
+
+    totp_repository.add(token_data.user_id, &totp).await?;
+
+    Ok(Model)
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use hyper::{Body, Request};
+use serde::Deserialize;
+use util::{r#async::AsyncTryFrom, FromOwnedRequest};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    entity::{passkey::PublicKeyCredential, session::Client},
+    error::UseCaseError,
+    msg::{self, Wrap},
+    repository::{r#trait::PasskeyRepository, RepositorySet},
+};
+
+#[derive(Deserialize)]
+pub struct Payload {
+    /// start_passkey_authentication에서 받은 값
+    pub ceremony_id: Uuid,
+    pub credential: PublicKeyCredential,
+    #[serde(skip)]
+    pub client: Client,
+}
+
+#[async_trait::async_trait]
+impl FromOwnedRequest for Payload {
+    type Error = crate::Error;
+    type Parameter = ();
+
+    async fn from_owned_request(
+        _parameter: Self::Parameter,
+        request: Request<Body>,
+    ) -> Result<Self, Self::Error> {
+        let client = msg::client(&request);
+
+        let payload: Self = Wrap::async_try_from(request).await?.inner();
+
+        Ok(Self { client, ..payload })
+    }
+}
+
+#[derive(Debug)]
+pub struct Model {
+    pub user_id: Uuid,
+    /// authenticator가 PIN이나 생체 인증으로 유저를 확인함
+    pub user_verified: bool,
+    pub client: Client,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid credential")]
+    InvalidCredential,
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload {
+        ceremony_id,
+        credential,
+        client,
+    }: Payload,
+    repository: Arc<RepositorySet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    let passkey_repository = repository.passkey();
+
+    // 만료됐거나 decoy challenge의 ceremony id도 구분하지 않고 같은 에러를 돌려줌
+    let (user_id, state) = match passkey_repository.pop_authentication(ceremony_id).await? {
+        Some(x) => x,
+        None => return Err(Error::InvalidCredential.into()),
+    };
+
+    let result = config
+        .webauthn()
+        .finish_passkey_authentication(&credential, &state)
+        .map_err(|err| {
+            log::debug!("finish_passkey_authentication = {}", err);
+            Error::InvalidCredential
+        })?;
+
+    // sign count 등이 바뀌었으면 저장된 passkey도 갱신해줘야 함
+    if result.needs_update() {
+        for mut passkey in passkey_repository.list(user_id).await? {
+            if let Some(true) = passkey.update_credential(&result) {
+                passkey_repository.add(user_id, &passkey).await?;
+            }
+        }
+    }
+
+    Ok(Model {
+        user_id,
+        user_verified: result.user_verified(),
+        client,
+    })
+}
@@ -0,0 +1,212 @@
+use std::sync::Arc;
+
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use util::{http::Cookie, r#async::AsyncTryFrom, FromOwnedRequest};
+
+use crate::{
+    command::CommandSet,
+    config::Config,
+    entity::passkey::RegisterPublicKeyCredential,
+    error::UseCaseError,
+    msg::Wrap,
+    repository::{r#trait::PasskeyRepository, RepositorySet},
+};
+
+use super::check_access_token;
+
+pub struct Payload {
+    pub access_token: String,
+    pub credential: RegisterPublicKeyCredential,
+}
+
+#[async_trait::async_trait]
+impl FromOwnedRequest for Payload {
+    type Error = crate::Error;
+    type Parameter = ();
+
+    async fn from_owned_request(
+        _parameter: Self::Parameter,
+        request: Request<Body>,
+    ) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+
+        let credential = Wrap::async_try_from(request).await?.inner();
+
+        Ok(Self {
+            access_token,
+            credential,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Model;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Not found registration")]
+    NotFoundRegistration,
+
+    #[error("Invalid credential")]
+    InvalidCredential,
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload {
+        access_token,
+        credential,
+    }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    let token_data = check_access_token::execute(
+        check_access_token::Payload {
+            access_token,
+            minimum_role: None,
+            validate_exp: true,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![],
+            mfa_required_role: None,
+            role_scopes: None,
+        },
+        repository.clone(),
+        command,
+    )
+    .await?;
+
+    let state = match repository
+        .passkey()
+        .pop_registration(token_data.user_id)
+        .await?
+    {
+        Some(state) => state,
+        None => return Err(Error::NotFoundRegistration.into()),
+    };
+
+    let passkey = config
+        .webauthn()
+        .finish_passkey_registration(&credential, &state)
+        .map_err(|err| {
+            log::debug!("finish_passkey_registration = {}", err);
+            Error::InvalidCredential
+        })?;
+
+    repository
+        .passkey()
+        .add(token_data.user_id, &passkey)
+        .await?;
+
+    Ok(Model)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use madome_sdk::api::user::model::User;
+    use sai::{Component, System};
+    use serde_json::json;
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::command::{self, CommandSet};
+    use crate::config::Config;
+    use crate::entity::token::Token;
+    use crate::repository::{r#trait::SecretKeyRepository, RepositorySet};
+    use crate::usecase::{finish_passkey_registration, start_passkey_registration};
+
+    #[tokio::test]
+    async fn ceremony() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [access_token: String, config: Arc<Config>] ->
+        {
+            let secret_key = "secret1234".to_string();
+            let user_id = Uuid::new_v4();
+            let token = Token::new(user_id);
+
+            repository.secret_key().add(token.id, &secret_key).await.unwrap();
+
+            access_token = token.serialize(&secret_key).expect("token serialize").0;
+
+            let get_user_info = command::tests::GetUser::from(User {
+                id: user_id,
+                email: "passkey@madome.app".to_string(),
+                role: 0,
+                name: "passkey".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now()
+            });
+
+            command.set_get_user_info(get_user_info);
+
+            let mut c = Config::default();
+            c.set_webauthn("madome.app", "https://madome.app");
+            config = Arc::new(c);
+        },
+        {
+            let payload = start_passkey_registration::Payload {
+                access_token: access_token.clone(),
+            };
+            let start_passkey_registration::Model(challenge) = start_passkey_registration::execute(
+                payload,
+                repository.clone(),
+                command.clone(),
+                config.clone(),
+            )
+            .await
+            .unwrap();
+
+            let challenge = serde_json::to_value(challenge).unwrap();
+
+            assert_eq!(challenge["publicKey"]["rp"]["id"], "madome.app");
+            assert_eq!(challenge["publicKey"]["user"]["name"], "passkey@madome.app");
+
+            // authenticator가 만들지 않은 credential
+            let credential = json!({
+                "id": "AAAA",
+                "rawId": "AAAA",
+                "response": {
+                    "attestationObject": "AAAA",
+                    "clientDataJSON": "AAAA",
+                },
+                "type": "public-key",
+                "extensions": {},
+            });
+
+            let payload = || finish_passkey_registration::Payload {
+                access_token: access_token.clone(),
+                credential: serde_json::from_value(credential.clone()).unwrap(),
+            };
+
+            let r = finish_passkey_registration::execute(payload(), repository.clone(), command.clone(), config.clone())
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(finish_passkey_registration::Error::InvalidCredential));
+
+            // 실패해도 state는 한번만 사용할 수 있음
+            let r = finish_passkey_registration::execute(payload(), repository, command, config)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(finish_passkey_registration::Error::NotFoundRegistration));
+        });
+    }
+}
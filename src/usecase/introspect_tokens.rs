@@ -0,0 +1,510 @@
+use std::{collections::HashMap, sync::Arc};
+
+use either::Either;
+use futures_util::future;
+use hyper::{Body, Request};
+use madome_sdk::api::user::{self, get_user};
+use serde::{Deserialize, Serialize};
+use util::{ori, r#async::AsyncTryFrom, FromOwnedRequest};
+use uuid::Uuid;
+
+use crate::{
+    command::CommandSet,
+    entity::{api_key::ApiKey, scope, secret_key::SecretKey, token::AccessToken},
+    error::UseCaseError,
+    msg::{self, Wrap},
+    repository::{
+        r#trait::{ApiKeyRepository, SecretKeyRepository},
+        RepositorySet,
+    },
+};
+
+use super::check_service_token;
+
+/// 한번에 확인할 수 있는 token 수
+pub const MAX_TOKENS: usize = 100;
+
+#[derive(Deserialize)]
+struct RequestBody {
+    tokens: Vec<String>,
+}
+
+/// POST /auth/token/introspect
+///
+/// `auth:introspect` scope가 있는 service token을 Authorization header로 받음
+pub struct Payload {
+    pub service_token: String,
+    /// access token 또는 api key
+    pub tokens: Vec<String>,
+}
+
+#[async_trait::async_trait]
+impl FromOwnedRequest for Payload {
+    type Error = crate::Error;
+    type Parameter = ();
+
+    async fn from_owned_request(
+        _parameter: Self::Parameter,
+        request: Request<Body>,
+    ) -> Result<Self, Self::Error> {
+        let service_token = msg::bearer_token(&request).unwrap_or_default();
+
+        let RequestBody { tokens } = Wrap::async_try_from(request).await?.inner();
+
+        Ok(Self {
+            service_token,
+            tokens,
+        })
+    }
+}
+
+/// RFC 7662 2.2, active가 false이면 다른 값은 없음
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Introspection {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<u8>,
+    /// scope가 지정된 token이나 api key만, 공백으로 구분함
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// 서명이나 secret을 확인한 access token 또는 api key
+struct Verified {
+    user_id: Uuid,
+    token_id: Uuid,
+    /// 만료되지 않는 api key는 None
+    exp: Option<i64>,
+    scopes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model {
+    /// 요청한 순서대로
+    pub tokens: Vec<Introspection>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Too many tokens: limit is {0}")]
+    TooManyTokens(usize),
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+async fn verify_api_key(
+    api_key: &str,
+    api_key_repository: Arc<impl ApiKeyRepository>,
+) -> crate::Result<Option<Verified>> {
+    let (id, secret) = ori!(ApiKey::split(api_key));
+
+    let api_key = ori!(api_key_repository.get(id).await?);
+
+    if api_key.expired() || !api_key.verify_secret(secret) {
+        return Ok(None);
+    }
+
+    Ok(Some(Verified {
+        user_id: api_key.user_id,
+        token_id: api_key.id,
+        exp: api_key.expires_at,
+        scopes: api_key.scopes,
+    }))
+}
+
+pub async fn execute(
+    Payload {
+        service_token,
+        tokens,
+    }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+) -> crate::Result<Model> {
+    check_service_token::execute(
+        check_service_token::Payload {
+            service_token,
+            minimum_role: None,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![scope::AUTH_INTROSPECT.to_string()],
+        },
+        repository.clone(),
+    )
+    .await?;
+
+    if tokens.len() > MAX_TOKENS {
+        return Err(Error::TooManyTokens(MAX_TOKENS).into());
+    }
+
+    // 서명을 확인하기 전이라 믿을 수 없음, secret key를 찾는 데만 사용함
+    let payloads = tokens
+        .iter()
+        .map(|x| AccessToken::deserialize_payload(x))
+        .collect::<Vec<_>>();
+
+    let token_ids = payloads.iter().flatten().map(|x| x.id).collect::<Vec<_>>();
+
+    // token마다 GET하지 않고 MGET 한번으로 가져옴
+    let secret_keys = repository
+        .secret_key()
+        .get_many(&token_ids)
+        .await?
+        .into_iter()
+        .zip(token_ids)
+        .filter_map(|(secret_key, token_id)| Some((token_id, secret_key?)))
+        .collect::<HashMap<_, _>>();
+
+    let access_tokens = tokens.iter().zip(payloads).map(|(token, payload)| {
+        let SecretKey(secret_key) = secret_keys.get(&payload?.id)?;
+
+        let claims = AccessToken::deserialize(token, secret_key, true)?.claims;
+
+        Some(Verified {
+            user_id: claims.user_id,
+            token_id: claims.id,
+            exp: Some(claims.exp),
+            scopes: claims.scopes,
+        })
+    });
+
+    // api key는 secret의 hash를 확인해야 해서 하나씩 가져옴
+    let api_keys = future::try_join_all(tokens.iter().map(|token| {
+        let repository = &repository;
+
+        async move {
+            if ApiKey::is_api_key(token) {
+                verify_api_key(token, repository.api_key()).await
+            } else {
+                Ok(None)
+            }
+        }
+    }))
+    .await?;
+
+    let verified = access_tokens
+        .zip(api_keys)
+        .map(|(access_token, api_key)| access_token.or(api_key))
+        .collect::<Vec<_>>();
+
+    let mut user_ids = verified
+        .iter()
+        .flatten()
+        .map(|x| x.user_id)
+        .collect::<Vec<_>>();
+    user_ids.sort_unstable();
+    user_ids.dedup();
+
+    let users = future::join_all(user_ids.into_iter().map(|user_id| {
+        let command = &command;
+
+        async move { (user_id, command.get_user_info(Either::Left(user_id)).await) }
+    }))
+    .await;
+
+    let mut roles = HashMap::new();
+
+    for (user_id, r) in users {
+        match r {
+            Ok(user) => {
+                roles.insert(user_id, user.role);
+            }
+            // 탈퇴한 유저의 token은 active가 아님
+            Err(crate::Error::UserSdk(user::Error::GetUser(get_user::Error::NotFoundUser))) => {}
+            // 확인하지 못한 유저의 token만 active가 아닌 것으로 보고 나머지는 그대로 응답함
+            Err(err) => {
+                log::warn!("introspect: get user {}: {}", user_id, err);
+            }
+        }
+    }
+
+    let tokens = verified
+        .into_iter()
+        .map(|verified| {
+            let verified = match verified {
+                Some(verified) => verified,
+                None => return Introspection::default(),
+            };
+
+            match roles.get(&verified.user_id) {
+                Some(role) => Introspection {
+                    active: true,
+                    user_id: Some(verified.user_id),
+                    token_id: Some(verified.token_id),
+                    exp: verified.exp,
+                    role: Some(*role),
+                    scope: (!verified.scopes.is_empty()).then(|| verified.scopes.join(" ")),
+                },
+                None => Introspection::default(),
+            }
+        })
+        .collect();
+
+    Ok(Model { tokens })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use madome_sdk::api::user::model::User;
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::command::{self, CommandSet};
+    use crate::entity::{api_key::ApiKey, scope, service_client::ServiceClient, token::Token};
+    use crate::repository::{
+        r#trait::{ApiKeyRepository, SecretKeyRepository, ServiceClientRepository},
+        RepositorySet,
+    };
+    use crate::usecase::{check_access_token, create_service_token};
+
+    use super::{Introspection, Payload, MAX_TOKENS};
+
+    /// service client를 등록하고 client credentials로 service token을 발급받음
+    async fn issue_service_token(
+        repository: Arc<RepositorySet>,
+        command: Arc<CommandSet>,
+        name: &str,
+        scopes: &[&str],
+    ) -> String {
+        let (client, secret) =
+            ServiceClient::new(name, scopes.iter().map(|x| x.to_string()).collect());
+
+        repository.service_client().add(&client).await.unwrap();
+
+        let payload = create_service_token::Payload {
+            grant_type: create_service_token::CLIENT_CREDENTIALS.to_string(),
+            client_id: client.id.clone(),
+            client_secret: secret,
+            scope: None,
+        };
+
+        create_service_token::execute(payload, repository, command)
+            .await
+            .unwrap()
+            .access_token
+    }
+
+    fn user(user_id: Uuid) -> User {
+        User {
+            id: user_id,
+            email: "".to_string(),
+            role: 1,
+            name: "".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn success() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [service_token: String, secret_key: String, user_id: Uuid, token: Token, revoked: Token] ->
+        {
+            service_token = issue_service_token(repository.clone(), command.clone(), "madome-notification", &[scope::AUTH_INTROSPECT]).await;
+
+            secret_key = "secret1234".to_string();
+            user_id = Uuid::new_v4();
+            token = Token::new(user_id);
+            revoked = Token::new(user_id);
+
+            repository.secret_key().add(token.id, &secret_key).await.unwrap();
+
+            command.set_get_user_info(command::tests::GetUser::from(user(user_id)));
+        },
+        {
+            let (access_token, _) = token.serialize(&secret_key).expect("token serialize");
+            // secret key가 지워진 token
+            let (revoked_access_token, _) = revoked.serialize(&secret_key).expect("token serialize");
+
+            let payload = Payload {
+                service_token,
+                tokens: vec![access_token, "invalid".to_string(), revoked_access_token],
+            };
+            let r = super::execute(payload, repository, command).await.unwrap();
+
+            assert_eq!(r.tokens.len(), 3);
+            assert!(r.tokens[0].active);
+            assert_eq!(r.tokens[0].user_id, Some(user_id));
+            assert_eq!(r.tokens[0].token_id, Some(token.id));
+            assert_eq!(r.tokens[0].role, Some(1));
+            assert_eq!(r.tokens[0].scope, None);
+            assert_eq!(r.tokens[1], Introspection::default());
+            assert_eq!(r.tokens[2], Introspection::default());
+
+            let serialized = serde_json::to_value(&r.tokens[1]).unwrap();
+            assert_eq!(serialized, serde_json::json!({ "active": false }));
+        });
+    }
+
+    #[tokio::test]
+    async fn success_api_key() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [service_token: String, user_id: Uuid, api_key: ApiKey, key: String, expired_key: String, unknown_key: String] ->
+        {
+            service_token = issue_service_token(repository.clone(), command.clone(), "madome-notification", &[scope::AUTH_INTROSPECT]).await;
+
+            user_id = Uuid::new_v4();
+
+            let (x, y) = ApiKey::new(user_id, "library", vec!["library:read".to_string(), "library:write".to_string()], None);
+            api_key = x;
+            key = y;
+
+            let (expired, x) = ApiKey::new(user_id, "expired", vec![], Some(-1));
+            expired_key = x;
+
+            // 저장하지 않은 api key
+            let (_, x) = ApiKey::new(user_id, "unknown", vec![], None);
+            unknown_key = x;
+
+            repository.api_key().add(&api_key).await.unwrap();
+            repository.api_key().add(&expired).await.unwrap();
+
+            command.set_get_user_info(command::tests::GetUser::from(user(user_id)));
+        },
+        {
+            let (_, secret) = ApiKey::split(&key).unwrap();
+            let wrong_secret_key = key.replace(secret, "wrong");
+
+            let payload = Payload {
+                service_token,
+                tokens: vec![key, expired_key, unknown_key, wrong_secret_key],
+            };
+            let r = super::execute(payload, repository, command).await.unwrap();
+
+            let expected = Introspection {
+                active: true,
+                user_id: Some(user_id),
+                token_id: Some(api_key.id),
+                exp: None,
+                role: Some(1),
+                scope: Some("library:read library:write".to_string()),
+            };
+
+            assert_eq!(r.tokens.len(), 4);
+            assert_eq!(r.tokens[0], expected);
+            assert_eq!(r.tokens[1], Introspection::default());
+            assert_eq!(r.tokens[2], Introspection::default());
+            assert_eq!(r.tokens[3], Introspection::default());
+        });
+    }
+
+    #[tokio::test]
+    async fn success_user_unavailable() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [service_token: String, secret_key: String, user_id: Uuid, token: Token, unavailable: Token] ->
+        {
+            service_token = issue_service_token(repository.clone(), command.clone(), "madome-notification", &[scope::AUTH_INTROSPECT]).await;
+
+            secret_key = "secret1234".to_string();
+            user_id = Uuid::new_v4();
+            token = Token::new(user_id);
+            // user 서버가 404가 아닌 에러를 응답하는 유저의 token
+            unavailable = Token::new(Uuid::new_v4());
+
+            repository.secret_key().add(token.id, &secret_key).await.unwrap();
+            repository.secret_key().add(unavailable.id, &secret_key).await.unwrap();
+
+            let get_user_info = command::tests::GetUser::from(user(user_id)).with_unavailable(unavailable.user_id);
+
+            command.set_get_user_info(get_user_info);
+        },
+        {
+            let (access_token, _) = token.serialize(&secret_key).expect("token serialize");
+            let (unavailable_access_token, _) = unavailable.serialize(&secret_key).expect("token serialize");
+
+            let payload = Payload {
+                service_token,
+                tokens: vec![unavailable_access_token, access_token],
+            };
+            let r = super::execute(payload, repository, command).await.unwrap();
+
+            assert_eq!(r.tokens.len(), 2);
+            assert_eq!(r.tokens[0], Introspection::default());
+            assert!(r.tokens[1].active);
+            assert_eq!(r.tokens[1].user_id, Some(user_id));
+        });
+    }
+
+    #[tokio::test]
+    async fn error_insufficient_scope() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [service_token: String] ->
+        {
+            service_token = issue_service_token(repository.clone(), command.clone(), "madome-library", &["library:read"]).await;
+        },
+        {
+            let payload = Payload {
+                service_token,
+                tokens: vec![],
+            };
+            let r = super::execute(payload, repository, command)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            let expected: crate::Error =
+                check_access_token::Error::InsufficientScope(scope::AUTH_INTROSPECT.to_string()).into();
+
+            assert_debug!(r, expected);
+        });
+    }
+
+    #[tokio::test]
+    async fn error_too_many_tokens() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [service_token: String] ->
+        {
+            service_token = issue_service_token(repository.clone(), command.clone(), "madome-notification", &[scope::AUTH_INTROSPECT]).await;
+        },
+        {
+            let payload = Payload {
+                service_token,
+                tokens: vec!["token".to_string(); MAX_TOKENS + 1],
+            };
+            let r = super::execute(payload, repository, command)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            let expected: crate::Error = super::Error::TooManyTokens(MAX_TOKENS).into();
+
+            assert_debug!(r, expected);
+        });
+    }
+}
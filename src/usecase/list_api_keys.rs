@@ -0,0 +1,70 @@
+use std::{convert::TryFrom, sync::Arc};
+
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use serde::Serialize;
+use util::http::Cookie;
+
+use crate::{
+    command::CommandSet,
+    entity::api_key::ApiKey,
+    repository::{r#trait::ApiKeyRepository, RepositorySet},
+};
+
+use super::check_access_token;
+
+/// GET /auth/api-keys
+pub struct Payload {
+    pub access_token: String,
+}
+
+impl TryFrom<Request<Body>> for Payload {
+    type Error = crate::Error;
+
+    fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+
+        Ok(Self { access_token })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model {
+    pub api_keys: Vec<ApiKey>,
+}
+
+pub async fn execute(
+    Payload { access_token }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+) -> crate::Result<Model> {
+    let token_data = check_access_token::execute(
+        check_access_token::Payload {
+            access_token,
+            minimum_role: None,
+            validate_exp: true,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![],
+            mfa_required_role: None,
+            role_scopes: None,
+        },
+        repository.clone(),
+        command,
+    )
+    .await?;
+
+    let mut api_keys = repository
+        .api_key()
+        .list(token_data.user_id)
+        .await?
+        .into_iter()
+        .map(ApiKey::without_secret)
+        .collect::<Vec<_>>();
+
+    api_keys.sort_by_key(|x| std::cmp::Reverse(x.created_at));
+
+    Ok(Model { api_keys })
+}
@@ -0,0 +1,216 @@
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
+
+use either::Either;
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use serde::Serialize;
+use util::http::Cookie;
+use uuid::Uuid;
+
+use crate::{
+    audit::AuditSinkKind,
+    command::CommandSet,
+    config::Config,
+    entity::audit::AuditEvent,
+    error::UseCaseError,
+    msg,
+    repository::{r#trait::AuditRepository, RepositorySet},
+};
+
+use super::check_access_token;
+
+pub const DEFAULT_COUNT: usize = 50;
+pub const MAX_COUNT: usize = 500;
+
+/// GET /auth/admin/audit?user_id= | ?email= [&count=]
+pub struct Payload {
+    pub access_token: String,
+    pub user: Either<Uuid, String>,
+    pub count: usize,
+}
+
+impl TryFrom<Request<Body>> for Payload {
+    type Error = crate::Error;
+
+    fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+        let qs = querystring::querify(request.uri().query().unwrap_or(""))
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+        let user = msg::user_id_or_email(&qs)?;
+        let count = qs
+            .get("count")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_COUNT)
+            .min(MAX_COUNT);
+
+        Ok(Self {
+            access_token,
+            user,
+            count,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model {
+    pub events: Vec<AuditEvent>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// AUDIT_SINK가 redis가 아니면 읽을 곳이 없음
+    #[error("Unsupported audit sink: {0:?}")]
+    UnsupportedAuditSink(AuditSinkKind),
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload {
+        access_token,
+        user,
+        count,
+    }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    check_access_token::execute(
+        check_access_token::Payload::admin(access_token, &config),
+        repository.clone(),
+        command.clone(),
+    )
+    .await?;
+
+    // 빈 목록을 주면 audit log가 없는 것처럼 보임
+    match config.audit_sink() {
+        AuditSinkKind::Redis => {}
+        kind => return Err(Error::UnsupportedAuditSink(kind).into()),
+    }
+
+    let user = command.get_user_info(user).await?;
+
+    let events = repository.audit().list(user.id, count).await?;
+
+    Ok(Model { events })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use either::Either;
+    use madome_sdk::api::user::model::User;
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::audit::AuditSinkKind;
+    use crate::command::{self, CommandSet};
+    use crate::config::{Config, DEFAULT_ADMIN_ROLE};
+    use crate::entity::{
+        audit::{AuditEvent, AuditKind},
+        session::Client,
+    };
+    use crate::repository::RepositorySet;
+    use crate::usecase::{create_token_pair, list_audit_events};
+
+    fn user(id: Uuid) -> User {
+        User {
+            id,
+            email: "admin@madome.app".to_string(),
+            role: DEFAULT_ADMIN_ROLE,
+            name: "".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn success() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(command::tests::GetUser::from(user(user_id)));
+
+            repository.add_audit_event(AuditEvent::new(AuditKind::AuthcodeVerified).user_id(user_id));
+            repository.add_audit_event(AuditEvent::new(AuditKind::TokenPairIssued).user_id(user_id));
+            repository.add_audit_event(AuditEvent::new(AuditKind::TokenPairIssued).user_id(Uuid::new_v4()));
+        },
+        {
+            let payload = create_token_pair::Payload {
+                subject: create_token_pair::Subject::UserId(user_id),
+                client: Client::default(),
+            };
+            let r = create_token_pair::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+            let mut config = Config::default();
+            config.set_audit_sink(AuditSinkKind::Redis);
+
+            let payload = list_audit_events::Payload {
+                access_token: r.access_token,
+                user: Either::Left(user_id),
+                count: list_audit_events::DEFAULT_COUNT,
+            };
+            let r = list_audit_events::execute(payload, repository, command, Arc::new(config))
+                .await
+                .unwrap();
+
+            // 최근 것부터
+            let kinds = r.events.iter().map(|x| x.kind).collect::<Vec<_>>();
+            assert_eq!(kinds, [AuditKind::TokenPairIssued, AuditKind::AuthcodeVerified]);
+        });
+    }
+
+    #[tokio::test]
+    async fn error_unsupported_audit_sink() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(command::tests::GetUser::from(user(user_id)));
+        },
+        {
+            let payload = create_token_pair::Payload {
+                subject: create_token_pair::Subject::UserId(user_id),
+                client: Client::default(),
+            };
+            let r = create_token_pair::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+            let payload = list_audit_events::Payload {
+                access_token: r.access_token,
+                user: Either::Left(user_id),
+                count: list_audit_events::DEFAULT_COUNT,
+            };
+            let r = list_audit_events::execute(payload, repository, command, Arc::new(Config::default()))
+                .await
+                .expect_err("expected error, but returns ok");
+
+            let expected: crate::Error =
+                list_audit_events::Error::UnsupportedAuditSink(AuditSinkKind::Stdout).into();
+
+            assert_debug!(r, expected);
+        });
+    }
+}
@@ -0,0 +1,175 @@
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
+
+use either::Either;
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use serde::Serialize;
+use util::http::Cookie;
+use uuid::Uuid;
+
+use crate::{
+    command::CommandSet,
+    config::Config,
+    entity::session::Session,
+    msg,
+    repository::{r#trait::SessionRepository, RepositorySet},
+};
+
+use super::check_access_token;
+
+/// GET /auth/admin/sessions?user_id= | ?email=
+pub struct Payload {
+    pub access_token: String,
+    pub user: Either<Uuid, String>,
+}
+
+impl TryFrom<Request<Body>> for Payload {
+    type Error = crate::Error;
+
+    fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+        let qs = querystring::querify(request.uri().query().unwrap_or(""))
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+        let user = msg::user_id_or_email(&qs)?;
+
+        Ok(Self { access_token, user })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model {
+    pub user_id: Uuid,
+    pub sessions: Vec<Session>,
+}
+
+pub async fn execute(
+    Payload { access_token, user }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    check_access_token::execute(
+        check_access_token::Payload::admin(access_token, &config),
+        repository.clone(),
+        command.clone(),
+    )
+    .await?;
+
+    let user = command.get_user_info(user).await?;
+
+    let mut sessions = repository.session().list(user.id).await?;
+
+    sessions.sort_by_key(|x| std::cmp::Reverse(x.created_at));
+
+    Ok(Model {
+        user_id: user.id,
+        sessions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use either::Either;
+    use madome_sdk::api::user::model::User;
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::command::{self, CommandSet};
+    use crate::config::{Config, DEFAULT_ADMIN_ROLE};
+    use crate::entity::session::Client;
+    use crate::repository::RepositorySet;
+    use crate::usecase::{check_access_token, create_token_pair, list_sessions};
+
+    fn user(id: Uuid, role: u8) -> User {
+        User {
+            id,
+            email: "admin@madome.app".to_string(),
+            role,
+            name: "".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn success() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(command::tests::GetUser::from(user(user_id, DEFAULT_ADMIN_ROLE)));
+        },
+        {
+            let mut access_tokens = Vec::new();
+
+            for _ in 0..2 {
+                let payload = create_token_pair::Payload {
+                    subject: create_token_pair::Subject::UserId(user_id),
+                    client: Client::default(),
+                };
+                let r = create_token_pair::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+                access_tokens.push(r.access_token);
+            }
+
+            let payload = list_sessions::Payload {
+                access_token: access_tokens[0].clone(),
+                user: Either::Left(user_id),
+            };
+            let r = list_sessions::execute(payload, repository, command, Arc::new(Config::default()))
+                .await
+                .unwrap();
+
+            assert_eq!(r.user_id, user_id);
+            assert_eq!(r.sessions.len(), 2);
+            // 최근 것부터
+            assert!(r.sessions[0].created_at >= r.sessions[1].created_at);
+        });
+    }
+
+    #[tokio::test]
+    async fn error_permission_denied() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(command::tests::GetUser::from(user(user_id, 0)));
+        },
+        {
+            let payload = create_token_pair::Payload {
+                subject: create_token_pair::Subject::UserId(user_id),
+                client: Client::default(),
+            };
+            let r = create_token_pair::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+            let payload = list_sessions::Payload {
+                access_token: r.access_token,
+                user: Either::Left(user_id),
+            };
+            let r = list_sessions::execute(payload, repository, command, Arc::new(Config::default()))
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::PermissionDenied));
+        });
+    }
+}
@@ -2,8 +2,27 @@ pub mod check_access_token;
 pub mod check_and_refresh_token_pair;
 pub mod check_authcode;
 pub mod check_refresh_token;
+pub mod check_service_token;
 pub mod check_token_pair;
+pub mod check_totp;
+pub mod clear_authcodes;
+pub mod create_api_key;
 pub mod create_authcode;
+pub mod create_service_token;
 pub mod create_token_pair;
+pub mod create_totp;
+pub mod delete_api_key;
 pub mod delete_token_pair;
+pub mod enable_totp;
+pub mod finish_passkey_authentication;
+pub mod finish_passkey_registration;
+pub mod introspect_tokens;
+pub mod list_api_keys;
+pub mod list_audit_events;
+pub mod list_sessions;
+pub mod notify_new_login;
 pub mod refresh_token_pair;
+pub mod revoke_session;
+pub mod revoke_sessions;
+pub mod start_passkey_authentication;
+pub mod start_passkey_registration;
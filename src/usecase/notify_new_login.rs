@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use either::Either;
+use uuid::Uuid;
+
+use crate::{
+    command::{CommandSet, Mail},
+    config::Config,
+    entity::session::{Client, RevokeLink},
+    repository::{r#trait::KnownDeviceRepository, RepositorySet},
+};
+
+#[derive(Debug)]
+pub struct Payload {
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    pub client: Client,
+}
+
+#[derive(Debug)]
+pub struct Model {
+    pub notified: bool,
+}
+
+/// 처음 보는 기기나 IP에서 로그인했다면 알림 메일을 보냄
+pub async fn execute(
+    Payload {
+        user_id,
+        session_id,
+        client,
+    }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    let known_device_repository = repository.known_device();
+
+    // 기록된 기기가 하나도 없다면 기록만 하고 알리지 않음
+    let has_any = known_device_repository.has_any(user_id).await?;
+
+    let mut is_new = false;
+
+    for key in client.known_device_keys() {
+        is_new |= known_device_repository.add(user_id, &key).await?;
+    }
+
+    if !has_any || !is_new {
+        return Ok(Model { notified: false });
+    }
+
+    let user = command.get_user_info(Either::Left(user_id)).await?;
+
+    let revoke_link =
+        RevokeLink::new(user_id, session_id).serialize(&config.session_revoke_secret());
+
+    let device = match &client.device_name {
+        Some(device_name) => format!("{} ({})", client.user_agent_family(), device_name),
+        None => client.user_agent_family().to_string(),
+    };
+
+    let mail = Mail::NewLogin {
+        device,
+        ip: client.ip.unwrap_or_default(),
+        time: Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        revoke_url: format!(
+            "{}/auth/sessions/revoke?token={}",
+            config.madome_auth_url(),
+            revoke_link
+        ),
+    };
+
+    // mail에는 session을 끊을 수 있는 revoke link가 있으므로 남기지 않음
+    log::debug!(
+        "new login: email = {} session_id = {}",
+        user.email,
+        session_id
+    );
+
+    #[cfg(feature = "aws-ses")]
+    {
+        command.send_email(user.email, mail).await?;
+    }
+
+    #[cfg(not(feature = "aws-ses"))]
+    drop(mail);
+
+    Ok(Model { notified: true })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use madome_sdk::api::user::model::User;
+    use sai::{Component, System};
+    use util::test_registry;
+    use uuid::Uuid;
+
+    use crate::command::{self, CommandSet};
+    use crate::config::Config;
+    use crate::entity::session::Client;
+    use crate::repository::RepositorySet;
+    use crate::usecase::notify_new_login::{self, Payload};
+
+    #[tokio::test]
+    async fn notify_only_unknown_device() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid, config: Arc<Config>] ->
+        {
+            user_id = Uuid::new_v4();
+
+            let get_user_info = command::tests::GetUser::from(User {
+                id: user_id,
+                email: "login@madome.app".to_string(),
+                role: 0,
+                name: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now()
+            });
+
+            command.set_get_user_info(get_user_info);
+
+            let mut c = Config::default();
+            c.set_session_revoke("https://api.madome.app", "secret");
+            config = Arc::new(c);
+        },
+        {
+            let client = |ip: &str| Client {
+                user_agent: Some("Mozilla/5.0 Firefox/100.0".to_string()),
+                ip: Some(ip.to_string()),
+                device_name: None,
+            };
+
+            let mut notified = Vec::new();
+
+            for ip in ["127.0.0.1", "127.0.0.1", "127.0.0.2"] {
+                let payload = Payload {
+                    user_id,
+                    session_id: Uuid::new_v4(),
+                    client: client(ip),
+                };
+
+                let model = notify_new_login::execute(payload, repository.clone(), command.clone(), config.clone())
+                    .await
+                    .expect("notify new login");
+
+                notified.push(model.notified);
+            }
+
+            // 첫 로그인은 기록만 하고, 같은 기기는 알리지 않음
+            assert_eq!(notified, vec![false, false, true]);
+        });
+    }
+}
@@ -7,8 +7,13 @@ use uuid::Uuid;
 
 use crate::{
     command::CommandSet,
+    entity::session::Client,
     error::UseCaseError,
-    repository::{r#trait::SecretKeyRepository, RepositorySet},
+    metrics, msg,
+    repository::{
+        r#trait::{SecretKeyRepository, SessionRepository},
+        RepositorySet,
+    },
 };
 
 use super::{check_token_pair, create_token_pair};
@@ -17,6 +22,18 @@ use super::{check_token_pair, create_token_pair};
 pub struct Payload {
     pub access_token: String,
     pub refresh_token: String,
+    pub client: Client,
+    /// true이면 user agent가 바뀐 refresh를 warn log로 남김
+    pub warn_suspicious: bool,
+}
+
+impl Payload {
+    pub fn with_warn_suspicious(self, warn_suspicious: bool) -> Self {
+        Self {
+            warn_suspicious,
+            ..self
+        }
+    }
 }
 
 impl TryFrom<Request<Body>> for Payload {
@@ -27,10 +44,13 @@ impl TryFrom<Request<Body>> for Payload {
 
         let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
         let refresh_token = cookie.take(MADOME_REFRESH_TOKEN).unwrap_or_default();
+        let client = msg::client(&request);
 
         Ok(Self {
             access_token,
             refresh_token,
+            client,
+            warn_suspicious: false,
         })
     }
 }
@@ -58,6 +78,8 @@ pub async fn execute(
     Payload {
         access_token,
         refresh_token,
+        client,
+        warn_suspicious,
     }: Payload,
     repository: Arc<RepositorySet>,
     command: Arc<CommandSet>,
@@ -76,13 +98,41 @@ pub async fn execute(
         return Err(Error::CannotRemovedSecretKey.into());
     }
 
+    let prev_session = repository.session().get(token_data.token_id).await?;
+
+    if let Some(prev_session) = &prev_session {
+        if warn_suspicious && prev_session.client.suspicious_change(&client) {
+            log::warn!(
+                "suspicious refresh: user_id = {} token_id = {} user_agent = {} -> {} ip = {:?} -> {:?}",
+                token_data.user_id,
+                token_data.token_id,
+                prev_session.client.user_agent_family(),
+                client.user_agent_family(),
+                prev_session.client.ip,
+                client.ip
+            );
+        }
+
+        repository.session().remove(token_data.token_id).await?;
+    }
+
     let t = create_token_pair::execute(
-        create_token_pair::Payload::UserId(token_data.user_id),
+        create_token_pair::Payload {
+            subject: create_token_pair::Subject::Refresh {
+                user_id: token_data.user_id,
+                amr: token_data.amr,
+                auth_time: token_data.auth_time,
+                session: prev_session,
+            },
+            client,
+        },
         repository.clone(),
         command.clone(),
     )
     .await?;
 
+    metrics::TOKEN_REFRESHES.inc();
+
     Ok(Model {
         access_token: t.access_token,
         refresh_token: t.refresh_token,
@@ -92,4 +142,58 @@ pub async fn execute(
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use chrono::Utc;
+    use sai::{Component, System};
+    use util::test_registry;
+    use uuid::Uuid;
+
+    use crate::{
+        command::CommandSet,
+        entity::{
+            session::Client,
+            token::{amr, AccessToken, Token},
+        },
+        repository::{r#trait::SecretKeyRepository, RepositorySet},
+        usecase::refresh_token_pair::{self, Payload},
+    };
+
+    #[tokio::test]
+    async fn success_keeps_auth_time() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [secret_key: String, token: Token, auth_time: i64] ->
+        {
+            secret_key = "secret1234".to_string();
+            auth_time = Utc::now().timestamp() - 60 * 60;
+            token = Token::new(Uuid::new_v4())
+                .with_amr(vec![amr::EMAIL.to_string(), amr::OTP.to_string(), amr::MFA.to_string()])
+                .with_auth_time(auth_time);
+
+            repository.secret_key().add(token.id, &secret_key).await.unwrap();
+        },
+        {
+            let (access_token, refresh_token) = token.serialize(&secret_key).expect("token serialize");
+
+            let payload = Payload {
+                access_token,
+                refresh_token,
+                client: Client::default(),
+                warn_suspicious: false,
+            };
+            let r = refresh_token_pair::execute(payload, repository, command).await.unwrap();
+
+            assert_ne!(r.token_id, token.id);
+
+            // 갱신은 다시 인증한 게 아니므로 auth_time과 amr을 그대로 가져감
+            let claims = AccessToken::deserialize_payload(&r.access_token).expect("access token");
+
+            assert_eq!(claims.auth_time, auth_time);
+            assert_eq!(claims.amr, token.amr);
+        });
+    }
+}
@@ -0,0 +1,172 @@
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
+
+use hyper::{Body, Request};
+
+use crate::{
+    command::CommandSet,
+    config::Config,
+    entity::{
+        audit::{AuditEvent, AuditKind},
+        session::RevokeLink,
+    },
+    error::UseCaseError,
+    metrics,
+    repository::{
+        r#trait::{SecretKeyRepository, SessionRepository},
+        RepositorySet,
+    },
+};
+
+/// 새 기기 로그인 알림 메일의 "본인이 아닙니다" 링크
+pub struct Payload {
+    pub token: String,
+}
+
+impl TryFrom<Request<Body>> for Payload {
+    type Error = crate::Error;
+
+    fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
+        let qs = querystring::querify(request.uri().query().unwrap_or(""))
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let token = qs.get("token").map(|v| v.to_string()).unwrap_or_default();
+
+        Ok(Self { token })
+    }
+}
+
+#[derive(Debug)]
+pub struct Model;
+
+/// GET으로 들어오면 revoke하지 않고 POST할 수 있는 확인 페이지를 보여줌
+#[derive(Debug)]
+pub struct Confirmation;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid revoke link")]
+    InvalidRevokeLink,
+
+    #[error("Not found session")]
+    NotFoundSession,
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload { token }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    let link = RevokeLink::deserialize(&token, &config.session_revoke_secret())
+        .ok_or(Error::InvalidRevokeLink)?;
+
+    // refresh하면 token id가 바뀌므로 session id로 찾음
+    let session = repository
+        .session()
+        .list(link.user_id)
+        .await?
+        .into_iter()
+        .find(|x| x.id == link.session_id)
+        .ok_or(Error::NotFoundSession)?;
+
+    repository.secret_key().remove(session.token_id).await?;
+
+    repository.session().remove(session.token_id).await?;
+
+    metrics::TOKEN_REVOCATIONS
+        .with_label_values(&["revoke_link"])
+        .inc();
+
+    command
+        .audit(
+            AuditEvent::new(AuditKind::TokenPairRevoked)
+                .user_id(session.user_id)
+                .token_id(session.token_id)
+                .detail("revoke_link"),
+        )
+        .await;
+
+    Ok(Model)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::command::CommandSet;
+    use crate::config::Config;
+    use crate::entity::session::{Client, RevokeLink, Session};
+    use crate::repository::{r#trait::SessionRepository, RepositorySet};
+    use crate::usecase::revoke_session::{self, Payload};
+
+    #[tokio::test]
+    async fn success() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [session: Session, config: Arc<Config>] ->
+        {
+            session = Session::new(Uuid::new_v4(), Uuid::new_v4(), Client::default());
+
+            repository.session().add(&session).await.unwrap();
+
+            let mut c = Config::default();
+            c.set_session_revoke("https://api.madome.app", "secret");
+            config = Arc::new(c);
+        },
+        {
+            let token = RevokeLink::new(session.user_id, session.id).serialize("secret");
+
+            revoke_session::execute(Payload { token }, repository.clone(), command, config)
+                .await
+                .expect("revoke session");
+
+            let r = repository.session().get(session.token_id).await.unwrap();
+
+            assert!(r.is_none());
+        });
+    }
+
+    #[tokio::test]
+    async fn error_invalid_revoke_link() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [session: Session, config: Arc<Config>] ->
+        {
+            session = Session::new(Uuid::new_v4(), Uuid::new_v4(), Client::default());
+
+            repository.session().add(&session).await.unwrap();
+
+            let mut c = Config::default();
+            c.set_session_revoke("https://api.madome.app", "secret");
+            config = Arc::new(c);
+        },
+        {
+            let token = RevokeLink::new(session.user_id, session.id).serialize("other secret");
+
+            let r = revoke_session::execute(Payload { token }, repository.clone(), command, config)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(revoke_session::Error::InvalidRevokeLink));
+        });
+    }
+}
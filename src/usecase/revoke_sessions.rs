@@ -0,0 +1,226 @@
+use std::{collections::HashMap, convert::TryFrom, sync::Arc};
+
+use either::Either;
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use serde::Serialize;
+use util::http::Cookie;
+use uuid::Uuid;
+
+use crate::{
+    command::CommandSet,
+    config::Config,
+    entity::audit::{AuditEvent, AuditKind},
+    metrics, msg,
+    repository::{
+        r#trait::{SecretKeyRepository, SessionRepository},
+        RepositorySet,
+    },
+};
+
+use super::check_access_token;
+
+/// DELETE /auth/admin/sessions?user_id= | ?email= [&session_id=]
+///
+/// session_id가 없으면 유저의 모든 session을 지움
+pub struct Payload {
+    pub access_token: String,
+    pub user: Either<Uuid, String>,
+    pub session_id: Option<Uuid>,
+}
+
+impl TryFrom<Request<Body>> for Payload {
+    type Error = crate::Error;
+
+    fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+        let qs = querystring::querify(request.uri().query().unwrap_or(""))
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+        let user = msg::user_id_or_email(&qs)?;
+        // DELETE /auth/admin/sessions/{session_id} 또는 ?session_id=
+        // 잘못된 값을 없는 것으로 보면 모든 session을 지우게 됨
+        let session_id = msg::param(&request, "session_id")
+            .or_else(|| qs.get("session_id").copied())
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| msg::Error::RequiredQuery("session_id"))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            access_token,
+            user,
+            session_id,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model {
+    pub revoked: usize,
+}
+
+pub async fn execute(
+    Payload {
+        access_token,
+        user,
+        session_id,
+    }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    let admin = check_access_token::execute(
+        check_access_token::Payload::admin(access_token, &config),
+        repository.clone(),
+        command.clone(),
+    )
+    .await?;
+
+    let user = command.get_user_info(user).await?;
+
+    let sessions = repository
+        .session()
+        .list(user.id)
+        .await?
+        .into_iter()
+        .filter(|x| session_id.map(|id| id == x.id).unwrap_or(true));
+
+    let mut revoked = 0;
+
+    for session in sessions {
+        repository.secret_key().remove(session.token_id).await?;
+        repository.session().remove(session.token_id).await?;
+
+        metrics::TOKEN_REVOCATIONS
+            .with_label_values(&["admin"])
+            .inc();
+
+        command
+            .audit(
+                AuditEvent::new(AuditKind::TokenPairRevoked)
+                    .user_id(user.id)
+                    .token_id(session.token_id)
+                    .detail(format!("admin = {}", admin.user_id)),
+            )
+            .await;
+
+        revoked += 1;
+    }
+
+    Ok(Model { revoked })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use either::Either;
+    use madome_sdk::api::user::model::User;
+    use sai::{Component, System};
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::command::{self, CommandSet};
+    use crate::config::{Config, DEFAULT_ADMIN_ROLE};
+    use crate::entity::session::Client;
+    use crate::repository::{r#trait::SessionRepository, RepositorySet};
+    use crate::usecase::{check_access_token, create_token_pair, revoke_sessions};
+
+    fn user(id: Uuid, role: u8) -> User {
+        User {
+            id,
+            email: "admin@madome.app".to_string(),
+            role,
+            name: "".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn success() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(command::tests::GetUser::from(user(user_id, DEFAULT_ADMIN_ROLE)));
+        },
+        {
+            let mut access_tokens = Vec::new();
+
+            for _ in 0..2 {
+                let payload = create_token_pair::Payload {
+                    subject: create_token_pair::Subject::UserId(user_id),
+                    client: Client::default(),
+                };
+                let r = create_token_pair::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+                access_tokens.push(r.access_token);
+            }
+
+            let payload = revoke_sessions::Payload {
+                access_token: access_tokens[0].clone(),
+                user: Either::Left(user_id),
+                session_id: None,
+            };
+            let r = revoke_sessions::execute(payload, repository.clone(), command.clone(), Arc::new(Config::default()))
+                .await
+                .unwrap();
+
+            assert_eq!(r.revoked, 2);
+
+            let sessions = repository.session().list(user_id).await.unwrap();
+
+            assert!(sessions.is_empty());
+        });
+    }
+
+    #[tokio::test]
+    async fn error_permission_denied() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [user_id: Uuid] ->
+        {
+            user_id = Uuid::new_v4();
+
+            command.set_get_user_info(command::tests::GetUser::from(user(user_id, 0)));
+        },
+        {
+            let payload = create_token_pair::Payload {
+                subject: create_token_pair::Subject::UserId(user_id),
+                client: Client::default(),
+            };
+            let r = create_token_pair::execute(payload, repository.clone(), command.clone()).await.unwrap();
+
+            let payload = revoke_sessions::Payload {
+                access_token: r.access_token,
+                user: Either::Left(user_id),
+                session_id: None,
+            };
+            let r = revoke_sessions::execute(payload, repository.clone(), command, Arc::new(Config::default()))
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(check_access_token::Error::PermissionDenied));
+
+            let sessions = repository.session().list(user_id).await.unwrap();
+
+            assert_eq!(sessions.len(), 1);
+        });
+    }
+}
@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use either::Either;
+use madome_sdk::api::user::{self, get_user};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use webauthn_rs::prelude::WebauthnError;
+
+use crate::{
+    command::CommandSet,
+    config::Config,
+    entity::passkey::{self, RequestChallengeResponse},
+    error::UseCaseError,
+    repository::{r#trait::PasskeyRepository, RepositorySet},
+};
+
+#[derive(Deserialize)]
+pub struct Payload {
+    #[serde(rename = "email")]
+    pub user_email: String,
+}
+
+/// 가입 여부나 passkey 유무와 상관없이 같은 모양으로 응답함
+#[derive(Debug, Serialize)]
+pub struct Model {
+    /// 로그인을 끝낼 때 credential과 함께 보내야 함
+    pub ceremony_id: Uuid,
+
+    #[serde(flatten)]
+    pub challenge: Challenge,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Challenge {
+    Passkey(RequestChallengeResponse),
+    /// passkey::decoy_challenge
+    Decoy(Value),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Webauthn: {0}")]
+    Webauthn(WebauthnError),
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload { user_email }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    let ceremony_id = Uuid::new_v4();
+
+    let user = match command
+        .get_user_info(Either::Right(user_email.clone()))
+        .await
+    {
+        Ok(user) => Some(user),
+        Err(crate::Error::UserSdk(user::Error::GetUser(get_user::Error::NotFoundUser))) => None,
+        Err(err) => return Err(err),
+    };
+
+    let passkeys = match &user {
+        Some(user) => repository.passkey().list(user.id).await?,
+        None => vec![],
+    };
+
+    let user = match user {
+        Some(user) if !passkeys.is_empty() => user,
+        // state를 저장하지 않으므로 이 ceremony id로는 로그인할 수 없음
+        _ => {
+            return Ok(Model {
+                ceremony_id,
+                challenge: Challenge::Decoy(passkey::decoy_challenge(
+                    config.webauthn_rp_id(),
+                    config.passkey_decoy_secret(),
+                    &user_email,
+                )),
+            })
+        }
+    };
+
+    let (challenge, state) = config
+        .webauthn()
+        .start_passkey_authentication(&passkeys)
+        .map_err(Error::Webauthn)?;
+
+    repository
+        .passkey()
+        .add_authentication(ceremony_id, user.id, &state)
+        .await?;
+
+    Ok(Model {
+        ceremony_id,
+        challenge: Challenge::Passkey(challenge),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+    use madome_sdk::api::user::model::User;
+    use sai::{Component, System};
+    use serde_json::json;
+    use util::{assert_debug, test_registry};
+    use uuid::Uuid;
+
+    use crate::command::{self, CommandSet};
+    use crate::config::Config;
+    use crate::entity::session::Client;
+    use crate::repository::RepositorySet;
+    use crate::usecase::{
+        finish_passkey_authentication,
+        start_passkey_authentication::{self, Payload},
+    };
+
+    const DECOY_SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+    fn config(decoy_secret: &str) -> Arc<Config> {
+        let mut config = Config::default();
+        config.set_webauthn("madome.app", "https://madome.app");
+        config.set_passkey_decoy_secret(decoy_secret);
+        Arc::new(config)
+    }
+
+    #[tokio::test]
+    async fn uniform_response() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [config: Arc<Config>] ->
+        {
+            // passkey를 등록하지 않은 유저
+            let get_user_info = command::tests::GetUser::from(User {
+                id: Uuid::new_v4(),
+                email: "passkey@madome.app".to_string(),
+                role: 0,
+                name: "".to_string(),
+                created_at: Utc::now(),
+                updated_at: Utc::now()
+            });
+
+            command.set_get_user_info(get_user_info);
+
+            config = self::config(DECOY_SECRET);
+        },
+        {
+            let start = |user_email: &str, config: Arc<Config>| {
+                let payload = Payload {
+                    user_email: user_email.to_string(),
+                };
+
+                start_passkey_authentication::execute(payload, repository.clone(), command.clone(), config)
+            };
+
+            let without_passkey = start("passkey@madome.app", config.clone()).await.unwrap();
+            let unknown = start("unknown@madome.app", config.clone()).await.unwrap();
+            // 재시작했거나 다른 replica가 응답한 경우
+            let restarted = start("unknown@madome.app", self::config(DECOY_SECRET)).await.unwrap();
+            let other_secret = start(
+                "unknown@madome.app",
+                self::config("fedcba9876543210fedcba9876543210"),
+            )
+            .await
+            .unwrap();
+
+            let without_passkey = serde_json::to_value(without_passkey).unwrap();
+            let unknown = serde_json::to_value(unknown).unwrap();
+            let again = serde_json::to_value(restarted).unwrap();
+            let other_secret = serde_json::to_value(other_secret).unwrap();
+
+            let keys = |x: &serde_json::Value| {
+                x["publicKey"].as_object().unwrap().keys().cloned().collect::<Vec<_>>()
+            };
+
+            assert_eq!(keys(&without_passkey), keys(&unknown));
+            assert_eq!(unknown["publicKey"]["rpId"], "madome.app");
+            assert_eq!(unknown["publicKey"]["allowCredentials"].as_array().unwrap().len(), 1);
+            assert_ne!(unknown["ceremony_id"], again["ceremony_id"]);
+            assert_ne!(unknown["publicKey"]["challenge"], again["publicKey"]["challenge"]);
+            // 같은 email이면 같은 credential id를 받아야 진짜와 구분할 수 없음
+            assert_eq!(
+                unknown["publicKey"]["allowCredentials"],
+                again["publicKey"]["allowCredentials"]
+            );
+            assert_ne!(
+                unknown["publicKey"]["allowCredentials"],
+                without_passkey["publicKey"]["allowCredentials"]
+            );
+            assert_ne!(
+                unknown["publicKey"]["allowCredentials"],
+                other_secret["publicKey"]["allowCredentials"]
+            );
+        });
+    }
+
+    #[tokio::test]
+    async fn error_invalid_credential_with_decoy_ceremony() {
+        let mut test = System::<TestRegistry>::new();
+
+        test.start().await;
+
+        test_registry!(
+        [repository: RepositorySet, command: CommandSet] ->
+        [config: Arc<Config>] ->
+        {
+            config = self::config(DECOY_SECRET);
+        },
+        {
+            let payload = Payload {
+                user_email: "unknown@madome.app".to_string(),
+            };
+            let start_passkey_authentication::Model { ceremony_id, .. } =
+                start_passkey_authentication::execute(payload, repository.clone(), command, config.clone())
+                    .await
+                    .unwrap();
+
+            let credential = json!({
+                "id": "AAAA",
+                "rawId": "AAAA",
+                "response": {
+                    "authenticatorData": "AAAA",
+                    "clientDataJSON": "AAAA",
+                    "signature": "AAAA",
+                    "userHandle": null,
+                },
+                "type": "public-key",
+                "extensions": {},
+            });
+
+            let payload = finish_passkey_authentication::Payload {
+                ceremony_id,
+                credential: serde_json::from_value(credential).unwrap(),
+                client: Client::default(),
+            };
+            let r = finish_passkey_authentication::execute(payload, repository, config)
+                .await
+                .expect_err("expected error, but returns ok");
+
+            assert_debug!(r, crate::Error::from(finish_passkey_authentication::Error::InvalidCredential));
+        });
+    }
+}
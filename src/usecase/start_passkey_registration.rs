@@ -0,0 +1,102 @@
+use std::{convert::TryFrom, sync::Arc};
+
+use either::Either;
+use hyper::{Body, Request};
+use madome_sdk::api::cookie::MADOME_ACCESS_TOKEN;
+use serde::Serialize;
+use util::http::Cookie;
+use webauthn_rs::prelude::WebauthnError;
+
+use crate::{
+    command::CommandSet,
+    config::Config,
+    entity::passkey::{self, CreationChallengeResponse},
+    error::UseCaseError,
+    repository::{r#trait::PasskeyRepository, RepositorySet},
+};
+
+use super::check_access_token;
+
+pub struct Payload {
+    pub access_token: String,
+}
+
+impl TryFrom<Request<Body>> for Payload {
+    type Error = crate::Error;
+
+    fn try_from(request: Request<Body>) -> Result<Self, Self::Error> {
+        let mut cookie = Cookie::from(&request);
+
+        let access_token = cookie.take(MADOME_ACCESS_TOKEN).unwrap_or_default();
+
+        Ok(Self { access_token })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Model(pub CreationChallengeResponse);
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Webauthn: {0}")]
+    Webauthn(WebauthnError),
+}
+
+impl From<Error> for crate::Error {
+    fn from(err: Error) -> Self {
+        UseCaseError::from(err).into()
+    }
+}
+
+pub async fn execute(
+    Payload { access_token }: Payload,
+    repository: Arc<RepositorySet>,
+    command: Arc<CommandSet>,
+    config: Arc<Config>,
+) -> crate::Result<Model> {
+    let token_data = check_access_token::execute(
+        check_access_token::Payload {
+            access_token,
+            minimum_role: None,
+            validate_exp: true,
+            require_mfa: false,
+            max_age: None,
+            scopes: vec![],
+            mfa_required_role: None,
+            role_scopes: None,
+        },
+        repository.clone(),
+        command.clone(),
+    )
+    .await?;
+
+    let user = command
+        .get_user_info(Either::Left(token_data.user_id))
+        .await?;
+
+    // 이미 등록된 passkey는 다시 등록하지 못하게 함
+    let exclude_credentials = repository
+        .passkey()
+        .list(user.id)
+        .await?
+        .iter()
+        .map(|x| x.cred_id().clone())
+        .collect::<Vec<_>>();
+
+    let (challenge, state) = config
+        .webauthn()
+        .start_passkey_registration(
+            passkey::user_unique_id(user.id),
+            &user.email,
+            &user.name,
+            Some(exclude_credentials),
+        )
+        .map_err(Error::Webauthn)?;
+
+    repository
+        .passkey()
+        .add_registration(user.id, &state)
+        .await?;
+
+    Ok(Model(challenge))
+}